@@ -4,10 +4,16 @@
 //! It defines the main application struct and the main loop.
 //!
 
-use std::sync::Arc;
+use std::{
+    any::TypeId,
+    cell::RefCell,
+    sync::{Arc, Once},
+    time::{Duration, Instant},
+};
 
-use plugin::Plugin;
-use thndr_ecs::prelude::*;
+use plugin::{Plugin, PluginError, PluginResult};
+use thndr_ecs::{event::Event, prelude::*};
+use tracing::{error, warn};
 
 /// A plugin is a way to add functionality to the application.
 pub mod plugin;
@@ -22,44 +28,466 @@ pub trait ScheduleRunner {
 
 /// The default runner for the application.
 /// This runner is used when no other runner is specified.
-#[derive(Default, Debug)]
-pub struct DefaultRunner;
+///
+/// By default it runs frames back to back, as fast as possible, e.g. `app.set_runner(DefaultRunner::default())`.
+/// Use [DefaultRunner::with_target_fps] to cap the frame rate, e.g. for a dedicated server.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct DefaultRunner {
+    /// The minimum duration of a single frame, or `None` to run as fast as possible.
+    pub frame_budget: Option<Duration>,
+    /// How much of the end of each frame is spent spinning instead of sleeping, see [DefaultRunner::with_spin_threshold].
+    pub spin_threshold: Duration,
+}
+
+impl DefaultRunner {
+    /// Creates a new [DefaultRunner], running as fast as possible.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Caps the runner at `fps` frames per second. A target of `0` removes the cap.
+    ///
+    /// Whatever is left of a frame's budget is slept away before the next frame starts,
+    /// so `Time` deltas reflect the paced rate.
+    ///
+    /// *Note*: [std::thread::sleep] only guarantees to sleep *at least* the requested time.
+    /// Depending on the platform's timer resolution it can overshoot by a millisecond or more
+    /// (up to ~15ms on some Windows setups). Use [DefaultRunner::with_spin_threshold] if you need
+    /// tighter pacing.
+    pub fn with_target_fps(mut self, fps: u32) -> Self {
+        self.frame_budget = (fps > 0).then(|| Duration::from_secs_f64(1.0 / fps as f64));
+
+        self
+    }
+
+    /// Spin for the last `threshold` of every frame instead of sleeping.
+    /// This trades a bit of CPU time for more accurate frame pacing, 1ms is usually enough.
+    ///
+    /// Only has an effect together with [DefaultRunner::with_target_fps].
+    pub fn with_spin_threshold(mut self, threshold: Duration) -> Self {
+        self.spin_threshold = threshold;
+
+        self
+    }
+}
+
+/// Waits until `deadline`, sleeping until `spin_threshold` before it, and spinning for the rest.
+fn wait_until(deadline: Instant, spin_threshold: Duration) {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if remaining > spin_threshold {
+        std::thread::sleep(remaining - spin_threshold);
+    }
+
+    while Instant::now() < deadline {
+        std::hint::spin_loop();
+    }
+}
 
 impl ScheduleRunner for DefaultRunner {
     fn run_app(&mut self, app: &mut App) {
-        let mut startup = app.startup.build();
-        let mut pre_update = app.pre_update.build();
-        let mut update = app.update.build();
-        let mut post_update = app.post_update.build();
+        let mut schedules = Schedules::new(app);
 
-        for system in &app.direct_startup {
-            system(&mut app.world);
-        }
-        startup.execute((&mut app.world,)).expect("Startup failed");
+        schedules.run_startup(app);
 
         loop {
-            for system in &app.direct_pre_update {
-                system(&mut app.world);
+            let frame_start = Instant::now();
+
+            if StepMode::advance(&mut app.world) {
+                schedules.run_update(app);
+                schedules.run_render(app);
+            }
+
+            match self.frame_budget {
+                Some(frame_budget) => wait_until(frame_start + frame_budget, self.spin_threshold),
+                // don't spin a whole core while paused
+                None if StepMode::is_paused(&mut app.world) => {
+                    std::thread::sleep(Duration::from_millis(10))
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+/// A singleton component for stepping through the game one frame at a time while debugging, like a debugger steps
+/// through code. Without one, the game is always [StepMode::Running].
+///
+/// Runners call [StepMode::advance] before every frame, and skip the frame when paused. The window runners keep handling
+/// window events while paused, so the window stays responsive, and `thndr_window`'s `StepKeys` can bind keys to pause and step.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepMode {
+    /// Frames run as usual.
+    #[default]
+    Running,
+    /// No frames run.
+    Paused,
+    /// Exactly one frame runs, after which the mode reverts to [StepMode::Paused].
+    StepOnce,
+}
+
+impl StepMode {
+    /// Returns whether the next frame should run, according to the [StepMode] in the `world`.
+    ///
+    /// Consumes a [StepMode::StepOnce], so the systems of the stepped frame already see [StepMode::Paused].
+    pub fn advance(world: &mut World) -> bool {
+        let mut query = world.query::<&mut StepMode>();
+        let Ok(mode) = query.single_mut() else {
+            return true;
+        };
+
+        match *mode {
+            StepMode::Running => true,
+            StepMode::Paused => false,
+            StepMode::StepOnce => {
+                *mode = StepMode::Paused;
+                true
+            }
+        }
+    }
+
+    /// Check if the [StepMode] in the `world` is [StepMode::Paused].
+    pub fn is_paused(world: &mut World) -> bool {
+        world
+            .query::<&StepMode>()
+            .single_mut()
+            .is_ok_and(|mode| *mode == StepMode::Paused)
+    }
+}
+
+/// How long the stages of the last frame took, measured by the [Schedules], see [Schedules::frame_timings].
+/// Every stage includes its direct systems, and the custom schedules positioned around it.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameTimings {
+    /// The whole frame, from the start of pre-update to the end of render.
+    /// Includes whatever the runner did in between, e.g. handling window events.
+    pub total: Duration,
+    /// The pre-update stage.
+    pub pre_update: Duration,
+    /// The update stage.
+    pub update: Duration,
+    /// The post-update stage.
+    pub post_update: Duration,
+    /// The render stage.
+    pub render: Duration,
+}
+
+impl FrameTimings {
+    /// Get the stage that took the longest, and how long it took.
+    pub fn slowest(&self) -> (Stage, Duration) {
+        [
+            (Stage::PreUpdate, self.pre_update),
+            (Stage::Update, self.update),
+            (Stage::PostUpdate, self.post_update),
+            (Stage::Render, self.render),
+        ]
+        .into_iter()
+        .fold((Stage::PreUpdate, Duration::ZERO), |slowest, stage| {
+            if stage.1 > slowest.1 {
+                stage
+            } else {
+                slowest
             }
-            pre_update
-                .execute((&mut app.world,))
-                .expect("Pre-update failed");
+        })
+    }
+}
+
+/// A singleton component that logs a warning whenever a frame takes longer than the [FrameBudget::target],
+/// to catch performance regressions during development. Without one, frames aren't checked. See [App::set_frame_budget].
+///
+/// The warning includes the slowest stage. Warnings are throttled, so a sustained slow period logs one warning
+/// every [FrameBudget::throttle], counting the slow frames in between, instead of one for every frame.
+#[derive(Debug, Clone)]
+pub struct FrameBudget {
+    /// The longest a frame may take, e.g. 16ms for 60 FPS.
+    pub target: Duration,
+    /// The least time between two warnings. Defaults to one second.
+    pub throttle: Duration,
+    last_warning: Option<Instant>,
+    suppressed: u32,
+}
+
+impl FrameBudget {
+    /// Creates a new [FrameBudget] of `target` per frame.
+    pub fn new(target: Duration) -> Self {
+        Self {
+            target,
+            throttle: Duration::from_secs(1),
+            last_warning: None,
+            suppressed: 0,
+        }
+    }
+
+    /// Sets the [FrameBudget::throttle].
+    pub fn with_throttle(mut self, throttle: Duration) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Checks a frame that ended at `now` against the budget. Returns the warning to log, if the frame is over budget
+    /// and the last warning is at least [FrameBudget::throttle] ago.
+    pub fn check(&mut self, timings: &FrameTimings, now: Instant) -> Option<String> {
+        if timings.total <= self.target {
+            return None;
+        }
 
-            for system in &app.direct_update {
-                system(&mut app.world);
+        if let Some(last_warning) = self.last_warning {
+            if now.duration_since(last_warning) < self.throttle {
+                self.suppressed += 1;
+                return None;
             }
-            update.execute((&mut app.world,)).expect("Update failed");
+        }
+
+        let (stage, duration) = timings.slowest();
+        let mut warning = format!(
+            "Frame took {:.2}ms, over the budget of {:.2}ms. The slowest stage was {stage:?} with {:.2}ms",
+            timings.total.as_secs_f64() * 1000.0,
+            self.target.as_secs_f64() * 1000.0,
+            duration.as_secs_f64() * 1000.0,
+        );
+        if self.suppressed > 0 {
+            warning += &format!(
+                ", and {} more frames went over budget since the last warning",
+                self.suppressed
+            );
+        }
+
+        self.last_warning = Some(now);
+        self.suppressed = 0;
+
+        Some(warning)
+    }
+}
 
-            for system in &app.direct_post_update {
-                system(&mut app.world);
+/// One of the schedules of an [App], in the order they run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// Runs once, before the first frame.
+    Startup,
+    /// Runs at the start of every frame.
+    PreUpdate,
+    /// Runs every frame, after pre-update.
+    Update,
+    /// Runs every frame, after update.
+    PostUpdate,
+    /// Runs whenever the runner presents a frame, after post-update.
+    Render,
+}
+
+/// Where a custom schedule, added with [App::add_schedule], runs relative to the built-in [Stage]s.
+///
+/// Custom schedules at the same position run in the order they were added.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SchedulePosition {
+    /// Runs right before the stage, so before its direct systems as well.
+    /// Before [Stage::Startup], that's before the startup systems of plugins too.
+    Before(Stage),
+    /// Runs right after the stage.
+    After(Stage),
+    /// Only runs when [Schedules::run_schedule] is called, e.g. by a custom [ScheduleRunner].
+    #[default]
+    Manual,
+}
+
+/// An event that is spawned once by [Schedules::run_startup], after every startup system ran and before anything
+/// of the first frame, including the custom schedules before pre-update. Every [ScheduleRunner] built on [Schedules]
+/// spawns it, no plugin is needed.
+///
+/// It comes with an [Event], so it can be observed and is cleaned up like any other event once `thndr_event`'s
+/// cleanup runs. Query for it in a system, e.g. to check that everything the game needs was created during startup.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppStartupCompleteEvent;
+
+/// The built schedules of an [App], ready to be executed by a [ScheduleRunner].
+pub struct Schedules {
+    startup: Schedule,
+    pre_update: Schedule,
+    update: Schedule,
+    post_update: Schedule,
+    render: Schedule,
+    custom: Vec<(&'static str, SchedulePosition, Schedule)>,
+    timings: FrameTimings,
+    frame_start: Option<Instant>,
+}
+
+impl Schedules {
+    /// Builds the schedules of the `app`.
+    pub fn new(app: &mut App) -> Self {
+        Self {
+            startup: app.startup.build(),
+            pre_update: app.pre_update.build(),
+            update: app.update.build(),
+            post_update: app.post_update.build(),
+            render: app.render.build(),
+            custom: app
+                .custom
+                .iter_mut()
+                .map(|(label, position, schedule)| (*label, *position, schedule.build()))
+                .collect(),
+            timings: FrameTimings::default(),
+            frame_start: None,
+        }
+    }
+
+    /// Get how long the stages of the last frame took.
+    pub fn frame_timings(&self) -> FrameTimings {
+        self.timings
+    }
+
+    /// Checks the last frame against every [FrameBudget], and logs their warnings.
+    fn check_frame_budget(&self, world: &mut World) {
+        let now = Instant::now();
+        for (_, budget) in world.query_mut::<&mut FrameBudget>() {
+            if let Some(warning) = budget.check(&self.timings, now) {
+                warn!("{warning}");
             }
-            post_update
-                .execute((&mut app.world,))
-                .expect("Post-update failed");
+        }
+    }
+
+    /// Runs the custom schedules at `position`.
+    fn run_custom(&mut self, position: SchedulePosition, world: &mut World) {
+        for (label, _, schedule) in self
+            .custom
+            .iter_mut()
+            .filter(|(_, custom, _)| *custom == position)
+        {
+            schedule
+                .execute((&mut *world,))
+                .unwrap_or_else(|error| panic!("Schedule {label} failed: {error:?}"));
+        }
+    }
+
+    /// Runs the custom schedule named `label`, no matter its [SchedulePosition].
+    /// Used by runners for [SchedulePosition::Manual] schedules, e.g. an editor pass that only runs while editing.
+    ///
+    /// *Note*: Direct systems only get the `World`, so they can't run schedules. Give the schedule a position instead.
+    ///
+    /// # Panics
+    ///
+    /// If there is no schedule named `label`.
+    pub fn run_schedule(&mut self, label: &str, app: &mut App) {
+        let (_, _, schedule) = self
+            .custom
+            .iter_mut()
+            .find(|(custom, _, _)| *custom == label)
+            .unwrap_or_else(|| panic!("There is no schedule named {label}"));
+
+        schedule
+            .execute((&mut app.world,))
+            .unwrap_or_else(|error| panic!("Schedule {label} failed: {error:?}"));
+    }
+
+    /// Runs the direct startup systems, followed by the startup schedule.
+    /// Spawns the [AppStartupCompleteEvent] once everything ran.
+    pub fn run_startup(&mut self, app: &mut App) {
+        self.run_custom(SchedulePosition::Before(Stage::Startup), &mut app.world);
+        for system in &app.direct_startup {
+            system(&mut app.world);
+        }
+        self.startup
+            .execute((&mut app.world,))
+            .expect("Startup failed");
+        self.run_custom(SchedulePosition::After(Stage::Startup), &mut app.world);
+
+        app.world.spawn((Event::default(), AppStartupCompleteEvent));
+    }
+
+    /// Runs the pre-update, update and post-update schedules, each preceded by their direct systems.
+    /// If a [NextWorld] was spawned during the last frame, it is swapped in first, see [App::set_world].
+    ///
+    /// The [Commands] of a schedule are applied at the end of that schedule, so an entity spawned in pre-update
+    /// is queryable in update during the same frame, and direct systems always see the commands of earlier schedules.
+    /// Within a schedule, commands are only applied at the end. If a later system in the same schedule needs to see them,
+    /// add a flush point between the two systems, e.g. with `app.update.flush()`.
+    pub fn run_update(&mut self, app: &mut App) {
+        let frame_start = Instant::now();
+        self.frame_start = Some(frame_start);
+
+        let next = app
+            .world
+            .query_mut::<&NextWorld>()
+            .into_iter()
+            .next()
+            .map(|(entity, _)| entity);
+        if let Some(next) = next.and_then(|entity| app.world.remove_one::<NextWorld>(entity).ok()) {
+            app.set_world(next.0);
+        }
+
+        self.run_custom(SchedulePosition::Before(Stage::PreUpdate), &mut app.world);
+        for system in &app.direct_pre_update {
+            system(&mut app.world);
+        }
+        self.pre_update
+            .execute((&mut app.world,))
+            .expect("Pre-update failed");
+        self.run_custom(SchedulePosition::After(Stage::PreUpdate), &mut app.world);
+        let pre_update_end = Instant::now();
+        self.timings.pre_update = pre_update_end - frame_start;
+
+        self.run_custom(SchedulePosition::Before(Stage::Update), &mut app.world);
+        for system in &app.direct_update {
+            system(&mut app.world);
+        }
+        self.update
+            .execute((&mut app.world,))
+            .expect("Update failed");
+        self.run_custom(SchedulePosition::After(Stage::Update), &mut app.world);
+        let update_end = Instant::now();
+        self.timings.update = update_end - pre_update_end;
+
+        self.run_custom(SchedulePosition::Before(Stage::PostUpdate), &mut app.world);
+        for system in &app.direct_post_update {
+            system(&mut app.world);
+        }
+        self.post_update
+            .execute((&mut app.world,))
+            .expect("Post-update failed");
+        self.run_custom(SchedulePosition::After(Stage::PostUpdate), &mut app.world);
+        self.timings.post_update = update_end.elapsed();
+    }
+
+    /// Runs the direct render systems, followed by the render schedule.
+    ///
+    /// This ends the frame, so afterwards the [FrameBudget] is checked. A render without an update before it,
+    /// e.g. a redraw requested by the platform while paused, isn't a frame and isn't checked.
+    pub fn run_render(&mut self, app: &mut App) {
+        let render_start = Instant::now();
+        self.run_custom(SchedulePosition::Before(Stage::Render), &mut app.world);
+        for system in &app.direct_render {
+            system(&mut app.world);
+        }
+        self.render
+            .execute((&mut app.world,))
+            .expect("Render failed");
+        self.run_custom(SchedulePosition::After(Stage::Render), &mut app.world);
+        self.timings.render = render_start.elapsed();
+
+        if let Some(frame_start) = self.frame_start.take() {
+            self.timings.total = frame_start.elapsed();
+            self.check_frame_budget(&mut app.world);
         }
     }
 }
 
+/// A system that records `capacity` empty commands on its first run, so the command buffer of its schedule
+/// reaches that capacity at once, see [App::with_command_capacity]. Later runs only check that this happened.
+fn reserve_commands(capacity: usize) -> impl Fn(Commands) + Send + Sync {
+    let reserved = Once::new();
+
+    move |mut commands: Commands| {
+        reserved.call_once(|| {
+            for _ in 0..capacity {
+                commands.write(|_: &mut World| {});
+            }
+        });
+    }
+}
+
+/// A singleton component with a world to swap in before the next frame, e.g. the next level.
+///
+/// A loading screen builds the next world in the background, on the [TaskPool], and spawns it once it is done.
+/// The [Schedules] swap it in with [App::set_world] at the start of the next frame, after all commands were applied.
+pub struct NextWorld(pub World);
+
 /// The main application struct.
 ///
 /// This struct is the main entry point for the application.
@@ -83,10 +511,32 @@ pub struct App {
     pub post_update: ScheduleBuilder,
     /// The direct post-update systems.
     pub direct_post_update: Vec<Box<dyn Fn(&mut World)>>,
+    /// The render schedule.
+    pub render: ScheduleBuilder,
+    /// The direct render systems.
+    pub direct_render: Vec<Box<dyn Fn(&mut World)>>,
     /// The runner for the application.
     pub runner: Option<Box<dyn ScheduleRunner>>,
     /// The plugins for the application.
     pub plugins: Vec<Arc<dyn Plugin>>,
+    /// The number of commands every schedule's command buffer is sized for, see [App::with_command_capacity].
+    /// `0` leaves the buffers to grow as needed.
+    pub command_capacity: usize,
+    /// The registered resources, and how to find the entities holding them, see [App::register_resource].
+    ///
+    /// [App::set_world] uses this to carry the resources over to the new world.
+    pub resources: Vec<(TypeId, fn(&World) -> Vec<Entity>)>,
+    /// The custom schedules, with their labels and positions, see [App::add_schedule].
+    pub custom: Vec<(&'static str, SchedulePosition, ScheduleBuilder)>,
+}
+
+/// Get the entities holding a `T`, usually a single one.
+fn resource_entities<T: Component>(world: &World) -> Vec<Entity> {
+    world
+        .query::<&T>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect()
 }
 
 impl App {
@@ -98,13 +548,54 @@ impl App {
             pre_update: ScheduleBuilder::new(),
             update: ScheduleBuilder::new(),
             post_update: ScheduleBuilder::new(),
+            render: ScheduleBuilder::new(),
             direct_startup: Vec::new(),
             direct_pre_update: Vec::new(),
             direct_update: Vec::new(),
             direct_post_update: Vec::new(),
+            direct_render: Vec::new(),
             runner: None,
             plugins: Vec::new(),
+            command_capacity: 0,
+            resources: Vec::new(),
+            custom: Vec::new(),
+        }
+    }
+
+    /// Create a new application, with the command buffer of every schedule sized for `capacity` commands,
+    /// e.g. for a particle system spawning and despawning thousands of entities per frame.
+    ///
+    /// The buffers are reused across frames either way, see [Commands], but without this they grow during
+    /// the first frames, reallocating every time they double. Here, every schedule starts with a system that records
+    /// `capacity` empty commands on its first run, before any other system of the schedule records anything.
+    /// After that first run it only checks a [Once]. Flushing still drains the buffers, so no commands are kept around.
+    ///
+    /// *Note*: Covers the built-in schedules, including the one the startup systems of plugins are built into,
+    /// and every custom schedule added later, see [App::add_schedule].
+    /// The components of spawned entities are still stored by the buffers as before.
+    pub fn with_command_capacity(capacity: usize) -> Self {
+        let mut app = Self {
+            command_capacity: capacity,
+            ..Self::new()
+        };
+
+        app.startup = app.schedule_builder();
+        app.pre_update = app.schedule_builder();
+        app.update = app.schedule_builder();
+        app.post_update = app.schedule_builder();
+        app.render = app.schedule_builder();
+
+        app
+    }
+
+    /// Creates an empty schedule builder, which starts by reserving the [App::command_capacity], if there is one.
+    fn schedule_builder(&self) -> ScheduleBuilder {
+        let mut builder = ScheduleBuilder::new();
+        if self.command_capacity > 0 {
+            builder.add_system(reserve_commands(self.command_capacity));
         }
+
+        builder
     }
 
     /// Add a plugin to the application.
@@ -118,6 +609,9 @@ impl App {
     }
 
     /// Add a system to the startup schedule.
+    ///
+    /// Startup systems added by plugins always run before the ones added by the user,
+    /// see [App::run] for the full ordering.
     pub fn add_startup_system<Args, Ret, S>(&mut self, system: S) -> &mut Self
     where
         S: 'static + System<Args, Ret> + Send,
@@ -157,6 +651,72 @@ impl App {
         self
     }
 
+    /// Add a system to the render schedule.
+    ///
+    /// Rendering is separate from the simulation: the render schedule runs after post-update,
+    /// whenever the runner presents a frame. With the [thndr_window::WindowRunner] that is in response to
+    /// the window's redraw request, which the runner makes after every update.
+    pub fn add_render_system<Args, Ret, S>(&mut self, system: S) -> &mut Self
+    where
+        S: 'static + System<Args, Ret> + Send,
+    {
+        self.render.add_system(system);
+
+        self
+    }
+
+    /// Add a custom schedule named `label`, that runs at `position`, e.g. a networking plugin receiving
+    /// before `update` and sending after it:
+    ///
+    /// ```ignore
+    /// app.add_schedule("NetworkReceive", SchedulePosition::Before(Stage::Update))
+    ///     .add_schedule("NetworkSend", SchedulePosition::After(Stage::Update))
+    ///     .add_system_to("NetworkReceive", receive_packets)
+    ///     .add_system_to("NetworkSend", send_packets);
+    /// ```
+    ///
+    /// Adding a schedule that already exists only moves it to `position`, its systems are kept.
+    pub fn add_schedule(&mut self, label: &'static str, position: SchedulePosition) -> &mut Self {
+        self.custom_schedule(label).1 = position;
+
+        self
+    }
+
+    /// Add a system to the custom schedule named `label`.
+    ///
+    /// If the schedule wasn't added with [App::add_schedule] (yet), it is added as [SchedulePosition::Manual].
+    /// So systems can be added before the plugin that positions the schedule is built.
+    pub fn add_system_to<Args, Ret, S>(&mut self, label: &'static str, system: S) -> &mut Self
+    where
+        S: 'static + System<Args, Ret> + Send,
+    {
+        self.custom_schedule(label).2.add_system(system);
+
+        self
+    }
+
+    /// Get the custom schedule named `label`, adding it as [SchedulePosition::Manual] if it doesn't exist.
+    fn custom_schedule(
+        &mut self,
+        label: &'static str,
+    ) -> &mut (&'static str, SchedulePosition, ScheduleBuilder) {
+        let index = match self
+            .custom
+            .iter()
+            .position(|(custom, _, _)| *custom == label)
+        {
+            Some(index) => index,
+            None => {
+                let schedule = self.schedule_builder();
+                self.custom
+                    .push((label, SchedulePosition::Manual, schedule));
+                self.custom.len() - 1
+            }
+        };
+
+        &mut self.custom[index]
+    }
+
     /// Add a direct system to the startup schedule.
     pub fn add_direct_startup_system<F>(&mut self, system: F) -> &mut Self
     where
@@ -197,6 +757,120 @@ impl App {
         self
     }
 
+    /// Add a direct system to the render schedule.
+    pub fn add_direct_render_system<F>(&mut self, system: F) -> &mut Self
+    where
+        F: 'static + Fn(&mut World),
+    {
+        self.direct_render.push(Box::new(system));
+
+        self
+    }
+
+    /// Add a direct system to `stage`, that only runs the first time the stage runs, and is skipped after that.
+    ///
+    /// Unlike a startup system, it can rely on everything plugins set up during startup, including the commands
+    /// of their startup systems. E.g. creating GPU pipelines once the device, created by a plugin at startup, exists:
+    ///
+    /// ```ignore
+    /// app.add_run_once_system(Stage::Update, |world| {
+    ///     let pipelines = create_pipelines(world);
+    ///     world.spawn((pipelines,));
+    /// });
+    /// ```
+    pub fn add_run_once_system<F>(&mut self, stage: Stage, system: F) -> &mut Self
+    where
+        F: 'static + FnOnce(&mut World),
+    {
+        let system = RefCell::new(Some(system));
+        let system = move |world: &mut World| {
+            if let Some(system) = system.borrow_mut().take() {
+                system(world);
+            }
+        };
+
+        match stage {
+            Stage::Startup => self.add_direct_startup_system(system),
+            Stage::PreUpdate => self.add_direct_pre_update_system(system),
+            Stage::Update => self.add_direct_update_system(system),
+            Stage::PostUpdate => self.add_direct_post_update_system(system),
+            Stage::Render => self.add_direct_render_system(system),
+        }
+    }
+
+    /// Register `T` as a resource, a singleton component like `Time`, so it survives [App::set_world].
+    /// Plugins register the resources their systems rely on. Registering a type more than once has no effect.
+    pub fn register_resource<T: Component>(&mut self) -> &mut Self {
+        let id = TypeId::of::<T>();
+        if !self.resources.iter().any(|(resource, _)| *resource == id) {
+            self.resources.push((id, resource_entities::<T>));
+        }
+
+        self
+    }
+
+    /// Run the engine's parallel systems, e.g. transform propagation, in a dedicated pool of `threads` worker threads,
+    /// instead of rayon's global pool. The pool is spawned as a [ComputePool] singleton, replacing any earlier one.
+    ///
+    /// By default, the global pool is used, which has one thread per logical CPU. That's the fastest for a single game,
+    /// but a dedicated server running many instances on one machine caps each of them, e.g. to 2 threads,
+    /// and a game can leave headroom for its audio and render threads. `0` also uses one thread per logical CPU,
+    /// but in a pool of its own, with threads named `thndr-compute-{i}`.
+    ///
+    /// *Note*: Only systems that run their `par_*` iterators with [ComputePool::install_in] are bounded.
+    pub fn set_thread_pool_size(&mut self, threads: usize) -> &mut Self {
+        for pool in resource_entities::<ComputePool>(&self.world) {
+            let _ = self.world.despawn(pool);
+        }
+        self.world.spawn((ComputePool::new(Some(threads)),));
+
+        self.register_resource::<ComputePool>()
+    }
+
+    /// Log a warning whenever a frame takes longer than `target`, see [FrameBudget].
+    /// The budget is spawned as a singleton component, replacing any earlier one. Without it, no warnings are logged.
+    pub fn set_frame_budget(&mut self, target: Duration) -> &mut Self {
+        for budget in resource_entities::<FrameBudget>(&self.world) {
+            let _ = self.world.despawn(budget);
+        }
+        self.world.spawn((FrameBudget::new(target),));
+
+        self.register_resource::<FrameBudget>()
+    }
+
+    /// Replace the world with `world`, e.g. when loading the next level. Systems, direct systems and plugins are
+    /// untouched, they simply run against the new world from now on.
+    ///
+    /// The entities holding registered resources (see [App::register_resource]) are moved over, unless `world`
+    /// already has a resource of that type. Every other entity is dropped with the old world.
+    /// The moved entities keep all their components, but get new ids, so don't hold on to ids across a swap.
+    ///
+    /// *Note*: Commands are only applied at the end of a schedule, so flush them before swapping, or they are lost.
+    /// Spawning a [NextWorld] instead takes care of that, by swapping at the start of the next frame.
+    pub fn set_world(&mut self, world: World) {
+        let mut old = self.replace_world(world);
+
+        let mut moved = Vec::new();
+        for (_, entities) in &self.resources {
+            if entities(&self.world).is_empty() {
+                moved.extend(entities(&old));
+            }
+        }
+        moved.sort_unstable();
+        moved.dedup();
+
+        for entity in moved {
+            if let Ok(components) = old.take(entity) {
+                self.world.spawn(components);
+            }
+        }
+    }
+
+    /// Replace the world with `world`, and return the old one. Unlike [App::set_world], nothing is carried over.
+    pub fn replace_world(&mut self, world: World) -> World {
+        std::mem::replace(&mut self.world, world)
+    }
+
     /// Set the runner for the application.
     pub fn set_runner<R>(&mut self, runner: R) -> &mut Self
     where
@@ -208,23 +882,639 @@ impl App {
     }
 
     /// Run the application.
+    ///
+    /// Startup runs in the following order:
+    /// 1. direct startup systems added by plugins
+    /// 2. startup systems added by plugins, after which their commands are applied
+    /// 3. direct startup systems added by the user
+    /// 4. startup systems added by the user
+    ///
+    /// So user startup systems can rely on anything a plugin sets up during startup (e.g. `Time`, or the window).
+    ///
+    /// If any plugin fails to build, the errors are logged and this returns without starting the runner.
+    /// Use [App::try_run] to handle the errors, e.g. to exit with an error code.
     pub fn run(&mut self) {
+        let _ = self.try_run();
+    }
+
+    /// Run the application, like [App::run], but return the errors of plugins that failed to build.
+    ///
+    /// All plugins are built before the errors are reported, so every misconfiguration shows up at once.
+    /// Every error is logged with `error!`, and if any plugin failed, the runner is not started.
+    pub fn try_run(&mut self) -> std::result::Result<(), Vec<PluginError>> {
+        // build the plugins into empty startup schedules, so we can put them in front of the user's
+        let plugin_builder = self.schedule_builder();
+        let user_startup = std::mem::replace(&mut self.startup, plugin_builder);
+        let user_direct_startup = std::mem::take(&mut self.direct_startup);
+
+        let mut errors = Vec::new();
         let plugins = self.plugins.clone();
         for plugin in plugins {
-            plugin.build(self);
+            if let Err(error) = plugin.build(self) {
+                error!("Failed to build plugin {}: {}", plugin.name(), error);
+                errors.push(error);
+            }
         }
 
+        if !errors.is_empty() {
+            // drop whatever the plugins added, and hand the user's startup systems back, so the app can be fixed and run again
+            self.startup = user_startup;
+            self.direct_startup = user_direct_startup;
+            return Err(errors);
+        }
+
+        let plugin_startup =
+            RefCell::new(std::mem::replace(&mut self.startup, user_startup).build());
+        let plugin_direct_startup =
+            std::mem::replace(&mut self.direct_startup, user_direct_startup);
+
+        self.direct_startup.insert(
+            0,
+            Box::new(move |world: &mut World| {
+                for system in &plugin_direct_startup {
+                    system(world);
+                }
+                plugin_startup
+                    .borrow_mut()
+                    .execute((world,))
+                    .expect("Plugin startup failed");
+            }),
+        );
+
         let mut runner = self
             .runner
             .take()
-            .unwrap_or_else(|| Box::new(DefaultRunner));
+            .unwrap_or_else(|| Box::new(DefaultRunner::new()));
 
         runner.run_app(self);
+
+        Ok(())
+    }
+}
+
+/// A plugin that spawns a shared [TaskPool], for running expensive work in the background.
+#[derive(Default, Debug)]
+pub struct TaskPoolPlugin {
+    /// The number of worker threads. If `None`, one thread per logical CPU is used.
+    pub threads: Option<usize>,
+}
+
+impl Plugin for TaskPoolPlugin {
+    fn build(&self, app: &mut App) -> PluginResult {
+        app.world.spawn((TaskPool::new(self.threads),));
+        app.register_resource::<TaskPool>();
+
+        Ok(())
     }
 }
 
 /// Common types, traits, and functions.
 pub mod prelude {
-    pub use crate::plugin::Plugin;
-    pub use crate::{App, ScheduleRunner};
+    pub use crate::plugin::{Plugin, PluginError, PluginResult};
+    pub use crate::{
+        App, AppStartupCompleteEvent, DefaultRunner, FrameBudget, FrameTimings, NextWorld,
+        SchedulePosition, ScheduleRunner, Schedules, Stage, StepMode, TaskPoolPlugin,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+    };
+    use thndr_ecs::event::EventStatus;
+
+    /// Runs only the startup of the app.
+    struct StartupRunner;
+
+    impl ScheduleRunner for StartupRunner {
+        fn run_app(&mut self, app: &mut App) {
+            Schedules::new(app).run_startup(app);
+        }
+    }
+
+    struct Resource;
+    struct Found;
+
+    struct ResourcePlugin;
+
+    impl Plugin for ResourcePlugin {
+        fn build(&self, app: &mut App) -> PluginResult {
+            app.add_startup_system(|mut commands: Commands| {
+                commands.spawn((Resource,));
+            });
+
+            Ok(())
+        }
+    }
+
+    struct FailingPlugin;
+
+    impl Plugin for FailingPlugin {
+        fn build(&self, _app: &mut App) -> PluginResult {
+            Err(PluginError::Custom("misconfigured".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_plugin_errors_abort_run() {
+        let mut app = App::new();
+        app.add_plugin(ResourcePlugin)
+            .add_plugin(FailingPlugin)
+            .add_plugin(FailingPlugin)
+            .set_runner(StartupRunner);
+
+        let errors = app.try_run().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].to_string(), "misconfigured");
+
+        // the runner never started, so the startup systems didn't run
+        assert_eq!(app.world.query::<&Resource>().iter().count(), 0);
+
+        // run only logs the errors, and returns instead of exiting the process
+        let mut app = App::new();
+        app.add_plugin(FailingPlugin)
+            .set_runner(StartupRunner)
+            .run();
+        assert_eq!(app.world.len(), 0);
+    }
+
+    #[test]
+    fn test_plugin_errors_keep_user_startup() {
+        let mut app = App::new();
+        app.add_startup_system(|mut commands: Commands| {
+            commands.spawn((Found,));
+        })
+        .add_plugin(FailingPlugin)
+        .set_runner(StartupRunner);
+
+        assert!(app.try_run().is_err());
+
+        // once the failing plugin is gone, the app runs with the user's startup systems intact
+        app.plugins.clear();
+        assert!(app.try_run().is_ok());
+        assert_eq!(app.world.query::<&Found>().iter().count(), 1);
+    }
+
+    fn read_resource(world: SubWorld<&Resource>, mut commands: Commands) {
+        if world.query::<&Resource>().iter().count() == 1 {
+            commands.spawn((Found,));
+        }
+    }
+
+    struct Enemy;
+    struct Seen;
+
+    fn spawn_enemy(mut commands: Commands) {
+        commands.spawn((Enemy,));
+    }
+
+    fn see_enemy(world: SubWorld<&Enemy>, mut commands: Commands) {
+        if world.query::<&Enemy>().iter().count() == 1 {
+            commands.spawn((Seen,));
+        }
+    }
+
+    #[test]
+    fn test_commands_flushed_between_schedules() {
+        let mut app = App::new();
+        app.add_pre_update_system(spawn_enemy)
+            .add_update_system(see_enemy);
+
+        Schedules::new(&mut app).run_update(&mut app);
+
+        assert_eq!(app.world.query::<&Seen>().iter().count(), 1);
+    }
+
+    #[test]
+    fn test_commands_flushed_within_schedule() {
+        let mut app = App::new();
+        app.add_update_system(spawn_enemy);
+        app.update.flush();
+        app.add_update_system(see_enemy);
+
+        Schedules::new(&mut app).run_update(&mut app);
+
+        assert_eq!(app.world.query::<&Seen>().iter().count(), 1);
+    }
+
+    #[test]
+    fn test_plugin_startup_runs_first() {
+        let mut app = App::new();
+        app.add_startup_system(read_resource)
+            .add_direct_startup_system(|world| {
+                assert_eq!(world.query::<&Resource>().iter().count(), 1);
+            })
+            .add_plugin(ResourcePlugin)
+            .set_runner(StartupRunner)
+            .run();
+
+        assert_eq!(app.world.query::<&Found>().iter().count(), 1);
+    }
+
+    #[test]
+    fn test_startup_complete_event() {
+        let mut app = App::new();
+        app.add_direct_startup_system(|world| {
+            assert_eq!(world.query::<&AppStartupCompleteEvent>().iter().count(), 0);
+        })
+        .add_plugin(ResourcePlugin)
+        .set_runner(StartupRunner)
+        .run();
+
+        // spawned once startup is done, as a regular event, without any plugin spawning it
+        let events = app
+            .world
+            .query_mut::<(&Event, &AppStartupCompleteEvent)>()
+            .into_iter()
+            .map(|(_, (event, _))| event.status)
+            .collect::<Vec<_>>();
+        assert_eq!(events, vec![EventStatus::NotHandled]);
+    }
+
+    /// Runs the startup of the app, followed by 10 frames.
+    struct FramesRunner;
+
+    impl ScheduleRunner for FramesRunner {
+        fn run_app(&mut self, app: &mut App) {
+            let mut schedules = Schedules::new(app);
+            schedules.run_startup(app);
+
+            for _ in 0..10 {
+                schedules.run_update(app);
+                schedules.run_render(app);
+            }
+        }
+    }
+
+    struct Runs(u32);
+
+    #[test]
+    fn test_run_once_system() {
+        let mut app = App::new();
+        app.add_run_once_system(Stage::Update, |world| {
+            // the plugin's startup commands have been applied by now
+            assert_eq!(world.query::<&Resource>().iter().count(), 1);
+            world.spawn((Runs(0),));
+        })
+        .add_direct_update_system(|world| {
+            for (_, runs) in world.query_mut::<&mut Runs>() {
+                runs.0 += 1;
+            }
+        })
+        .add_plugin(ResourcePlugin)
+        .set_runner(FramesRunner)
+        .run();
+
+        // the once system ran in the first frame only, before the counting system
+        let runs = app
+            .world
+            .query_mut::<&Runs>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].1 .0, 10);
+    }
+
+    /// Counts the allocations of the current thread while [count_allocations] runs,
+    /// and forwards everything to the system allocator.
+    struct CountingAllocator;
+
+    thread_local! {
+        static COUNTING: Cell<bool> = const { Cell::new(false) };
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    fn record_allocation() {
+        let _ = COUNTING.try_with(|counting| {
+            if counting.get() {
+                let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+            }
+        });
+    }
+
+    // SAFETY: every call is forwarded to the system allocator unchanged
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            record_allocation();
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) }
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            record_allocation();
+            unsafe { System.realloc(ptr, layout, new_size) }
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAllocator = CountingAllocator;
+
+    /// Runs `f`, and returns the number of allocations it made.
+    fn count_allocations(f: impl FnOnce()) -> usize {
+        COUNTING.with(|counting| counting.set(true));
+        let start = ALLOCATIONS.with(Cell::get);
+        f();
+        let allocations = ALLOCATIONS.with(Cell::get) - start;
+        COUNTING.with(|counting| counting.set(false));
+
+        allocations
+    }
+
+    /// The allocations [record_commands] made in every frame.
+    struct RecordingAllocations(Vec<usize>);
+
+    fn record_commands(world: SubWorld<&mut RecordingAllocations>, mut commands: Commands) {
+        // the system counts by itself, as the schedule may run it on any thread
+        let allocations = count_allocations(|| {
+            for _ in 0..1000 {
+                commands.write(|_: &mut World| {});
+            }
+        });
+
+        for (_, recorded) in world.query::<&mut RecordingAllocations>().iter() {
+            recorded.0.push(allocations);
+        }
+    }
+
+    /// Runs 3 frames of `app` with [record_commands], and returns its allocations per frame.
+    fn recording_allocations(mut app: App) -> Vec<usize> {
+        app.world.spawn((RecordingAllocations(Vec::new()),));
+        app.add_update_system(record_commands);
+
+        let mut schedules = Schedules::new(&mut app);
+        schedules.run_startup(&mut app);
+        for _ in 0..3 {
+            schedules.run_update(&mut app);
+        }
+
+        app.world
+            .query_mut::<&RecordingAllocations>()
+            .into_iter()
+            .flat_map(|(_, recorded)| recorded.0.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_command_capacity() {
+        // the buffer grows during the first frame, and is reused after that
+        let allocations = recording_allocations(App::new());
+        assert!(allocations[0] > 0);
+        assert_eq!(allocations[1..], [0, 0]);
+
+        // sized before the first system records, so no frame allocates for the commands
+        let allocations = recording_allocations(App::with_command_capacity(1000));
+        assert_eq!(allocations, [0, 0, 0]);
+    }
+
+    /// Tries to run `frames` frames, like a runner would, and returns the total number of frames that ran.
+    fn step_frames(app: &mut App, schedules: &mut Schedules, frames: u32) -> u32 {
+        for _ in 0..frames {
+            if StepMode::advance(&mut app.world) {
+                schedules.run_update(app);
+            }
+        }
+
+        app.world
+            .query_mut::<&Runs>()
+            .into_iter()
+            .next()
+            .unwrap()
+            .1
+             .0
+    }
+
+    fn set_step_mode(app: &mut App, mode: StepMode) {
+        for (_, step_mode) in app.world.query_mut::<&mut StepMode>() {
+            *step_mode = mode;
+        }
+    }
+
+    #[test]
+    fn test_step_mode() {
+        let mut app = App::new();
+        app.world.spawn((Runs(0),));
+        app.world.spawn((StepMode::Paused,));
+        app.add_direct_update_system(|world| {
+            for (_, runs) in world.query_mut::<&mut Runs>() {
+                runs.0 += 1;
+            }
+        });
+        let mut schedules = Schedules::new(&mut app);
+
+        assert_eq!(step_frames(&mut app, &mut schedules, 5), 0);
+
+        // every step advances exactly one frame, and pauses again
+        for step in 1..=3 {
+            set_step_mode(&mut app, StepMode::StepOnce);
+            assert_eq!(step_frames(&mut app, &mut schedules, 5), step);
+            assert!(StepMode::is_paused(&mut app.world));
+        }
+
+        set_step_mode(&mut app, StepMode::Running);
+        assert_eq!(step_frames(&mut app, &mut schedules, 5), 8);
+    }
+
+    struct Level(u32);
+
+    #[test]
+    fn test_set_world() {
+        let mut app = App::new();
+        app.register_resource::<Runs>().register_resource::<Runs>();
+        app.world.spawn((Runs(3), 1u32));
+        app.world.spawn((StepMode::Running,));
+        let player = app.world.spawn((Level(1),));
+        let mut schedules = Schedules::new(&mut app);
+
+        let mut next = World::new();
+        next.spawn((Level(2),));
+        next.spawn((Level(2),));
+        app.world.spawn((NextWorld(next),));
+        schedules.run_update(&mut app);
+
+        // only the registered resource survives, with all of its components
+        assert!(!app.world.contains(player));
+        assert_eq!(app.world.query_mut::<&StepMode>().into_iter().count(), 0);
+        assert_eq!(app.world.query_mut::<&NextWorld>().into_iter().count(), 0);
+        let levels = app
+            .world
+            .query_mut::<&Level>()
+            .into_iter()
+            .map(|(_, level)| level.0)
+            .collect::<Vec<_>>();
+        assert_eq!(levels, vec![2, 2]);
+        let resource = app
+            .world
+            .query_mut::<(&Runs, &u32)>()
+            .into_iter()
+            .map(|(_, (runs, other))| (runs.0, *other))
+            .collect::<Vec<_>>();
+        assert_eq!(resource, vec![(3, 1)]);
+
+        // a resource in the new world wins over the old one
+        let mut next = World::new();
+        next.spawn((Runs(0),));
+        app.set_world(next);
+        assert_eq!(
+            app.world
+                .query_mut::<&Runs>()
+                .into_iter()
+                .next()
+                .unwrap()
+                .1
+                 .0,
+            0
+        );
+        assert_eq!(app.world.len(), 1);
+    }
+
+    #[test]
+    fn test_thread_pool_size() {
+        let mut app = App::new();
+        app.set_thread_pool_size(4).set_thread_pool_size(2);
+
+        let mut pools = app.world.query::<&ComputePool>();
+        let pool = pools.single_mut().unwrap();
+        assert_eq!(pool.threads(), 2);
+
+        let mut workers = ComputePool::install_in(Some(pool), || {
+            (0..256)
+                .into_par_iter()
+                .map(|_| std::thread::current().name().unwrap().to_string())
+                .collect::<Vec<_>>()
+        });
+        workers.sort_unstable();
+        workers.dedup();
+        assert!(workers.len() <= 2);
+        assert!(workers
+            .iter()
+            .all(|name| name.starts_with("thndr-compute-")));
+    }
+
+    struct Log(Vec<&'static str>);
+
+    fn log(entry: &'static str) -> impl Fn(SubWorld<&mut Log>) + Send + Sync {
+        move |world: SubWorld<&mut Log>| {
+            for (_, log) in world.query::<&mut Log>().iter() {
+                log.0.push(entry);
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_schedules() {
+        let mut app = App::new();
+        app.world.spawn((Log(Vec::new()),));
+        // systems can be added before the schedule is positioned
+        app.add_system_to("NetworkSend", log("send"))
+            .add_schedule("NetworkReceive", SchedulePosition::Before(Stage::Update))
+            .add_schedule("NetworkSend", SchedulePosition::After(Stage::Update))
+            .add_system_to("NetworkReceive", log("receive"))
+            .add_schedule("Editor", SchedulePosition::Manual)
+            .add_system_to("Editor", log("editor"))
+            .add_pre_update_system(log("pre_update"))
+            .add_update_system(log("update"));
+
+        let mut schedules = Schedules::new(&mut app);
+        schedules.run_update(&mut app);
+        let entries = |app: &mut App| {
+            app.world
+                .query_mut::<&Log>()
+                .into_iter()
+                .map(|(_, log)| log.0.clone())
+                .next()
+                .unwrap()
+        };
+        assert_eq!(
+            entries(&mut app),
+            vec!["pre_update", "receive", "update", "send"]
+        );
+
+        // manual schedules only run when asked to
+        schedules.run_schedule("Editor", &mut app);
+        assert_eq!(entries(&mut app).last(), Some(&"editor"));
+        assert_eq!(entries(&mut app).len(), 5);
+    }
+
+    #[test]
+    fn test_command_capacity_custom_schedule() {
+        let mut app = App::with_command_capacity(1000);
+        app.world.spawn((RecordingAllocations(Vec::new()),));
+        app.add_schedule("Particles", SchedulePosition::After(Stage::Update))
+            .add_system_to("Particles", record_commands);
+
+        let mut schedules = Schedules::new(&mut app);
+        for _ in 0..3 {
+            schedules.run_update(&mut app);
+        }
+
+        // schedules added after the app was created are sized as well
+        let allocations = app
+            .world
+            .query_mut::<&RecordingAllocations>()
+            .into_iter()
+            .flat_map(|(_, recorded)| recorded.0.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(allocations, [0, 0, 0]);
+    }
+
+    #[test]
+    fn test_frame_budget() {
+        let mut budget = FrameBudget::new(Duration::from_millis(16));
+        let slow = FrameTimings {
+            total: Duration::from_millis(25),
+            pre_update: Duration::from_millis(1),
+            update: Duration::from_millis(20),
+            post_update: Duration::from_millis(2),
+            render: Duration::from_millis(2),
+        };
+        let fast = FrameTimings {
+            total: Duration::from_millis(10),
+            ..slow
+        };
+        let start = Instant::now();
+
+        assert_eq!(budget.check(&fast, start), None);
+        let warning = budget.check(&slow, start).unwrap();
+        assert!(warning.contains("25.00ms"));
+        assert!(warning.contains("Update with 20.00ms"));
+
+        // a sustained slow period only warns once per second
+        for frame in 1..=3 {
+            let now = start + Duration::from_millis(frame * 25);
+            assert_eq!(budget.check(&slow, now), None);
+        }
+        let warning = budget.check(&slow, start + Duration::from_secs(1)).unwrap();
+        assert!(warning.contains("3 more frames"));
+    }
+
+    fn slow_update(_commands: Commands) {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_slow_frame_timings() {
+        let mut app = App::new();
+        app.set_frame_budget(Duration::from_millis(1))
+            .add_update_system(slow_update);
+
+        let mut schedules = Schedules::new(&mut app);
+        schedules.run_update(&mut app);
+        schedules.run_render(&mut app);
+
+        let timings = schedules.frame_timings();
+        assert!(timings.update >= Duration::from_millis(5));
+        assert!(timings.total >= timings.update);
+        assert_eq!(timings.slowest().0, Stage::Update);
+
+        // the runner already warned about this frame, so the next check within a second is throttled
+        let mut budgets = app.world.query::<&mut FrameBudget>();
+        let budget = budgets.single_mut().unwrap();
+        assert_eq!(budget.check(&timings, Instant::now()), None);
+    }
 }
@@ -1,7 +1,27 @@
+use thiserror::Error;
+
 use crate::App;
 
+/// An error that occurred while building a [Plugin].
+#[derive(Debug, Error)]
+pub enum PluginError {
+    /// A custom error, with a message explaining what went wrong.
+    #[error("{0}")]
+    Custom(String),
+}
+
+/// The result of building a [Plugin].
+pub type PluginResult = Result<(), PluginError>;
+
 /// A plugin adds functionality to the application.
 pub trait Plugin {
     /// Builds the plugin and adds it to the application.
-    fn build(&self, app: &mut App);
+    ///
+    /// Return an error if the plugin is misconfigured, so the app can report it before it starts running.
+    fn build(&self, app: &mut App) -> PluginResult;
+
+    /// The name of the plugin, used when reporting errors. Defaults to the type name.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
 }
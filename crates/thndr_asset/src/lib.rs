@@ -0,0 +1,257 @@
+//! # thndr_asset
+//!
+//! `thndr_asset` provides typed storage for assets, like GPU buffers, textures or audio, for the Thunder Engine.
+//!
+//! An [Assets] store owns the assets, and hands out [Handle]s to them. Components hold the handles instead of
+//! the assets themselves, e.g. a material holds a `Handle<Texture>`, so many entities can share a single texture.
+//!
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+};
+
+/// A handle to an asset of type `T` in an [Assets] store. It's `Copy` and only 8 bytes, so it is cheap to store on components.
+///
+/// Handles are generational: once the asset is removed, the handle is stale and [Assets::get] returns `None`,
+/// even if the slot was reused for another asset in the meantime.
+pub struct Handle<T> {
+    index: u32,
+    generation: u32,
+    // a handle doesn't own a `T`, so it is `Send` and `Sync` no matter the asset type
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    /// Get the index of the slot in the [Assets] store, e.g. for debugging.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Get the generation of the slot, which is bumped every time an asset is removed from it.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+// implemented by hand, as deriving would require `T` to implement them as well
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+        self.generation.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle")
+            .field("index", &self.index)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+/// A slot of an [Assets] store.
+#[derive(Debug)]
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// A store of assets of type `T`, handing out a [Handle] for every asset added to it.
+///
+/// The store owns the assets, so removing one drops it, and every handle to it becomes stale.
+/// Slots of removed assets are reused, so the store doesn't grow when assets are swapped out, e.g. when streaming.
+///
+/// It can be spawned as a singleton component, to share the assets with systems:
+///
+/// ```ignore
+/// let texture = textures.add(Texture::load("grass.png"));
+/// commands.spawn((Material { texture },));
+///
+/// // in the render system
+/// let texture = textures.get(material.texture)?;
+/// ```
+#[derive(Debug)]
+pub struct Assets<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+impl<T> Default for Assets<T> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<T> Assets<T> {
+    /// Creates a new, empty [Assets] store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an asset, and get a handle to it.
+    pub fn add(&mut self, value: T) -> Handle<T> {
+        self.len += 1;
+
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.slots[index as usize].value = Some(value);
+                index
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    value: Some(value),
+                });
+                (self.slots.len() - 1) as u32
+            }
+        };
+
+        Handle {
+            index,
+            generation: self.slots[index as usize].generation,
+            marker: PhantomData,
+        }
+    }
+
+    /// Get the slot of `handle`, if the handle isn't stale.
+    fn slot(&self, handle: Handle<T>) -> Option<&Slot<T>> {
+        self.slots
+            .get(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)
+    }
+
+    /// Get the asset of `handle`. Returns `None` if it was removed.
+    pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+        self.slot(handle)?.value.as_ref()
+    }
+
+    /// Get the asset of `handle` mutably. Returns `None` if it was removed.
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        self.slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)?
+            .value
+            .as_mut()
+    }
+
+    /// Check if the asset of `handle` is still in the store.
+    pub fn contains(&self, handle: Handle<T>) -> bool {
+        self.get(handle).is_some()
+    }
+
+    /// Remove the asset of `handle`, and return it. Every copy of `handle` is stale from now on.
+    /// Returns `None` if it was already removed.
+    pub fn remove(&mut self, handle: Handle<T>) -> Option<T> {
+        let slot = self
+            .slots
+            .get_mut(handle.index as usize)
+            .filter(|slot| slot.generation == handle.generation)?;
+        let value = slot.value.take()?;
+
+        // a slot whose generation would wrap around is retired, so old handles can never match again
+        if let Some(generation) = slot.generation.checked_add(1) {
+            slot.generation = generation;
+            self.free.push(handle.index);
+        }
+        self.len -= 1;
+
+        Some(value)
+    }
+
+    /// Get the number of assets in the store.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the store is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterate over all assets in the store, with their handles.
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let handle = Handle {
+                index: index as u32,
+                generation: slot.generation,
+                marker: PhantomData,
+            };
+
+            slot.value.as_ref().map(|value| (handle, value))
+        })
+    }
+}
+
+/// Common types, traits, and functions.
+pub mod prelude {
+    pub use crate::{Assets, Handle};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_get_remove() {
+        let mut textures = Assets::new();
+        let grass = textures.add("grass");
+        let stone = textures.add("stone");
+        assert_eq!(textures.len(), 2);
+        assert_eq!(textures.get(grass), Some(&"grass"));
+        assert_eq!(textures.get(stone), Some(&"stone"));
+
+        *textures.get_mut(stone).unwrap() = "cobblestone";
+        assert_eq!(textures.get(stone), Some(&"cobblestone"));
+
+        assert_eq!(textures.remove(grass), Some("grass"));
+        assert_eq!(textures.len(), 1);
+        assert_eq!(
+            textures.iter().collect::<Vec<_>>(),
+            vec![(stone, &"cobblestone")]
+        );
+    }
+
+    #[test]
+    fn test_stale_handles() {
+        let mut textures = Assets::new();
+        let grass = textures.add("grass");
+        textures.remove(grass);
+
+        assert_eq!(textures.get(grass), None);
+        assert_eq!(textures.get_mut(grass), None);
+        assert_eq!(textures.remove(grass), None);
+        assert!(textures.is_empty());
+
+        // the slot is reused, but the old handle still doesn't match
+        let sand = textures.add("sand");
+        assert_eq!(sand.index(), grass.index());
+        assert_ne!(sand, grass);
+        assert_eq!(textures.get(grass), None);
+        assert_eq!(textures.get(sand), Some(&"sand"));
+    }
+}
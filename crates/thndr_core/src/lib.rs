@@ -3,15 +3,34 @@
 //! `thndr_core` is the core library for the Thunder Engine.
 //! It's main job is to re-export all the other libraries in the engine.
 //!
+//! ## Features
+//!
+//! | Feature | Default | Adds | Pulls in |
+//! | --- | --- | --- | --- |
+//! | `tracing` | yes | `thndr_tracing` | `tracing`, `tracing-subscriber` |
+//! | `window` | yes | `thndr_window`, implies `tracing` | `winit` |
+//!
+//! With `default-features = false`, only the ECS, app, asset, math, net, time, event, transform, tween and physics crates remain,
+//! whose dependencies are hecs, rayon and glam. That is enough for e.g. a headless simulation or a dedicated server.
+//!
+//! An even smaller tree is possible by depending on `thndr_ecs`, `thndr_app` and `thndr_time` directly,
+//! without `thndr_core`. None of the crates pull in a GPU backend, `thndr_gpu` is always opt-in.
+//!
 
 /// Re-exports all major libraries in the Thunder Engine.
 pub mod prelude {
     pub use thndr_app::prelude::*;
+    pub use thndr_asset::prelude::*;
     pub use thndr_ecs::prelude::*;
     pub use thndr_event::prelude::*;
     pub use thndr_math::prelude::*;
+    pub use thndr_net::prelude::*;
+    pub use thndr_physics::prelude::*;
     pub use thndr_time::prelude::*;
+    #[cfg(feature = "tracing")]
     pub use thndr_tracing::prelude::*;
     pub use thndr_transform::prelude::*;
+    pub use thndr_tween::prelude::*;
+    #[cfg(feature = "window")]
     pub use thndr_window::prelude::*;
 }
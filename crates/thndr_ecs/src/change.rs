@@ -0,0 +1,170 @@
+use std::marker::PhantomData;
+
+use hecs::{ChangeTracker, Component, Entity, QueryBorrow, World};
+
+/// Records the tick on which the `T` component of an entity was last added or changed.
+///
+/// This is maintained by a [ChangeDetector], so systems can query `(&T, &ChangeTick<T>)`
+/// and skip everything that hasn't changed since a tick they remember.
+#[derive(Debug)]
+pub struct ChangeTick<T> {
+    tick: u64,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> ChangeTick<T> {
+    /// Get the tick on which the component was last added or changed.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Check if the component was added or changed after `tick`.
+    pub fn is_changed_since(&self, tick: u64) -> bool {
+        self.tick > tick
+    }
+}
+
+/// Detects changes to `T` components, and stamps them with a [ChangeTick].
+///
+/// Every call to [ChangeDetector::update] advances the tick by one, so ticks are monotonic for a world,
+/// as long as a single detector per component type is used. Ticks start at `0`, so everything added
+/// before the first update counts as changed since tick `0`.
+///
+/// *Note*: Detection works by comparing against a snapshot, so every update clones all `T` components
+/// and compares them to the previous snapshot. Only track the components you really need, e.g. the ones being replicated.
+///
+/// ```ignore
+/// let detector = RefCell::new(ChangeDetector::<Position>::new());
+/// app.add_direct_post_update_system(move |world| {
+///     detector.borrow_mut().update(world);
+/// });
+/// ```
+pub struct ChangeDetector<T: Component + Clone + PartialEq> {
+    tracker: ChangeTracker<T>,
+    tick: u64,
+}
+
+impl<T: Component + Clone + PartialEq> Default for ChangeDetector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Component + Clone + PartialEq> ChangeDetector<T> {
+    /// Creates a new [ChangeDetector], at tick `0`.
+    pub fn new() -> Self {
+        Self {
+            tracker: ChangeTracker::new(),
+            tick: 0,
+        }
+    }
+
+    /// Get the current tick, which is the tick of the last update.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Advances the tick, and stamps every `T` that was added or changed since the last update with it.
+    /// Returns the new tick.
+    pub fn update(&mut self, world: &mut World) -> u64 {
+        self.tick += 1;
+
+        let mut changes = self.tracker.track(world);
+        let mut changed = changes
+            .added()
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        changed.extend(changes.changed().map(|(entity, _, _)| entity));
+        let removed = changes
+            .removed()
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        drop(changes);
+
+        for entity in changed {
+            if let Ok(mut tick) = world.get::<&mut ChangeTick<T>>(entity) {
+                tick.tick = self.tick;
+                continue;
+            }
+
+            let _ = world.insert_one(
+                entity,
+                ChangeTick::<T> {
+                    tick: self.tick,
+                    _marker: PhantomData,
+                },
+            );
+        }
+
+        for entity in removed {
+            let _ = world.remove_one::<ChangeTick<T>>(entity);
+        }
+
+        self.tick
+    }
+}
+
+/// A query for the `T` components that were added or changed after a tick, see [changed_since].
+pub struct ChangedSince<'w, T: Component> {
+    query: QueryBorrow<'w, (&'static T, &'static ChangeTick<T>)>,
+    tick: u64,
+}
+
+impl<T: Component> ChangedSince<'_, T> {
+    /// Iterates over the changed components, together with their entities.
+    pub fn iter(&mut self) -> impl Iterator<Item = (Entity, &T)> + '_ {
+        let tick = self.tick;
+
+        self.query
+            .iter()
+            .filter(move |(_, (_, change))| change.is_changed_since(tick))
+            .map(|(entity, (component, _))| (entity, component))
+    }
+}
+
+/// Queries all `T` components that were added or changed after `tick`, as detected by a [ChangeDetector].
+///
+/// Like [World::query], this only borrows the world immutably, so it can be used next to other queries.
+///
+/// ```ignore
+/// for (entity, position) in changed_since::<Position>(&world, last_sent).iter() {
+///     send_position(entity, position);
+/// }
+/// ```
+pub fn changed_since<T: Component>(world: &World, tick: u64) -> ChangedSince<'_, T> {
+    ChangedSince {
+        query: world.query(),
+        tick,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Transform(f32);
+
+    #[test]
+    fn test_changed_since() {
+        let mut world = World::new();
+        let mut detector = ChangeDetector::<Transform>::new();
+
+        let entities = (0..1000)
+            .map(|i| world.spawn((Transform(i as f32),)))
+            .collect::<Vec<_>>();
+
+        let tick = detector.update(&mut world);
+        assert_eq!(changed_since::<Transform>(&world, 0).iter().count(), 1000);
+        assert_eq!(changed_since::<Transform>(&world, tick).iter().count(), 0);
+
+        world.get::<&mut Transform>(entities[500]).unwrap().0 = -1.0;
+        detector.update(&mut world);
+
+        let changed = changed_since::<Transform>(&world, tick)
+            .iter()
+            .map(|(entity, transform)| (entity, transform.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(changed, vec![(entities[500], Transform(-1.0))]);
+    }
+}
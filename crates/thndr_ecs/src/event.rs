@@ -0,0 +1,37 @@
+//! The event components, shared by `thndr_app` and `thndr_event`.
+//!
+//! They live here so the app can spawn events (e.g. `AppStartupCompleteEvent`) without depending on `thndr_event`,
+//! which builds the cleanup, observers and commands on top of them.
+
+/// The status of an [Event].
+///
+/// Handlers follow a simple protocol: a handler sets `Handled` to claim an event, and `Rejected` to pass it on
+/// to the next handler. An event that is still `NotHandled` wasn't seen by any handler yet.
+/// Both `NotHandled` and `Rejected` events are up for grabs, see [EventStatus::is_unclaimed].
+/// E.g. a UI focus chain lets the focused widget reject the keys it doesn't use, so its parent can handle them.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventStatus {
+    /// The event was not handled.
+    #[default]
+    NotHandled,
+    /// The event was seen by a handler, which declined it.
+    Rejected,
+    /// The event was handled.
+    Handled,
+    /// The event was consumed.
+    Consumed,
+}
+
+impl EventStatus {
+    /// Check if no handler claimed the event yet, i.e. it is `NotHandled` or `Rejected`.
+    pub fn is_unclaimed(self) -> bool {
+        matches!(self, EventStatus::NotHandled | EventStatus::Rejected)
+    }
+}
+
+/// The [Event] type. Used to represent an event.
+#[derive(Default, Debug, Clone)]
+pub struct Event {
+    /// The status of the event.
+    pub status: EventStatus,
+}
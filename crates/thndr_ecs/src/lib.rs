@@ -4,11 +4,31 @@
 //!
 //! Currently, it is a thin wrapper over [hecs](https://crates.io/crates/hecs), with some extra scheduling of systems.
 
-use hecs::{Component, DynamicBundle, Entity};
-use hecs_hierarchy::HierarchyMut;
-use hecs_schedule::{CommandBuffer, Write};
+use hecs::{
+    Bundle, Component, DynamicBundle, Entity, Query, QueryBorrow, Ref, RefMut, With, Without, World,
+};
+use hecs_hierarchy::{Child, HierarchyMut};
+use hecs_schedule::{CommandBuffer, ComponentAccess, SubWorld, Write};
+use thiserror::Error;
+
+/// Provides change detection.
+pub mod change;
+/// Provides the [Event](event::Event) component, see `thndr_event` for working with events.
+pub mod event;
+/// Provides relationships between entities, besides the [Tree].
+pub mod relationship;
+/// Provides a thread pool for running tasks in the background.
+pub mod task;
 
 /// The `Commands` type. Used to defer operations on the world.
+///
+/// The operations are applied when the schedule flushes, which happens at the end of every schedule,
+/// or at any flush point added with [ScheduleBuilder::flush](hecs_schedule::ScheduleBuilder::flush).
+///
+/// Every schedule owns a single command buffer, which all its systems record into. Flushing drains the buffer
+/// but keeps its allocations, so after the first few frames a system spawning and despawning thousands
+/// of entities per frame records its commands without allocating. No commands are carried over between flushes.
+/// Create the app with `App::with_command_capacity` to skip the growth during the first frames.
 pub type Commands<'a> = Write<'a, CommandBuffer>;
 
 /// The `CommandsExt` trait. Extends the `Commands` type with additional functionality related to hierarchies.
@@ -41,6 +61,82 @@ pub trait CommandsExt {
     /// Despawn parent and all children recursively. Essentially despawns a whole subtree including
     /// root. Does not fail if there are invalid, dangling IDs in tree.
     fn despawn_all<T: Component>(&mut self, parent: Entity);
+
+    /// Despawn `entity` and all its children recursively in the [Tree], e.g. a UI panel and everything in it.
+    /// `entity` is detached from its parent first, so the parent isn't left with a dangling id.
+    ///
+    /// This is [CommandsExt::despawn_all] for the [Tree], use that one for other relationships.
+    fn despawn_recursive(&mut self, entity: Entity);
+
+    /// Spawn an entity for every bundle, as a single command.
+    ///
+    /// Calling [CommandBuffer::spawn] in a loop records one command per entity, and spawns them one by one.
+    /// This records one command, which spawns all entities with [World::spawn_batch], so the archetype is
+    /// looked up and grown once. Use it for bulk spawns, like the tiles of a tilemap or a burst of particles.
+    /// Run the `spawn_batch` example to compare both on your machine.
+    ///
+    /// *Note*: The ids aren't known until the commands are flushed. If they are needed earlier (e.g. to attach children),
+    /// reserve them with [World::reserve_entities] and spawn them with [CommandsExt::insert_batch] instead.
+    fn spawn_batch<B, I>(&mut self, bundles: I)
+    where
+        B: Bundle + Send + Sync + 'static,
+        I: IntoIterator<Item = B>;
+
+    /// Insert components into many entities, as a single command. Also spawns reserved entities.
+    /// *Note*: Panics when the commands are flushed, if any of the entities no longer exists.
+    fn insert_batch<B, I>(&mut self, components: I)
+    where
+        B: DynamicBundle + Send + Sync + 'static,
+        I: IntoIterator<Item = (Entity, B)>;
+
+    /// Remove the `B` components from many entities, as a single command.
+    /// Entities that no longer exist, or don't have all components in `B`, are skipped.
+    fn remove_batch<B: Bundle + 'static>(&mut self, entities: impl IntoIterator<Item = Entity>);
+
+    /// Insert a resource, a singleton component like `Time` or `Keys`, replacing the existing one if there is one.
+    /// A loading system would e.g. insert the loaded database once it is done.
+    ///
+    /// Like any other command, it is applied when the commands are flushed, so systems see the resource from the next schedule on.
+    fn insert_resource<T: Component>(&mut self, resource: T);
+
+    /// Remove the resource of type `T`, when the commands are flushed.
+    /// Entities that are left without any components are despawned.
+    fn remove_resource<T: Component>(&mut self);
+
+    /// Despawn every entity matching the query `Q`, e.g. `commands.despawn_matching::<&Enemy>()` on a level reset.
+    ///
+    /// The query runs when the commands are flushed, not when this is called. Entities spawned by commands recorded
+    /// before this one are included, while entities spawned later in the frame (or in a later flush) survive.
+    /// Don't rely on either for entities spawned in the same frame.
+    ///
+    /// *Note*: This doesn't touch hierarchies, use [CommandsExt::despawn_matching_detached] for entities in a tree.
+    fn despawn_matching<Q: Query + 'static>(&mut self);
+
+    /// Like [CommandsExt::despawn_matching], but detaches every matching entity from tree `T` first, see [CommandsExt::detach_all].
+    /// The children of a despawned entity are left in the world, without a parent.
+    fn despawn_matching_detached<T: Component, Q: Query + 'static>(&mut self);
+
+    /// Get an [EntityCommands] builder for an existing or reserved `entity`, to chain operations on it:
+    ///
+    /// ```ignore
+    /// commands.entity(player).insert(Health(100)).attach_to::<Tree>(team);
+    /// ```
+    fn entity(&mut self, entity: Entity) -> EntityCommands<'_>;
+
+    /// Get an [EntityCommands] builder for a new entity, which is spawned when the commands are flushed:
+    ///
+    /// ```ignore
+    /// commands
+    ///     .spawn_empty()
+    ///     .insert_bundle(TransformBundle::default())
+    ///     .insert(Ship)
+    ///     .with_child((Cannon,))
+    ///     .with_child((Engine,));
+    /// ```
+    ///
+    /// *Note*: The id of the new entity isn't known until the commands are flushed. If it is needed earlier,
+    /// reserve it with [World::reserve_entity] and use [CommandsExt::entity] instead.
+    fn spawn_empty(&mut self) -> EntityCommands<'_>;
 }
 
 impl CommandsExt for Commands<'_> {
@@ -99,9 +195,475 @@ impl CommandsExt for Commands<'_> {
             world.despawn_all::<T>(parent);
         });
     }
+
+    fn despawn_recursive(&mut self, entity: Entity) {
+        self.write(move |world| {
+            if world.get::<&Child<Tree>>(entity).is_ok() {
+                let _ = world.detach::<Tree>(entity);
+            }
+            world.despawn_all::<Tree>(entity);
+        });
+    }
+
+    fn spawn_batch<B, I>(&mut self, bundles: I)
+    where
+        B: Bundle + Send + Sync + 'static,
+        I: IntoIterator<Item = B>,
+    {
+        let bundles = bundles.into_iter().collect::<Vec<_>>();
+
+        self.write(move |world| {
+            // the entities are spawned as the iterator is consumed
+            world.spawn_batch(bundles).for_each(drop);
+        });
+    }
+
+    fn insert_batch<B, I>(&mut self, components: I)
+    where
+        B: DynamicBundle + Send + Sync + 'static,
+        I: IntoIterator<Item = (Entity, B)>,
+    {
+        let components = components.into_iter().collect::<Vec<_>>();
+
+        self.write(move |world| {
+            for (entity, bundle) in components {
+                world
+                    .insert(entity, bundle)
+                    .expect("Failed to insert components");
+            }
+        });
+    }
+
+    fn remove_batch<B: Bundle + 'static>(&mut self, entities: impl IntoIterator<Item = Entity>) {
+        let entities = entities.into_iter().collect::<Vec<_>>();
+
+        self.write(move |world| {
+            for entity in entities {
+                let _ = world.remove::<B>(entity);
+            }
+        });
+    }
+
+    fn insert_resource<T: Component>(&mut self, resource: T) {
+        self.write(move |world| {
+            let existing = world
+                .query_mut::<&T>()
+                .into_iter()
+                .next()
+                .map(|(entity, _)| entity);
+
+            match existing {
+                Some(entity) => {
+                    let _ = world.insert_one(entity, resource);
+                }
+                None => {
+                    world.spawn((resource,));
+                }
+            }
+        });
+    }
+
+    fn remove_resource<T: Component>(&mut self) {
+        self.write(|world| {
+            let entities = world
+                .query_mut::<&T>()
+                .into_iter()
+                .map(|(entity, _)| entity)
+                .collect::<Vec<_>>();
+
+            for entity in entities {
+                let _ = world.remove_one::<T>(entity);
+
+                let empty = world
+                    .entity(entity)
+                    .is_ok_and(|entity| entity.component_types().next().is_none());
+                if empty {
+                    let _ = world.despawn(entity);
+                }
+            }
+        });
+    }
+
+    fn despawn_matching<Q: Query + 'static>(&mut self) {
+        self.write(|world| {
+            for entity in matching::<Q>(world) {
+                let _ = world.despawn(entity);
+            }
+        });
+    }
+
+    fn despawn_matching_detached<T: Component, Q: Query + 'static>(&mut self) {
+        self.write(|world| {
+            for entity in matching::<Q>(world) {
+                // entities might be outside the tree, or only have one side of it
+                let _ = world.detach_children::<T>(entity);
+                let _ = world.detach::<T>(entity);
+                let _ = world.despawn(entity);
+            }
+        });
+    }
+
+    fn entity(&mut self, entity: Entity) -> EntityCommands<'_> {
+        EntityCommands::new(self, Some(entity))
+    }
+
+    fn spawn_empty(&mut self) -> EntityCommands<'_> {
+        EntityCommands::new(self, None)
+    }
+}
+
+/// An operation recorded by [EntityCommands], applied to the entity when the commands are flushed.
+type EntityCommand = Box<dyn FnOnce(&mut World, Entity) + Send + Sync>;
+
+/// A builder chaining operations on a single entity, returned by [CommandsExt::entity] and [CommandsExt::spawn_empty].
+///
+/// The operations are recorded as a single command when the builder is dropped, usually at the end of the statement,
+/// and applied in order when the commands are flushed, like any other command. A new entity is spawned at that point,
+/// so every operation of the chain sees it, and its children are spawned and attached right after it.
+///
+/// *Note*: Panics when the commands are flushed, if the entity no longer exists.
+pub struct EntityCommands<'a> {
+    commands: &'a mut CommandBuffer,
+    entity: Option<Entity>,
+    ops: Vec<EntityCommand>,
+}
+
+impl<'a> EntityCommands<'a> {
+    fn new(commands: &'a mut CommandBuffer, entity: Option<Entity>) -> Self {
+        Self {
+            commands,
+            entity,
+            ops: Vec::new(),
+        }
+    }
+
+    /// Get the id of the entity, if it is known already. It is `None` for entities from [CommandsExt::spawn_empty].
+    pub fn id(&self) -> Option<Entity> {
+        self.entity
+    }
+
+    /// Insert a component, replacing the existing one of the same type.
+    pub fn insert<C: Component>(&mut self, component: C) -> &mut Self {
+        self.insert_bundle((component,))
+    }
+
+    /// Insert a bundle of components, replacing the existing ones of the same types.
+    pub fn insert_bundle<B: DynamicBundle + Send + Sync + 'static>(
+        &mut self,
+        bundle: B,
+    ) -> &mut Self {
+        self.ops.push(Box::new(move |world, entity| {
+            world
+                .insert(entity, bundle)
+                .expect("Failed to insert components");
+        }));
+        self
+    }
+
+    /// Attach the entity to `parent` in tree `T`, see [CommandsExt::attach].
+    pub fn attach_to<T: Component>(&mut self, parent: Entity) -> &mut Self {
+        self.ops.push(Box::new(move |world, entity| {
+            world
+                .attach::<T>(entity, parent)
+                .expect("Failed to attach entity");
+        }));
+        self
+    }
+
+    /// Spawn a new entity with the `bundle`, and attach it to this one in the [Tree].
+    pub fn with_child<B: DynamicBundle + Send + Sync + 'static>(&mut self, bundle: B) -> &mut Self {
+        self.ops.push(Box::new(move |world, entity| {
+            let child = world.spawn(bundle);
+            world
+                .attach::<Tree>(child, entity)
+                .expect("Failed to attach entity");
+        }));
+        self
+    }
+}
+
+impl Drop for EntityCommands<'_> {
+    fn drop(&mut self) {
+        if self.entity.is_some() && self.ops.is_empty() {
+            return;
+        }
+
+        let target = self.entity;
+        let ops = std::mem::take(&mut self.ops);
+        self.commands.write(move |world| {
+            let entity = target.unwrap_or_else(|| world.spawn(()));
+            for op in ops {
+                op(world, entity);
+            }
+        });
+    }
+}
+
+/// Collect the entities matching `Q`, so they can be despawned while iterating.
+fn matching<Q: Query>(world: &mut World) -> Vec<Entity> {
+    world
+        .query_mut::<Q>()
+        .into_iter()
+        .map(|(entity, _)| entity)
+        .collect()
+}
+
+/// The `WorldExt` trait. Extends the `World` with more forgiving accessors, for use in direct systems.
+///
+/// A missing entity or component simply results in `None` or `false`. The only way these methods panic is
+/// by borrowing a component that is already borrowed in a conflicting way, just like everywhere else in hecs.
+/// Everything else hecs offers (e.g. `reserve_entities`, `flush` or archetype inspection) is available on the `World` itself.
+pub trait WorldExt {
+    /// Get a reference to the `T` component of `entity`.
+    /// Returns `None` if the entity doesn't exist or doesn't have the component.
+    fn get_component<T: Component>(&self, entity: Entity) -> Option<Ref<'_, T>>;
+
+    /// Get a mutable reference to the `T` component of `entity`.
+    /// Returns `None` if the entity doesn't exist or doesn't have the component.
+    fn get_component_mut<T: Component>(&self, entity: Entity) -> Option<RefMut<'_, T>>;
+
+    /// Check if `entity` exists and has a `T` component.
+    fn has_component<T: Component>(&self, entity: Entity) -> bool;
+
+    /// Get the number of entities in the world.
+    fn entity_count(&self) -> u32;
+
+    /// Run the query `Q` on a single entity, and pass the result to `f`.
+    /// Returns `None` if the entity doesn't exist or doesn't match the query.
+    fn try_query_one<Q: Query, R>(
+        &self,
+        entity: Entity,
+        f: impl FnOnce(Q::Item<'_>) -> R,
+    ) -> Option<R>;
+
+    /// Find the first entity matching the query `Q`, for which `predicate` returns `true`.
+    /// E.g. a dead entity: `world.find::<&Health>(|health| health.current <= 0)`.
+    ///
+    /// Only the entity is returned, as the query is no longer borrowed afterwards. Use [World::get] to access its components.
+    /// *Note*: The iteration order is not stable, so with multiple matches, any of them may be returned.
+    fn find<Q: Query>(&self, predicate: impl Fn(&Q::Item<'_>) -> bool) -> Option<Entity>;
+
+    /// Collect all entities matching the query `Q`, for which `predicate` returns `true`.
+    ///
+    /// *Note*: This allocates a [Vec]. Iterate the query yourself in hot code, to avoid that.
+    fn filter_collect<Q: Query>(&self, predicate: impl Fn(&Q::Item<'_>) -> bool) -> Vec<Entity>;
+}
+
+impl WorldExt for World {
+    fn get_component<T: Component>(&self, entity: Entity) -> Option<Ref<'_, T>> {
+        self.get::<&T>(entity).ok()
+    }
+
+    fn get_component_mut<T: Component>(&self, entity: Entity) -> Option<RefMut<'_, T>> {
+        self.get::<&mut T>(entity).ok()
+    }
+
+    fn has_component<T: Component>(&self, entity: Entity) -> bool {
+        self.entity(entity).is_ok_and(|entity| entity.has::<T>())
+    }
+
+    fn entity_count(&self) -> u32 {
+        self.len()
+    }
+
+    fn try_query_one<Q: Query, R>(
+        &self,
+        entity: Entity,
+        f: impl FnOnce(Q::Item<'_>) -> R,
+    ) -> Option<R> {
+        let mut query = self.query_one::<Q>(entity).ok()?;
+        let item = query.get()?;
+
+        Some(f(item))
+    }
+
+    fn find<Q: Query>(&self, predicate: impl Fn(&Q::Item<'_>) -> bool) -> Option<Entity> {
+        self.query::<Q>()
+            .iter()
+            .find(|(_, item)| predicate(item))
+            .map(|(entity, _)| entity)
+    }
+
+    fn filter_collect<Q: Query>(&self, predicate: impl Fn(&Q::Item<'_>) -> bool) -> Vec<Entity> {
+        self.query::<Q>()
+            .iter()
+            .filter(|(_, item)| predicate(item))
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+}
+
+/// A query `Q`, that only matches entities with a `T` component. `T` is not borrowed.
+pub type Has<Q, T> = With<Q, &'static T>;
+
+/// A query `Q`, that only matches entities without a `T` component.
+pub type Lacks<Q, T> = Without<Q, &'static T>;
+
+/// The `QueryFilterExt` trait. Extends queries with filters that take a component type, instead of a query.
+///
+/// Filters can be chained any number of times, in any order, and compile to the same nested hecs filters
+/// as calling [QueryBorrow::with] and [QueryBorrow::without] yourself:
+///
+/// ```ignore
+/// // everything with a position and a velocity, that isn't frozen or a child
+/// world.query::<&mut Position>().has::<Velocity>().lacks::<Frozen>().lacks::<Child<Tree>>();
+/// ```
+///
+/// *Note*: A query that both has and lacks the same component never matches anything.
+/// The filtered components aren't borrowed, so they can be mutably borrowed elsewhere at the same time.
+///
+/// To match entities whether or not they have a component, query it as an `Option` instead, in the [SubWorld](hecs_schedule::SubWorld) too.
+/// This replaces a pair of queries with `has` and `lacks`, see the `optional_components` example.
+///
+/// ```ignore
+/// fn draw(world: SubWorld<(&Position, Option<&Tint>)>) {
+///     for (_, (position, tint)) in world.query::<(&Position, Option<&Tint>)>().iter() {
+///         let color = tint.map_or(Color::WHITE, |tint| tint.0);
+///     }
+/// }
+/// ```
+pub trait QueryFilterExt<'w, Q: Query> {
+    /// Only match entities that have a `T` component.
+    fn has<T: Component>(self) -> QueryBorrow<'w, Has<Q, T>>;
+
+    /// Only match entities that don't have a `T` component.
+    fn lacks<T: Component>(self) -> QueryBorrow<'w, Lacks<Q, T>>;
 }
 
-/// Marker type for entity hierarchies.
+impl<'w, Q: Query> QueryFilterExt<'w, Q> for QueryBorrow<'w, Q> {
+    fn has<T: Component>(self) -> QueryBorrow<'w, Has<Q, T>> {
+        self.with::<&T>()
+    }
+
+    fn lacks<T: Component>(self) -> QueryBorrow<'w, Lacks<Q, T>> {
+        self.without::<&T>()
+    }
+}
+
+/// Collect the results of the query `Q`, sorted by `key`.
+///
+/// By default, hecs iterates archetype by archetype, and the order within an archetype changes as entities are despawned or
+/// change components. So the order depends on how the world was built, and is not guaranteed to be stable.
+/// Use this if you need a deterministic order, e.g. to draw back to front by a z-index component.
+///
+/// *Note*: The sort is stable, so entities with equal keys keep their (non-deterministic) iteration order.
+/// Make the key unique if you need a fully deterministic order.
+pub fn sorted_query<'w, Q: Query, K: Ord>(
+    world: &'w mut World,
+    mut key: impl FnMut(Entity, &Q::Item<'w>) -> K,
+) -> Vec<(Entity, Q::Item<'w>)> {
+    let mut items = world.query_mut::<Q>().into_iter().collect::<Vec<_>>();
+    items.sort_by_key(|(entity, item)| key(*entity, item));
+
+    items
+}
+
+/// The `QuerySortExt` trait. Extends queries with sorted iteration, for use in systems. See [sorted_query].
+pub trait QuerySortExt<'w, Q: Query> {
+    /// Collect the results of the query, sorted by `key`. See [sorted_query] for the details.
+    fn iter_sorted_by_key<'q, K: Ord>(
+        &'q mut self,
+        key: impl FnMut(Entity, &Q::Item<'q>) -> K,
+    ) -> Vec<(Entity, Q::Item<'q>)>;
+}
+
+impl<'w, Q: Query> QuerySortExt<'w, Q> for QueryBorrow<'w, Q> {
+    fn iter_sorted_by_key<'q, K: Ord>(
+        &'q mut self,
+        mut key: impl FnMut(Entity, &Q::Item<'q>) -> K,
+    ) -> Vec<(Entity, Q::Item<'q>)> {
+        let mut items = self.iter().collect::<Vec<_>>();
+        items.sort_by_key(|(entity, item)| key(*entity, item));
+
+        items
+    }
+}
+
+/// The error returned by [QuerySingleExt::single_mut], when a query doesn't match exactly one entity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum SingleError {
+    /// No entity matched the query.
+    #[error("No entity matched the query")]
+    NoEntities,
+    /// More than one entity matched the query.
+    #[error("More than one entity matched the query")]
+    MultipleEntities,
+}
+
+/// The `QuerySingleExt` trait. Extends queries with access to the single entity they match, for singletons like `Keys` or `Time`.
+///
+/// ```ignore
+/// let mut keys = world.query::<&mut Keys>();
+/// let Ok(keys) = keys.single_mut() else {
+///     return;
+/// };
+/// keys.press(KeyCode::KeyW);
+/// ```
+///
+/// The query stays borrowed as long as the item is used, just like when iterating it,
+/// so it coexists with any other query the system could already run alongside it.
+pub trait QuerySingleExt<'w, Q: Query> {
+    /// Get the item of the only entity that matches the query.
+    /// Returns an error if no entity, or more than one entity matches.
+    fn single_mut(&mut self) -> std::result::Result<Q::Item<'_>, SingleError>;
+}
+
+impl<'w, Q: Query> QuerySingleExt<'w, Q> for QueryBorrow<'w, Q> {
+    fn single_mut(&mut self) -> std::result::Result<Q::Item<'_>, SingleError> {
+        let mut iter = self.iter();
+        let (_, item) = iter.next().ok_or(SingleError::NoEntities)?;
+
+        if iter.next().is_some() {
+            return Err(SingleError::MultipleEntities);
+        }
+
+        Ok(item)
+    }
+}
+
+/// The `SubWorldSingleExt` trait. Extends the `SubWorld` with access to the single entity with a component, for singletons like `Keys` or `Time`.
+///
+/// Unlike [QuerySingleExt::single_mut], the component doesn't borrow from a query that has to be kept around.
+///
+/// ```ignore
+/// fn press_w(world: SubWorld<&mut Keys>) {
+///     let Ok(mut keys) = world.single_mut::<Keys>() else {
+///         return;
+///     };
+///     keys.press(KeyCode::KeyW);
+/// }
+/// ```
+pub trait SubWorldSingleExt {
+    /// Get the component of the only entity that has a `C`.
+    /// Returns an error if no entity, or more than one entity has one.
+    ///
+    /// *Note*: Panics if the `SubWorld` doesn't have mutable access to `C`, just like a query it has no access to.
+    fn single_mut<C: Component>(&self) -> std::result::Result<RefMut<'_, C>, SingleError>;
+}
+
+impl<'w, A: ComponentAccess> SubWorldSingleExt for SubWorld<'w, A> {
+    fn single_mut<C: Component>(&self) -> std::result::Result<RefMut<'_, C>, SingleError> {
+        // the query only finds the entity, it has to be released before the component is borrowed mutably
+        let entity = {
+            let mut query = self.query::<&C>();
+            let mut entities = query.iter().map(|(entity, _)| entity);
+            let entity = entities.next().ok_or(SingleError::NoEntities)?;
+
+            if entities.next().is_some() {
+                return Err(SingleError::MultipleEntities);
+            }
+
+            entity
+        };
+
+        Ok(self
+            .get_mut::<C>(entity)
+            .expect("SubWorld has no mutable access to the component"))
+    }
+}
+
+/// Marker type for entity hierarchies. This is the hierarchy transforms are propagated through,
+/// use a [relationship::Relationship] for anything else.
 #[derive(Debug, Default, Clone, Copy)]
 pub struct Tree;
 
@@ -113,7 +675,399 @@ pub mod prelude {
     pub use hecs_schedule::*;
     pub use rayon::prelude::*;
 
+    pub use crate::change::{changed_since, ChangeDetector, ChangeTick, ChangedSince};
+    pub use crate::define_relationship;
+    pub use crate::relationship::Relationship;
+    pub use crate::task::{ComputePool, Task, TaskPool};
     pub use crate::Commands;
     pub use crate::CommandsExt;
+    pub use crate::EntityCommands;
+    pub use crate::QueryFilterExt;
     pub use crate::Tree;
+    pub use crate::WorldExt;
+    pub use crate::{sorted_query, QuerySortExt};
+    pub use crate::{Has, Lacks};
+    pub use crate::{QuerySingleExt, SingleError, SubWorldSingleExt};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hecs_hierarchy::Hierarchy;
+    use hecs_schedule::ScheduleBuilder;
+
+    #[test]
+    fn test_world_ext() {
+        let mut world = World::new();
+        let entity = world.spawn((1u32, 2.0f32));
+        let missing = world.spawn(());
+        world.despawn(missing).unwrap();
+
+        assert_eq!(world.entity_count(), 1);
+        assert!(world.has_component::<u32>(entity));
+        assert!(!world.has_component::<u64>(entity));
+        assert!(!world.has_component::<u32>(missing));
+
+        *world.get_component_mut::<u32>(entity).unwrap() += 1;
+        assert_eq!(world.get_component::<u32>(entity).as_deref(), Some(&2));
+        assert!(world.get_component::<u32>(missing).is_none());
+
+        let sum = world.try_query_one::<(&u32, &f32), _>(entity, |(a, b)| *a as f32 + *b);
+        assert_eq!(sum, Some(4.0));
+        assert_eq!(world.try_query_one::<&u64, _>(entity, |a| *a), None);
+        assert_eq!(world.try_query_one::<&u32, _>(missing, |a| *a), None);
+    }
+
+    fn spawn_tiles(mut commands: Commands) {
+        commands.spawn_batch((0..5u32).map(|i| (i, 1.0f32)));
+    }
+
+    fn mark_tiles(world: SubWorld<&u32>, mut commands: Commands) {
+        let tiles = world
+            .query::<&u32>()
+            .iter()
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+
+        commands.insert_batch(tiles.iter().map(|tile| (*tile, (true,))));
+        commands.remove_batch::<(f32,)>(tiles);
+    }
+
+    struct AssetDatabase(u32);
+
+    fn load_database(mut commands: Commands) {
+        commands.insert_resource(AssetDatabase(1));
+    }
+
+    fn count_databases(world: SubWorld<&AssetDatabase>, mut commands: Commands) {
+        let mut query = world.query::<&AssetDatabase>();
+        if let Ok(database) = query.single_mut() {
+            commands.insert_resource(database.0 + 1);
+        }
+    }
+
+    fn unload_database(mut commands: Commands) {
+        commands.remove_resource::<AssetDatabase>();
+    }
+
+    #[test]
+    fn test_resource_commands() {
+        let mut world = World::new();
+        let mut frame = ScheduleBuilder::new()
+            .add_system(load_database)
+            .add_system(count_databases)
+            .build();
+
+        // inserted at the end of the first frame, so only the second one sees it
+        frame.execute((&mut world,)).unwrap();
+        assert_eq!(world.query::<&AssetDatabase>().iter().count(), 1);
+        assert_eq!(world.query::<&u32>().iter().count(), 0);
+
+        frame.execute((&mut world,)).unwrap();
+        assert_eq!(world.query::<&u32>().single_mut().copied(), Ok(2));
+
+        // inserting again replaces the resource
+        frame.execute((&mut world,)).unwrap();
+        assert_eq!(world.query::<&AssetDatabase>().iter().count(), 1);
+        assert_eq!(world.query::<&u32>().iter().count(), 1);
+
+        let mut unload = ScheduleBuilder::new().add_system(unload_database).build();
+        unload.execute((&mut world,)).unwrap();
+        assert_eq!(world.query::<&AssetDatabase>().iter().count(), 0);
+        assert_eq!(world.entity_count(), 1);
+    }
+
+    #[test]
+    fn test_despawn_recursive() {
+        let mut world = World::new();
+        let root = world.spawn(("root",));
+        let panel = world.spawn(("panel",));
+        let button = world.spawn(("button",));
+        let label = world.spawn(("label",));
+        let sibling = world.spawn(("sibling",));
+        world.attach::<Tree>(panel, root).unwrap();
+        world.attach::<Tree>(button, panel).unwrap();
+        world.attach::<Tree>(label, button).unwrap();
+        world.attach::<Tree>(sibling, root).unwrap();
+
+        let mut schedule = ScheduleBuilder::new()
+            .add_system(move |mut commands: Commands| commands.despawn_recursive(panel))
+            .build();
+        schedule.execute((&mut world,)).unwrap();
+
+        for entity in [panel, button, label] {
+            assert!(!world.contains(entity));
+        }
+        // the rest of the tree only knows about the entities that are still alive
+        assert_eq!(
+            world.children::<Tree>(root).collect::<Vec<_>>(),
+            vec![sibling]
+        );
+        assert_eq!(world.parent::<Tree>(sibling).ok(), Some(root));
+    }
+
+    struct Ship;
+    struct Cannon;
+    struct Engine;
+
+    #[test]
+    fn test_entity_commands() {
+        let mut world = World::new();
+        let fleet = world.spawn(());
+
+        let mut schedule = ScheduleBuilder::new()
+            .add_system(move |mut commands: Commands| {
+                commands
+                    .spawn_empty()
+                    .insert(Ship)
+                    .insert_bundle((1u32, 2.0f32))
+                    .attach_to::<Tree>(fleet)
+                    .with_child((Cannon,))
+                    .with_child((Engine,));
+                commands.entity(fleet).insert(0u32);
+            })
+            .build();
+        schedule.execute((&mut world,)).unwrap();
+
+        let ship = world.query::<&Ship>().iter().next().unwrap().0;
+        assert_eq!(world.get_component::<u32>(ship).as_deref(), Some(&1));
+        assert_eq!(world.get_component::<u32>(fleet).as_deref(), Some(&0));
+        assert_eq!(world.parent::<Tree>(ship).ok(), Some(fleet));
+
+        let children = world.children::<Tree>(ship).collect::<Vec<_>>();
+        assert_eq!(children.len(), 2);
+        assert!(children
+            .iter()
+            .any(|child| world.has_component::<Cannon>(*child)));
+        assert!(children
+            .iter()
+            .any(|child| world.has_component::<Engine>(*child)));
+    }
+
+    struct Bullet;
+    struct Enemy;
+
+    fn reset_level(mut commands: Commands) {
+        commands.despawn_matching::<&Bullet>();
+        commands.despawn_matching_detached::<Tree, &Enemy>();
+    }
+
+    #[test]
+    fn test_despawn_matching() {
+        let mut world = World::new();
+        let player = world.spawn(("player",));
+        let wall = world.spawn(("wall", ZIndex(1)));
+        for _ in 0..3 {
+            world.spawn((Bullet, ZIndex(2)));
+        }
+        let squad = world.spawn(("squad",));
+        let enemy = world.spawn((Enemy,));
+        let weapon = world.spawn(("weapon",));
+        world.attach::<Tree>(enemy, squad).unwrap();
+        world.attach::<Tree>(weapon, enemy).unwrap();
+
+        let mut schedule = ScheduleBuilder::new().add_system(reset_level).build();
+        schedule.execute((&mut world,)).unwrap();
+
+        assert_eq!(world.query::<&Bullet>().iter().count(), 0);
+        assert_eq!(world.query::<&Enemy>().iter().count(), 0);
+        assert!(world.contains(player));
+        assert!(world.contains(wall));
+        assert!(world.contains(weapon));
+        assert_eq!(world.children::<Tree>(squad).count(), 0);
+        assert!(world.parent::<Tree>(weapon).is_err());
+    }
+
+    struct Tint(u32);
+
+    fn tint_sum(world: SubWorld<(&ZIndex, Option<&Tint>)>, mut commands: Commands) {
+        let sum = world
+            .query::<(&ZIndex, Option<&Tint>)>()
+            .iter()
+            .map(|(_, (z, tint))| z.0 * tint.map_or(1, |tint| tint.0))
+            .sum::<u32>();
+
+        commands.spawn((sum,));
+    }
+
+    #[test]
+    fn test_optional_components() {
+        let mut world = World::new();
+        world.spawn((ZIndex(1),));
+        world.spawn((ZIndex(2), Tint(10)));
+        world.spawn((Tint(100),));
+
+        let mut schedule = ScheduleBuilder::new().add_system(tint_sum).build();
+        schedule.execute((&mut world,)).unwrap();
+
+        // the entity without a z-index is skipped, the one without a tint isn't
+        assert_eq!(world.query::<&u32>().single_mut().copied(), Ok(21));
+    }
+
+    #[test]
+    fn test_batch_commands() {
+        let mut world = World::new();
+        let mut spawn = ScheduleBuilder::new().add_system(spawn_tiles).build();
+        let mut mark = ScheduleBuilder::new().add_system(mark_tiles).build();
+
+        spawn.execute((&mut world,)).unwrap();
+        assert_eq!(world.entity_count(), 5);
+        assert_eq!(world.query::<(&u32, &f32)>().iter().count(), 5);
+
+        mark.execute((&mut world,)).unwrap();
+        assert_eq!(world.query::<(&u32, &bool)>().iter().count(), 5);
+        assert_eq!(world.query::<&f32>().iter().count(), 0);
+    }
+
+    struct A;
+    struct B;
+    struct C;
+
+    struct ZIndex(u32);
+
+    struct Health(i32);
+
+    #[test]
+    fn test_find_and_filter() {
+        let mut world = World::new();
+        let alive = world.spawn((Health(10),));
+        let dead = world.spawn((Health(0),));
+        let also_dead = world.spawn((Health(-5), 1u32));
+
+        let found = world.find::<&Health>(|health| health.0 <= 0);
+        assert!(found == Some(dead) || found == Some(also_dead));
+        assert_eq!(world.find::<&Health>(|health| health.0 > 100), None);
+        assert_eq!(
+            world.find::<(&Health, &u32)>(|(health, _)| health.0 <= 0),
+            Some(also_dead)
+        );
+
+        let mut dead_ones = world.filter_collect::<&Health>(|health| health.0 <= 0);
+        dead_ones.sort();
+        let mut expected = vec![dead, also_dead];
+        expected.sort();
+        assert_eq!(dead_ones, expected);
+
+        assert_eq!(
+            world.filter_collect::<&Health>(|health| health.0 > 0),
+            vec![alive]
+        );
+        assert!(world.filter_collect::<&u64>(|_| true).is_empty());
+    }
+
+    #[test]
+    fn test_single_mut() {
+        let mut world = World::new();
+        assert_eq!(
+            world.query::<&mut u32>().single_mut().err(),
+            Some(SingleError::NoEntities)
+        );
+
+        world.spawn((1u32,));
+        *world.query::<&mut u32>().single_mut().unwrap() += 1;
+        assert_eq!(world.query::<&u32>().single_mut().copied(), Ok(2));
+
+        world.spawn((5u32,));
+        assert_eq!(
+            world.query::<&mut u32>().single_mut().err(),
+            Some(SingleError::MultipleEntities)
+        );
+    }
+
+    fn bump_single(world: SubWorld<&mut u32>) {
+        if let Ok(mut value) = world.single_mut::<u32>() {
+            *value += 1;
+        }
+    }
+
+    #[test]
+    fn test_subworld_single_mut() {
+        let mut world = World::new();
+        let mut schedule = ScheduleBuilder::new().add_system(bump_single).build();
+
+        // no entity, nothing happens
+        schedule.execute((&mut world,)).unwrap();
+
+        let first = world.spawn((1u32,));
+        schedule.execute((&mut world,)).unwrap();
+        assert_eq!(*world.get::<&u32>(first).unwrap(), 2);
+
+        // not a singleton anymore, so neither is touched
+        let second = world.spawn((5u32,));
+        schedule.execute((&mut world,)).unwrap();
+        assert_eq!(*world.get::<&u32>(first).unwrap(), 2);
+        assert_eq!(*world.get::<&u32>(second).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_sorted_query() {
+        let mut first = World::new();
+        let mut second = World::new();
+
+        let spawns = [("a", 3), ("b", 1), ("c", 2)];
+        for (name, z) in spawns {
+            first.spawn((name, ZIndex(z)));
+        }
+        // same entities, but in a different order and with an extra archetype in between
+        for (name, z) in spawns.into_iter().rev() {
+            second.spawn((name, ZIndex(z)));
+            second.spawn((A,));
+        }
+        second.spawn(("d", ZIndex(0), A));
+
+        let first = sorted_query::<(&&str, &ZIndex), _>(&mut first, |_, (_, z)| z.0)
+            .into_iter()
+            .map(|(_, (name, _))| *name)
+            .collect::<Vec<_>>();
+        let second = sorted_query::<(&&str, &ZIndex), _>(&mut second, |_, (_, z)| z.0)
+            .into_iter()
+            .map(|(_, (name, _))| *name)
+            .collect::<Vec<_>>();
+
+        assert_eq!(first, vec!["b", "c", "a"]);
+        assert_eq!(second, vec!["d", "b", "c", "a"]);
+
+        let mut world = World::new();
+        world.spawn(("x", ZIndex(2)));
+        world.spawn(("y", ZIndex(1)));
+        let mut query = world.query::<(&&str, &ZIndex)>();
+        let names = query
+            .iter_sorted_by_key(|_, (_, z)| z.0)
+            .into_iter()
+            .map(|(_, (name, _))| *name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["y", "x"]);
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let mut world = World::new();
+        let matching = world.spawn((1u32, A));
+        world.spawn((2u32,));
+        world.spawn((3u32, A, B));
+        world.spawn((4u32, A, C));
+        world.spawn((5u32, A, B, C));
+
+        let found = world
+            .query::<&u32>()
+            .has::<A>()
+            .lacks::<B>()
+            .lacks::<C>()
+            .iter()
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        assert_eq!(found, vec![matching]);
+
+        // the same filter, spelled as a type
+        let count = world
+            .query::<Lacks<Lacks<Has<&u32, A>, B>, C>>()
+            .iter()
+            .count();
+        assert_eq!(count, 1);
+
+        assert_eq!(
+            world.query::<&u32>().has::<A>().lacks::<A>().iter().count(),
+            0
+        );
+    }
 }
@@ -0,0 +1,109 @@
+use hecs::{Component, Entity, World};
+use hecs_hierarchy::{Error, Hierarchy, HierarchyMut};
+
+/// A relationship between entities, like "targeting" or "owned by", with helpers for the underlying hierarchy.
+///
+/// Every relationship is a separate hierarchy, with its own `Parent<R>` and `Child<R>` components,
+/// so an entity can be in several of them at once without them interfering. Define one with [define_relationship](crate::define_relationship).
+///
+/// *Note*: Only the [Tree](crate::Tree) affects transforms, other relationships are purely bookkeeping.
+/// Like with the [Tree](crate::Tree), detach entities before despawning them, so no dangling ids are left behind.
+pub trait Relationship: Component + Sized {
+    /// Relate `child` to `parent`, e.g. the attacker to its target.
+    /// An entity can only have a single parent per relationship, so it is detached from its old one first.
+    fn attach(world: &mut World, child: Entity, parent: Entity) -> Result<Entity, Error> {
+        if world.parent::<Self>(child).is_ok() {
+            world.detach::<Self>(child)?;
+        }
+
+        world.attach::<Self>(child, parent)
+    }
+
+    /// Remove `child` from its parent. Its own children stay attached to it.
+    fn detach(world: &mut World, child: Entity) -> Result<(), Error> {
+        world.detach::<Self>(child)
+    }
+
+    /// Get the parent of `child`, if it has one.
+    fn parent(world: &World, child: Entity) -> Option<Entity> {
+        world.parent::<Self>(child).ok()
+    }
+
+    /// Iterate over the children of `parent`, e.g. everything targeting it.
+    fn children(world: &World, parent: Entity) -> impl Iterator<Item = Entity> + '_ {
+        world.children::<Self>(parent)
+    }
+}
+
+impl Relationship for crate::Tree {}
+
+/// Defines a marker type for a [Relationship](crate::relationship::Relationship).
+///
+/// ```ignore
+/// define_relationship!(
+///     /// Relates an attacker to the entity it is targeting.
+///     pub Targeting
+/// );
+///
+/// Targeting::attach(world, attacker, target)?;
+/// let attackers = Targeting::children(world, target).count();
+/// ```
+#[macro_export]
+macro_rules! define_relationship {
+    ($(#[$meta:meta])* $vis:vis $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Default, Clone, Copy)]
+        $vis struct $name;
+
+        impl $crate::relationship::Relationship for $name {}
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Tree;
+
+    define_relationship!(Targeting);
+    define_relationship!(
+        /// Relates a ship to the station it is docked to.
+        DockedTo
+    );
+
+    #[test]
+    fn test_independent_relationships() {
+        let mut world = World::new();
+        let station = world.spawn(("station",));
+        let enemy = world.spawn(("enemy",));
+        let ship = world.spawn(("ship",));
+        let other_ship = world.spawn(("other ship",));
+
+        DockedTo::attach(&mut world, ship, station).unwrap();
+        DockedTo::attach(&mut world, other_ship, station).unwrap();
+        Targeting::attach(&mut world, ship, enemy).unwrap();
+
+        assert_eq!(DockedTo::parent(&world, ship), Some(station));
+        assert_eq!(Targeting::parent(&world, ship), Some(enemy));
+        assert_eq!(Tree::parent(&world, ship), None);
+        assert_eq!(DockedTo::children(&world, station).count(), 2);
+        assert_eq!(
+            Targeting::children(&world, enemy).collect::<Vec<_>>(),
+            vec![ship]
+        );
+        assert_eq!(Targeting::children(&world, station).count(), 0);
+
+        // retargeting moves the ship, and leaves the docking alone
+        Targeting::attach(&mut world, ship, other_ship).unwrap();
+        assert_eq!(Targeting::children(&world, enemy).count(), 0);
+        assert_eq!(Targeting::parent(&world, ship), Some(other_ship));
+        assert_eq!(DockedTo::parent(&world, ship), Some(station));
+
+        DockedTo::detach(&mut world, ship).unwrap();
+        assert_eq!(DockedTo::parent(&world, ship), None);
+        assert_eq!(
+            DockedTo::children(&world, station).collect::<Vec<_>>(),
+            vec![other_ship]
+        );
+        assert_eq!(Targeting::parent(&world, ship), Some(other_ship));
+    }
+}
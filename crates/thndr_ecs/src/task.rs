@@ -0,0 +1,207 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+/// A pool of worker threads, for offloading expensive work (decoding assets, generating terrain, pathfinding)
+/// so it doesn't stall the frame.
+///
+/// Spawn it as a component, and poll the returned [Task]s every frame.
+///
+/// *Note*: Tasks run outside of the schedule, so they can't touch the `World`.
+/// Move everything they need into the closure, and apply the result on the main thread once the task is finished.
+#[derive(Debug)]
+pub struct TaskPool {
+    pool: ThreadPool,
+}
+
+impl Default for TaskPool {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl TaskPool {
+    /// Creates a new [TaskPool] with `threads` worker threads.
+    /// If `threads` is `None`, one thread per logical CPU is used.
+    pub fn new(threads: Option<usize>) -> Self {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads.unwrap_or(0))
+            .thread_name(|i| format!("thndr-task-{i}"))
+            .build()
+            .expect("Failed to create task pool");
+
+        Self { pool }
+    }
+
+    /// Get the number of worker threads.
+    pub fn threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Runs `task` on one of the worker threads, returning a [Task] to get the result from later.
+    pub fn spawn<T, F>(&self, task: F) -> Task<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::sync_channel(1);
+
+        self.pool.spawn(move || {
+            // a panic would abort the whole process, so hand it to the owner of the task instead
+            let result = panic::catch_unwind(AssertUnwindSafe(task));
+            // the task might have been dropped, in which case nobody cares about the result
+            let _ = sender.send(result);
+        });
+
+        Task { receiver }
+    }
+}
+
+/// A dedicated pool of worker threads for the engine's parallel systems, e.g. transform propagation.
+///
+/// Spawn it as a singleton component, usually with `App::set_thread_pool_size`, and the parallel systems run
+/// their `par_*` iterators in it, with [ComputePool::install_in]. Without one they use rayon's global pool,
+/// which has one thread per logical CPU.
+///
+/// Unlike the [TaskPool], whose tasks run in the background over several frames,
+/// the work in this pool is done by the end of the system that started it.
+#[derive(Debug)]
+pub struct ComputePool {
+    pool: ThreadPool,
+}
+
+impl ComputePool {
+    /// Creates a new [ComputePool] with `threads` worker threads.
+    /// If `threads` is `None`, one thread per logical CPU is used.
+    pub fn new(threads: Option<usize>) -> Self {
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(threads.unwrap_or(0))
+            .thread_name(|i| format!("thndr-compute-{i}"))
+            .build()
+            .expect("Failed to create compute pool");
+
+        Self { pool }
+    }
+
+    /// Get the number of worker threads.
+    pub fn threads(&self) -> usize {
+        self.pool.current_num_threads()
+    }
+
+    /// Runs `op` in the pool, so every `par_*` iterator in it is spread over the worker threads of this pool.
+    /// Blocks until `op` is done.
+    pub fn install<R, OP>(&self, op: OP) -> R
+    where
+        R: Send,
+        OP: FnOnce() -> R + Send,
+    {
+        self.pool.install(op)
+    }
+
+    /// Runs `op` in `pool`, or on the current thread with rayon's global pool if there is none.
+    ///
+    /// ```ignore
+    /// fn simulate(world: SubWorld<(&ComputePool, &mut Particle)>) {
+    ///     let mut pool = world.query::<&ComputePool>();
+    ///     ComputePool::install_in(pool.single_mut().ok(), || {
+    ///         world.query::<&mut Particle>().iter().par_bridge().for_each(step);
+    ///     });
+    /// }
+    /// ```
+    pub fn install_in<R, OP>(pool: Option<&ComputePool>, op: OP) -> R
+    where
+        R: Send,
+        OP: FnOnce() -> R + Send,
+    {
+        match pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+}
+
+/// A task running on a [TaskPool].
+#[derive(Debug)]
+pub struct Task<T> {
+    receiver: Receiver<thread::Result<T>>,
+}
+
+impl<T> Task<T> {
+    /// Returns the result if the task is finished, without blocking.
+    /// Once the result has been returned, this returns `None` forever.
+    ///
+    /// If the task panicked, the panic is resumed on the calling thread.
+    pub fn poll(&mut self) -> Option<T> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result.unwrap_or_else(|e| panic::resume_unwind(e))),
+            Err(TryRecvError::Empty | TryRecvError::Disconnected) => None,
+        }
+    }
+
+    /// Blocks until the task is finished, and returns the result.
+    ///
+    /// If the task panicked, the panic is resumed on the calling thread.
+    pub fn block(self) -> T {
+        self.receiver
+            .recv()
+            .expect("Task result was already taken")
+            .unwrap_or_else(|e| panic::resume_unwind(e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spawn_tasks() {
+        let pool = TaskPool::new(Some(4));
+        assert_eq!(pool.threads(), 4);
+
+        let mut tasks = (0..32u64)
+            .map(|i| pool.spawn(move || (0..=i).sum::<u64>()))
+            .collect::<Vec<_>>();
+
+        let mut results = vec![None; tasks.len()];
+        while results.iter().any(Option::is_none) {
+            for (task, result) in tasks.iter_mut().zip(&mut results) {
+                if let Some(value) = task.poll() {
+                    *result = Some(value);
+                }
+            }
+        }
+
+        let expected = (0..32u64)
+            .map(|i| Some(i * (i + 1) / 2))
+            .collect::<Vec<_>>();
+        assert_eq!(results, expected);
+        assert!(tasks.iter_mut().all(|task| task.poll().is_none()));
+    }
+
+    #[test]
+    fn test_compute_pool() {
+        let pool = ComputePool::new(Some(2));
+        assert_eq!(pool.threads(), 2);
+        assert_eq!(pool.install(rayon::current_num_threads), 2);
+        assert_eq!(
+            ComputePool::install_in(Some(&pool), rayon::current_num_threads),
+            2
+        );
+        assert_eq!(
+            ComputePool::install_in(None, rayon::current_num_threads),
+            rayon::current_num_threads()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "task failed")]
+    fn test_task_panic() {
+        let pool = TaskPool::new(Some(1));
+
+        pool.spawn(|| panic!("task failed")).block();
+    }
+}
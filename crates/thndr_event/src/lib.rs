@@ -5,6 +5,11 @@
 //! For now, it simply works with closures, but in the future it will be extended to work with traits.
 //!
 
+use std::{
+    any::{type_name, TypeId},
+    cell::RefCell,
+};
+
 use thndr_app::prelude::*;
 use thndr_ecs::prelude::*;
 
@@ -12,49 +17,224 @@ use thndr_ecs::prelude::*;
 // this works since everything in hecs is re-exported from thndr_ecs
 extern crate self as hecs;
 
-/// The status of an [Event].
+pub use thndr_app::AppStartupCompleteEvent;
+pub use thndr_ecs::event::{Event, EventStatus};
+
+/// Settings for the [event_cleanup]. A singleton component, the defaults are used without one.
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
-pub enum EventStatus {
-    /// The event was not handled.
-    #[default]
-    NotHandled,
-    /// The event was handled.
-    Handled,
-    /// The event was consumed.
-    Consumed,
+pub struct EventCleanupSettings {
+    /// Whether `Rejected` events stay `Rejected` in the first cleanup pass, instead of becoming `Handled` like `NotHandled` events.
+    /// Handy to find events that every handler declined, e.g. keys no widget used. They are still despawned on time.
+    pub keep_rejected: bool,
 }
 
-/// The [Event] type. Used to represent an event.
-#[derive(Default, Debug, Clone)]
-pub struct Event {
-    /// The status of the event.
-    pub status: EventStatus,
-}
+/// The number of [event_cleanup] passes an event has survived. Inserted by the first pass, and kept apart
+/// from the [Event] so it can still be built from its status alone. Only the cleanup touches it.
+#[derive(Debug, Clone, Copy)]
+pub struct EventAge(u8);
 
 /// A system that handles event cleanup.
-pub fn event_cleanup(world: SubWorld<&mut Event>, mut commands: Commands) {
-    for (entity, event) in world.query::<&mut Event>().iter() {
-        match event.status {
-            EventStatus::NotHandled => event.status = EventStatus::Handled,
-            EventStatus::Handled => event.status = EventStatus::Consumed,
-            EventStatus::Consumed => commands.despawn(entity),
+///
+/// Every event survives exactly three passes of this system, no matter what handlers did to its
+/// status in the meantime:
+/// the first pass makes it `Handled`, the second makes it `Consumed`, and the third despawns it.
+/// `Rejected` events are treated like `NotHandled` ones, unless [EventCleanupSettings::keep_rejected] is set.
+pub fn event_cleanup(
+    world: SubWorld<(&mut Event, &mut EventAge, &EventCleanupSettings)>,
+    mut commands: Commands,
+) {
+    let settings = world
+        .query::<&EventCleanupSettings>()
+        .iter()
+        .next()
+        .map(|(_, settings)| *settings)
+        .unwrap_or_default();
+
+    // first despawn the events that have been through all their passes,
+    // and only then advance the rest, so an event is never advanced and despawned in the same pass.
+    for (entity, age) in world.query::<&EventAge>().with::<&Event>().iter() {
+        if age.0 >= 2 {
+            commands.write(move |world| {
+                despawn_detached(world, entity);
+            });
+        }
+    }
+
+    for (entity, (event, age)) in world.query::<(&mut Event, Option<&mut EventAge>)>().iter() {
+        match age.as_ref().map_or(0, |age| age.0) {
+            0 => {
+                let keep = settings.keep_rejected && event.status == EventStatus::Rejected;
+                if event.status.is_unclaimed() && !keep {
+                    event.status = EventStatus::Handled;
+                }
+            }
+            1 => event.status = EventStatus::Consumed,
+            _ => continue,
+        }
+
+        match age {
+            Some(age) => age.0 += 1,
+            None => commands.insert_one(entity, EventAge(1)),
         }
     }
 }
 
+/// Despawns an entity, detaching it from any [Tree] first so no dangling ids are left behind.
+/// Returns `false` if the entity didn't exist.
+fn despawn_detached(world: &mut World, entity: Entity) -> bool {
+    if world.get::<&Child<Tree>>(entity).is_ok() {
+        let _ = world.detach::<Tree>(entity);
+    }
+    if world.get::<&Parent<Tree>>(entity).is_ok() {
+        let _ = world.detach_children::<Tree>(entity);
+    }
+    world.despawn(entity).is_ok()
+}
+
+/// An event that is spawned when an entity is despawned through [EventCommandsExt::despawn_with_event].
+/// Handy for cleaning up anything that is keyed by entity, e.g. GPU resources or a spatial index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntityDespawnedEvent {
+    /// The entity that was despawned. It no longer exists.
+    pub entity: Entity,
+}
+
+/// Counts the unclaimed (`NotHandled` or `Rejected`) events with a `T` payload.
+pub fn event_count<T: Component>(world: &World) -> usize {
+    world
+        .query::<(&Event, &T)>()
+        .iter()
+        .filter(|(_, (event, _))| event.status.is_unclaimed())
+        .count()
+}
+
+/// The `EventCommandsExt` trait. Extends the `Commands` type with event related functionality.
+pub trait EventCommandsExt {
+    /// Despawn every entity with an [Event] component, no matter its status.
+    /// Useful when changing scenes, so stale events don't leak into the new scene.
+    ///
+    /// *Note*: Event entities that are part of a [Tree] (unlikely, but possible) are detached first.
+    fn clear_events(&mut self);
+
+    /// Despawn `entity`, and spawn an [EntityDespawnedEvent] for it.
+    /// Like [EventCommandsExt::clear_events], the entity is detached from its [Tree] first.
+    ///
+    /// *Note*: hecs has no removal callbacks, so only despawns going through this command are observed.
+    /// Entities despawned in any other way despawn silently.
+    fn despawn_with_event(&mut self, entity: Entity);
+
+    /// Spawn an event with all `components` attached to it, e.g. a payload and a routing tag.
+    /// A single component works too, but needs to be a tuple as well: `commands.send_event((DamageEvent { amount: 5 },))`.
+    ///
+    /// Listeners query the components they are interested in next to the [Event]:
+    ///
+    /// ```ignore
+    /// commands.send_event((DamageEvent { amount: 5 }, TargetTag(player)));
+    ///
+    /// for (_, (event, damage, target)) in world.query::<(&mut Event, &DamageEvent, &TargetTag)>().iter() {
+    ///     // only the damage events with a target
+    /// }
+    /// ```
+    fn send_event<B: DynamicBundle + Send + Sync + 'static>(&mut self, components: B);
+}
+
+impl EventCommandsExt for Commands<'_> {
+    fn clear_events(&mut self) {
+        self.write(|world| {
+            let events = world
+                .query::<&Event>()
+                .iter()
+                .map(|(entity, _)| entity)
+                .collect::<Vec<_>>();
+
+            for entity in events {
+                despawn_detached(world, entity);
+            }
+        });
+    }
+
+    fn despawn_with_event(&mut self, entity: Entity) {
+        self.write(move |world| {
+            if despawn_detached(world, entity) {
+                world.spawn((Event::default(), EntityDespawnedEvent { entity }));
+            }
+        });
+    }
+
+    fn send_event<B: DynamicBundle + Send + Sync + 'static>(&mut self, components: B) {
+        self.write(move |world| {
+            let mut builder = EntityBuilder::new();
+            builder.add_bundle(components).add(Event::default());
+
+            world.spawn(builder.build());
+        });
+    }
+}
+
+/// The event payload types registered with [EventAppExt::add_event]. A singleton component, for tooling and introspection.
+#[derive(Default, Debug)]
+pub struct EventRegistry {
+    types: Vec<(TypeId, &'static str)>,
+    cleanup: bool,
+}
+
+impl EventRegistry {
+    /// Check if events with a `T` payload were registered.
+    pub fn is_registered<T: Component>(&self) -> bool {
+        self.types.iter().any(|(id, _)| *id == TypeId::of::<T>())
+    }
+
+    /// Get the type names of all registered payloads, in the order they were registered.
+    pub fn type_names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.types.iter().map(|(_, name)| *name)
+    }
+}
+
+/// Calls `f` with the [EventRegistry], spawning it first if there is none.
+fn with_registry<R>(world: &mut World, f: impl FnOnce(&mut EventRegistry) -> R) -> R {
+    if let Some((_, registry)) = world.query_mut::<&mut EventRegistry>().into_iter().next() {
+        return f(registry);
+    }
+
+    let mut registry = EventRegistry::default();
+    let result = f(&mut registry);
+    world.spawn((registry,));
+
+    result
+}
+
+/// Adds the [event_cleanup] system, unless it was already added.
+fn add_cleanup(app: &mut App) {
+    let added = with_registry(&mut app.world, |registry| {
+        std::mem::replace(&mut registry.cleanup, true)
+    });
+
+    if !added {
+        app.register_resource::<EventRegistry>();
+        app.add_post_update_system(event_cleanup);
+    }
+}
+
 /// A plugin that handles event cleanup. At the end of every frame,
-/// this will make all `NotHandled` events `Handled`.
-/// and all `Handled` events `Consumed`.
+/// this will make all `NotHandled` events `Handled`,
+/// all `Handled` events `Consumed`,
 /// and delete all `Consumed` events.
+///
+/// Events live for exactly three frames after they are spawned, see [event_cleanup].
+///
+/// *Note*: [EventAppExt::add_event] adds the cleanup as well, but it never runs twice, so both can be used together.
 pub struct EventCleanupPlugin;
 
 impl Plugin for EventCleanupPlugin {
-    fn build(&self, app: &mut App) {
-        app.add_post_update_system(event_cleanup);
+    fn build(&self, app: &mut App) -> PluginResult {
+        add_cleanup(app);
+
+        Ok(())
     }
 }
 
 /// A bundle that contains an [Event] and a payload.
+/// To attach more than a single payload, use [EventCommandsExt::send_event].
 #[derive(Debug, Default, Clone, Bundle, DynamicBundleClone)]
 pub struct EventBundle<T: Component> {
     /// The event.
@@ -63,7 +243,398 @@ pub struct EventBundle<T: Component> {
     pub payload: T,
 }
 
+/// The `EventAppExt` trait. Extends the [App] with event related functionality.
+pub trait EventAppExt {
+    /// Register events with a `T` payload, e.g. `app.add_event::<DamageEvent>()`.
+    ///
+    /// This makes sure the events are cleaned up like described in [event_cleanup], even without the [EventCleanupPlugin],
+    /// and records `T` in the [EventRegistry]. Registering a type more than once has no effect.
+    fn add_event<T: Component>(&mut self) -> &mut Self;
+
+    /// Add an observer to the application. The `callback` is invoked once for every unclaimed
+    /// event with a `T` payload, see [EventStatus::is_unclaimed], after which the event is marked `Handled`.
+    ///
+    /// This is handy for reacting to rare events (e.g. "on level loaded, spawn the player"),
+    /// without writing a full system for it.
+    ///
+    /// *Note*: Observers run as direct systems at the start of `update`.
+    /// Multiple observers run in the order they were added.
+    fn add_observer<T, F>(&mut self, callback: F) -> &mut Self
+    where
+        T: Component + Clone,
+        F: 'static + FnMut(&T, &mut World);
+}
+
+impl EventAppExt for App {
+    fn add_event<T: Component>(&mut self) -> &mut Self {
+        add_cleanup(self);
+
+        with_registry(&mut self.world, |registry| {
+            if !registry.is_registered::<T>() {
+                registry.types.push((TypeId::of::<T>(), type_name::<T>()));
+            }
+        });
+
+        self
+    }
+
+    fn add_observer<T, F>(&mut self, callback: F) -> &mut Self
+    where
+        T: Component + Clone,
+        F: 'static + FnMut(&T, &mut World),
+    {
+        let callback = RefCell::new(callback);
+
+        self.add_direct_update_system(move |world| {
+            // the payloads are cloned, so the callback can have full access to the world
+            let mut payloads = Vec::new();
+            for (_, (event, payload)) in world.query_mut::<(&mut Event, &T)>() {
+                if event.status.is_unclaimed() {
+                    event.status = EventStatus::Handled;
+                    payloads.push(payload.clone());
+                }
+            }
+
+            let mut callback = callback.borrow_mut();
+            for payload in &payloads {
+                callback(payload, world);
+            }
+        })
+    }
+}
+
 /// Common types, traits, and functions.
 pub mod prelude {
-    pub use crate::{event_cleanup, Event, EventBundle, EventCleanupPlugin, EventStatus};
+    pub use crate::{
+        event_cleanup, event_count, AppStartupCompleteEvent, EntityDespawnedEvent, Event,
+        EventAppExt, EventBundle, EventCleanupPlugin, EventCleanupSettings, EventCommandsExt,
+        EventRegistry, EventStatus,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_lifetime() {
+        let mut world = World::new();
+        let mut schedule = ScheduleBuilder::new().add_system(event_cleanup).build();
+
+        let event = world.spawn((Event::default(),));
+
+        let expected = [
+            EventStatus::NotHandled,
+            EventStatus::Handled,
+            EventStatus::Consumed,
+        ];
+        for status in expected {
+            assert_eq!(world.get::<&Event>(event).unwrap().status, status);
+            schedule.execute((&mut world,)).unwrap();
+        }
+
+        assert!(!world.contains(event));
+    }
+
+    #[test]
+    fn test_event_lifetime_handled_early() {
+        let mut world = World::new();
+        let mut schedule = ScheduleBuilder::new().add_system(event_cleanup).build();
+
+        // a handler claiming the event straight away must not shorten its lifetime
+        let event = world.spawn((Event {
+            status: EventStatus::Handled,
+        },));
+
+        for _ in 0..3 {
+            assert!(world.contains(event));
+            schedule.execute((&mut world,)).unwrap();
+        }
+
+        assert!(!world.contains(event));
+    }
+
+    struct KeyEvent(char);
+
+    struct Typed(String);
+    struct MenuOpened(bool);
+
+    /// The focused text field, only takes letters and passes everything else on.
+    fn text_field(world: SubWorld<(&mut Event, &KeyEvent, &mut Typed)>) {
+        let mut typed = String::new();
+        for (_, (event, key)) in world.query::<(&mut Event, &KeyEvent)>().iter() {
+            if event.status != EventStatus::NotHandled {
+                continue;
+            }
+
+            if key.0.is_alphabetic() {
+                typed.push(key.0);
+                event.status = EventStatus::Handled;
+            } else {
+                event.status = EventStatus::Rejected;
+            }
+        }
+
+        for (_, text) in world.query::<&mut Typed>().iter() {
+            text.0.push_str(&typed);
+        }
+    }
+
+    /// The menu behind the text field, opens on escape.
+    fn menu(world: SubWorld<(&mut Event, &KeyEvent, &mut MenuOpened)>) {
+        let mut opened = false;
+        for (_, (event, key)) in world.query::<(&mut Event, &KeyEvent)>().iter() {
+            if event.status.is_unclaimed() && key.0 == '\u{1b}' {
+                opened = true;
+                event.status = EventStatus::Handled;
+            }
+        }
+
+        for (_, menu) in world.query::<&mut MenuOpened>().iter() {
+            menu.0 |= opened;
+        }
+    }
+
+    #[test]
+    fn test_rejected_chain() {
+        let mut world = World::new();
+        world.spawn((Typed(String::new()), MenuOpened(false)));
+        let mut handlers = ScheduleBuilder::new()
+            .add_system(text_field)
+            .add_system(menu)
+            .build();
+        let mut cleanup = ScheduleBuilder::new().add_system(event_cleanup).build();
+
+        for key in ['h', 'i', '\u{1b}', '1'] {
+            world.spawn((Event::default(), KeyEvent(key)));
+        }
+        handlers.execute((&mut world,)).unwrap();
+
+        let (typed, opened) = world
+            .query_mut::<(&Typed, &MenuOpened)>()
+            .into_iter()
+            .map(|(_, (typed, menu))| (typed.0.clone(), menu.0))
+            .next()
+            .unwrap();
+        assert_eq!(typed, "hi");
+        assert!(opened);
+
+        // the digit was declined by both, and is only treated like an unseen event by the cleanup
+        assert_eq!(event_count::<KeyEvent>(&world), 1);
+        let unused = world
+            .query_mut::<(&Event, &KeyEvent)>()
+            .into_iter()
+            .find(|(_, (_, key))| key.0 == '1')
+            .map(|(entity, _)| entity)
+            .unwrap();
+        assert_eq!(
+            world.get::<&Event>(unused).unwrap().status,
+            EventStatus::Rejected
+        );
+
+        cleanup.execute((&mut world,)).unwrap();
+        assert_eq!(
+            world.get::<&Event>(unused).unwrap().status,
+            EventStatus::Handled
+        );
+
+        // unless configured to keep them
+        world.spawn((EventCleanupSettings {
+            keep_rejected: true,
+        },));
+        let unused = world.spawn((
+            Event {
+                status: EventStatus::Rejected,
+            },
+            KeyEvent('2'),
+        ));
+        cleanup.execute((&mut world,)).unwrap();
+        assert_eq!(
+            world.get::<&Event>(unused).unwrap().status,
+            EventStatus::Rejected
+        );
+        cleanup.execute((&mut world,)).unwrap();
+        cleanup.execute((&mut world,)).unwrap();
+        assert!(!world.contains(unused));
+    }
+
+    fn clear(mut commands: Commands) {
+        commands.clear_events();
+    }
+
+    struct HitEvent {
+        amount: i32,
+    }
+
+    struct TargetTag(Entity);
+
+    #[test]
+    fn test_send_event() {
+        let mut world = World::new();
+        let target = world.spawn(());
+        let mut schedule = ScheduleBuilder::new()
+            .add_system(move |mut commands: Commands| {
+                commands.send_event((HitEvent { amount: 5 }, TargetTag(target)));
+                commands.send_event((HitEvent { amount: 1 },));
+            })
+            .build();
+        schedule.execute((&mut world,)).unwrap();
+
+        assert_eq!(event_count::<HitEvent>(&world), 2);
+
+        let targeted = world
+            .query_mut::<(&mut Event, &HitEvent, &TargetTag)>()
+            .into_iter()
+            .map(|(_, (event, hit, tag))| {
+                event.status = EventStatus::Handled;
+                (hit.amount, tag.0)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(targeted, vec![(5, target)]);
+        assert_eq!(event_count::<HitEvent>(&world), 1);
+    }
+
+    #[test]
+    fn test_clear_events() {
+        let mut world = World::new();
+        let mut schedule = ScheduleBuilder::new().add_system(clear).build();
+
+        for _ in 0..3 {
+            world.spawn((Event::default(), 1u32));
+        }
+        world.spawn((
+            Event {
+                status: EventStatus::Handled,
+            },
+            1u32,
+        ));
+        assert_eq!(event_count::<u32>(&world), 3);
+
+        schedule.execute((&mut world,)).unwrap();
+
+        assert_eq!(world.query::<&Event>().iter().count(), 0);
+        assert_eq!(event_count::<u32>(&world), 0);
+    }
+
+    fn despawn(world: SubWorld<&u32>, mut commands: Commands) {
+        for (entity, _) in world.query::<&u32>().iter() {
+            commands.despawn_with_event(entity);
+        }
+    }
+
+    #[test]
+    fn test_despawn_with_event() {
+        let mut world = World::new();
+        let mut schedule = ScheduleBuilder::new().add_system(despawn).build();
+
+        let entity = world.spawn((1u32,));
+        schedule.execute((&mut world,)).unwrap();
+
+        assert!(!world.contains(entity));
+        let despawned = world
+            .query::<(&Event, &EntityDespawnedEvent)>()
+            .iter()
+            .map(|(_, (_, e))| e.entity)
+            .collect::<Vec<_>>();
+        assert_eq!(despawned, vec![entity]);
+    }
+
+    /// Runs the startup of the app, followed by 4 frames.
+    struct FramesRunner;
+
+    impl ScheduleRunner for FramesRunner {
+        fn run_app(&mut self, app: &mut App) {
+            let mut schedules = Schedules::new(app);
+            schedules.run_startup(app);
+
+            for _ in 0..4 {
+                schedules.run_update(app);
+                schedules.run_render(app);
+            }
+        }
+    }
+
+    struct Frames(Vec<usize>);
+
+    #[test]
+    fn test_startup_complete_event() {
+        let mut app = App::new();
+        app.add_direct_startup_system(|world| {
+            world.spawn((Frames(Vec::new()),));
+            assert_eq!(event_count::<AppStartupCompleteEvent>(world), 0);
+        })
+        .add_direct_pre_update_system(|world| {
+            let count = event_count::<AppStartupCompleteEvent>(world);
+            for (_, frames) in world.query_mut::<&mut Frames>() {
+                frames.0.push(count);
+            }
+        })
+        .add_plugin(EventCleanupPlugin)
+        .set_runner(FramesRunner)
+        .run();
+
+        let frames = app
+            .world
+            .query_mut::<&Frames>()
+            .into_iter()
+            .map(|(_, frames)| frames.0.clone())
+            .collect::<Vec<_>>();
+        // the event is there before the user's first pre-update system, and only counts once
+        assert_eq!(frames, vec![vec![1, 0, 0, 0]]);
+
+        // and normal cleanup removes it
+        assert_eq!(
+            app.world
+                .query_mut::<&AppStartupCompleteEvent>()
+                .into_iter()
+                .count(),
+            0
+        );
+    }
+
+    struct DamageEvent;
+
+    /// Records how many [DamageEvent]s exist at the start of every frame.
+    fn count_damage_events(app: &mut App) -> Vec<usize> {
+        app.add_direct_startup_system(|world| {
+            world.spawn((Frames(Vec::new()),));
+            world.spawn((Event::default(), DamageEvent));
+        })
+        .add_direct_pre_update_system(|world| {
+            let count = world.query::<&DamageEvent>().iter().count();
+            for (_, frames) in world.query_mut::<&mut Frames>() {
+                frames.0.push(count);
+            }
+        })
+        .set_runner(FramesRunner)
+        .run();
+
+        app.world
+            .query_mut::<&Frames>()
+            .into_iter()
+            .flat_map(|(_, frames)| frames.0.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_add_event() {
+        let mut app = App::new();
+        app.add_event::<DamageEvent>().add_event::<DamageEvent>();
+
+        // cleaned up after three frames, without the plugin
+        assert_eq!(count_damage_events(&mut app), vec![1, 1, 1, 0]);
+
+        let registry = app.world.query_mut::<&EventRegistry>();
+        let names = registry
+            .into_iter()
+            .flat_map(|(_, registry)| registry.type_names().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec![type_name::<DamageEvent>()]);
+
+        // combined with the plugin, the cleanup still only runs once per frame
+        let mut app = App::new();
+        app.add_event::<DamageEvent>()
+            .add_plugin(EventCleanupPlugin);
+        assert_eq!(count_damage_events(&mut app), vec![1, 1, 1, 0]);
+    }
 }
@@ -0,0 +1,141 @@
+//! Renders a triangle into a texture without a window, and reads the result back.
+//!
+//! There is no pipeline abstraction yet, so the triangle is drawn with wgpu directly,
+//! through the device's [wgpu_device](thndr_gpu::backend::webgpu::device::Device::wgpu_device) escape hatch.
+
+use thndr_gpu::{
+    backend::Backend,
+    interface::{
+        device::{Device, DeviceConfig, PowerPreference},
+        instance::{Instance, InstanceConfig, Version},
+        texture::{Texture, TextureDescriptor, TextureFormat, TextureUsage},
+    },
+};
+
+const SIZE: u32 = 64;
+
+/// A red triangle around the center, in clip space.
+const SHADER: &str = r"
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    var positions = array<vec2<f32>, 3>(
+        vec2<f32>(0.0, 0.5),
+        vec2<f32>(-0.5, -0.5),
+        vec2<f32>(0.5, -0.5),
+    );
+    return vec4<f32>(positions[index], 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0, 0.0, 0.0, 1.0);
+}
+";
+
+/// Clears the texture to black, and draws the triangle on top.
+fn draw_triangle(device: &Device, texture: &Texture) {
+    let Device::WebGPU(device) = device;
+    let Texture::WebGPU(texture) = texture;
+    let (device, queue) = (device.wgpu_device(), device.wgpu_queue());
+
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Triangle"),
+        source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+    });
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Triangle"),
+        layout: None,
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(wgpu::TextureFormat::Rgba8Unorm.into())],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    });
+
+    let view = texture
+        .wgpu_texture
+        .create_view(&wgpu::TextureViewDescriptor::default());
+    let mut encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Triangle"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&pipeline);
+        pass.draw(0..3, 0..1);
+    }
+    queue.submit(Some(encoder.finish()));
+}
+
+/// Get the RGBA texel at `x`, `y`.
+fn pixel(texels: &[u8], x: u32, y: u32) -> &[u8] {
+    let offset = ((y * SIZE + x) * 4) as usize;
+    &texels[offset..offset + 4]
+}
+
+fn main() {
+    let instance = Instance::new(InstanceConfig {
+        backend: Backend::WebGPU,
+        app_name: "Headless".to_string(),
+        app_version: Version {
+            major: 1,
+            minor: 0,
+            patch: 0,
+        },
+    })
+    .expect("Failed to create the instance");
+
+    // no compatible surface, the device is fully offscreen
+    let device = instance
+        .create_device(
+            DeviceConfig {
+                power_preference: PowerPreference::Low,
+            },
+            None,
+        )
+        .expect("Failed to create the device");
+    println!("{}", device.info());
+
+    let texture = device
+        .create_texture(TextureDescriptor {
+            width: SIZE,
+            height: SIZE,
+            format: TextureFormat::Rgba8Unorm,
+            usage: TextureUsage::RENDER_ATTACHMENT | TextureUsage::COPY_SRC,
+        })
+        .expect("Failed to create the texture");
+
+    draw_triangle(&device, &texture);
+
+    let texels = device
+        .read_texture(&texture)
+        .expect("Failed to read back the texture");
+
+    let center = pixel(&texels, SIZE / 2, SIZE / 2);
+    let corner = pixel(&texels, 0, 0);
+    println!("Center pixel: {center:?}, corner pixel: {corner:?}");
+
+    // the center is covered by the triangle, the corner only by the clear color
+    assert_eq!(center, [255, 0, 0, 255]);
+    assert_eq!(corner, [0, 0, 0, 255]);
+}
@@ -0,0 +1,199 @@
+//! Opens a transparent window, clears it to fully transparent, and draws one opaque triangle on top.
+//!
+//! The surface is configured with [AlphaMode::PreMultiplied]. If the platform doesn't support it,
+//! a warning is logged and the window stays opaque, showing the triangle on black.
+//!
+//! There is no pipeline abstraction yet, so the triangle is drawn with wgpu directly,
+//! through the device's [wgpu_device](thndr_gpu::backend::webgpu::device::Device::wgpu_device) escape hatch.
+
+use std::sync::Arc;
+
+use thndr_gpu::{
+    backend::{webgpu::texture::wgpu_texture_format, Backend},
+    interface::{
+        device::{Device, DeviceConfig, PowerPreference},
+        instance::{Instance, InstanceConfig, Version},
+        surface::{AlphaMode, Surface, SurfaceConfig, SurfaceConfiguration, SurfaceTexture},
+    },
+};
+use winit::{
+    event::{Event, WindowEvent},
+    event_loop::EventLoop,
+    window::WindowBuilder,
+};
+
+/// An opaque triangle around the center, in clip space.
+const SHADER: &str = r"
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    var positions = array<vec2<f32>, 3>(
+        vec2<f32>(0.0, 0.5),
+        vec2<f32>(-0.5, -0.5),
+        vec2<f32>(0.5, -0.5),
+    );
+    return vec4<f32>(positions[index], 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    // fully opaque, so it is the same premultiplied or not
+    return vec4<f32>(0.2, 0.6, 1.0, 1.0);
+}
+";
+
+/// Creates the triangle pipeline, for the `format` of the surface.
+fn create_pipeline(device: &Device, format: wgpu::TextureFormat) -> wgpu::RenderPipeline {
+    let Device::WebGPU(device) = device;
+    let device = device.wgpu_device();
+
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Triangle"),
+        source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+    });
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Triangle"),
+        layout: None,
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(format.into())],
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+    })
+}
+
+/// Clears the frame to `[0, 0, 0, 0]`, and draws the triangle on top.
+fn draw(device: &Device, pipeline: &wgpu::RenderPipeline, frame: &SurfaceTexture) {
+    let Device::WebGPU(device) = device;
+    let SurfaceTexture::WebGPU(frame) = frame;
+    let (device, queue) = (device.wgpu_device(), device.wgpu_queue());
+
+    let view = frame
+        .wgpu_texture
+        .texture
+        .create_view(&wgpu::TextureViewDescriptor::default());
+    let mut encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Triangle"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    // any alpha left in the clear color would cover what is behind the window
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(pipeline);
+        pass.draw(0..3, 0..1);
+    }
+    queue.submit(Some(encoder.finish()));
+}
+
+/// Renders and presents a single frame, recovering from lost or outdated surfaces.
+fn render(
+    surface: &Surface,
+    device: &Device,
+    config: &SurfaceConfiguration,
+    pipeline: &wgpu::RenderPipeline,
+) {
+    match surface.get_current_texture() {
+        Ok(frame) => {
+            draw(device, pipeline, &frame);
+            frame.present();
+        }
+        Err(error) if error.is_recoverable() => {
+            if let Err(error) = surface.recover(device, config, &error) {
+                eprintln!("Failed to recover the surface: {error}");
+            }
+        }
+        Err(error) => panic!("{error}"),
+    }
+}
+
+fn main() {
+    let event_loop = EventLoop::new().expect("Failed to create the event loop");
+    let window = Arc::new(
+        WindowBuilder::new()
+            .with_title("Transparent")
+            .with_transparent(true)
+            .build(&event_loop)
+            .expect("Failed to create the window"),
+    );
+
+    let instance = Instance::new(InstanceConfig {
+        backend: Backend::WebGPU,
+        app_name: "Transparent".to_string(),
+        app_version: Version {
+            major: 1,
+            minor: 0,
+            patch: 0,
+        },
+    })
+    .expect("Failed to create the instance");
+
+    let surface = instance
+        .create_surface(SurfaceConfig {
+            raw_window_handle: window.clone(),
+        })
+        .expect("Failed to create the surface");
+    let device = instance
+        .create_device(
+            DeviceConfig {
+                power_preference: PowerPreference::Low,
+            },
+            Some(&surface),
+        )
+        .expect("Failed to create the device");
+
+    let format = surface.preferred_format(&device);
+    let size = window.inner_size();
+    let mut config = SurfaceConfiguration {
+        format: Some(format),
+        // falls back to opaque with a warning, if the platform doesn't support transparency
+        alpha_mode: AlphaMode::PreMultiplied,
+        ..SurfaceConfiguration::new(size.width, size.height)
+    };
+    if let Err(error) = surface.configure(&device, &config) {
+        eprintln!("Failed to configure the surface: {error}");
+    }
+
+    let pipeline = create_pipeline(&device, wgpu_texture_format(format));
+
+    event_loop
+        .run(move |event, target| {
+            let Event::WindowEvent { event, .. } = event else {
+                return;
+            };
+
+            match event {
+                WindowEvent::CloseRequested => target.exit(),
+                WindowEvent::Resized(size) => {
+                    config.width = size.width;
+                    config.height = size.height;
+                    // a minimized window can't be configured, it is configured again once it is restored
+                    if surface.configure(&device, &config).is_ok() {
+                        window.request_redraw();
+                    }
+                }
+                WindowEvent::RedrawRequested => render(&surface, &device, &config, &pipeline),
+                _ => {}
+            }
+        })
+        .expect("Failed to run the event loop");
+}
@@ -2,6 +2,19 @@ use wgpu::util::DeviceExt as _;
 
 use crate::interface::buffer::{self, BufferUsage};
 
+/// Converts the [BufferUsage] to the wgpu usages.
+/// Every buffer can be written to, see [crate::device::Device::write_buffer].
+fn usages(usage: BufferUsage) -> wgpu::BufferUsages {
+    let usages = match usage {
+        BufferUsage::Vertex => wgpu::BufferUsages::VERTEX,
+        BufferUsage::Index => wgpu::BufferUsages::INDEX,
+        BufferUsage::Uniform => wgpu::BufferUsages::UNIFORM,
+        BufferUsage::Storage => wgpu::BufferUsages::STORAGE,
+    };
+
+    usages | wgpu::BufferUsages::COPY_DST
+}
+
 /// A WebGPU buffer.
 #[derive(Debug)]
 pub struct Buffer {
@@ -15,12 +28,7 @@ impl Buffer {
         descriptor: buffer::BufferDescriptor,
         device: &wgpu::Device,
     ) -> buffer::Result<Self> {
-        let usage = match descriptor.usage {
-            BufferUsage::Vertex => wgpu::BufferUsages::VERTEX,
-            BufferUsage::Index => wgpu::BufferUsages::INDEX,
-            BufferUsage::Uniform => wgpu::BufferUsages::UNIFORM,
-            BufferUsage::Storage => wgpu::BufferUsages::STORAGE,
-        };
+        let usage = usages(descriptor.usage);
         let wgpu_buffer = device.create_buffer(&wgpu::BufferDescriptor {
             label: None,
             size: descriptor.size,
@@ -35,12 +43,7 @@ impl Buffer {
         descriptor: buffer::BufferDataDescriptor<'_>,
         device: &wgpu::Device,
     ) -> buffer::Result<Self> {
-        let usage = match descriptor.usage {
-            BufferUsage::Vertex => wgpu::BufferUsages::VERTEX,
-            BufferUsage::Index => wgpu::BufferUsages::INDEX,
-            BufferUsage::Uniform => wgpu::BufferUsages::UNIFORM,
-            BufferUsage::Storage => wgpu::BufferUsages::STORAGE,
-        };
+        let usage = usages(descriptor.usage);
         let wgpu_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: None,
             contents: descriptor.data,
@@ -1,4 +1,9 @@
-use crate::interface::{buffer, device};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use crate::interface::{buffer, device, texture};
 
 use super::surface;
 
@@ -8,6 +13,7 @@ pub struct Device {
     adapter: wgpu::Adapter,
     device: wgpu::Device,
     queue: wgpu::Queue,
+    lost: Arc<AtomicBool>,
 }
 
 impl Device {
@@ -21,13 +27,44 @@ impl Device {
 
         let (device, queue) = Self::create_device(&adapter, &config).await?;
 
+        let lost = Arc::new(AtomicBool::new(false));
+        let lost_flag = Arc::clone(&lost);
+        device.set_device_lost_callback(move |reason, message| {
+            // the callback is also called when the device is dropped, which isn't a loss
+            if matches!(
+                reason,
+                wgpu::DeviceLostReason::Unknown | wgpu::DeviceLostReason::Destroyed
+            ) {
+                tracing::error!("The device was lost: {message}");
+                lost_flag.store(true, Ordering::Release);
+            }
+        });
+
         Ok(Self {
             adapter,
             device,
             queue,
+            lost,
         })
     }
 
+    /// The internal wgpu adapter.
+    pub(crate) fn wgpu_adapter(&self) -> &wgpu::Adapter {
+        &self.adapter
+    }
+
+    /// The internal wgpu device.
+    ///
+    /// An escape hatch for what the interface doesn't cover yet, e.g. creating pipelines.
+    pub fn wgpu_device(&self) -> &wgpu::Device {
+        &self.device
+    }
+
+    /// The internal wgpu queue, to submit work recorded on the [Device::wgpu_device].
+    pub fn wgpu_queue(&self) -> &wgpu::Queue {
+        &self.queue
+    }
+
     /// Creates the adapter to use for the device.
     async fn create_adapter<'a>(
         instance: &wgpu::Instance,
@@ -93,12 +130,202 @@ impl device::DeviceFunctions for Device {
         ))
     }
 
+    fn write_buffer(
+        &self,
+        buffer: &buffer::Buffer,
+        offset: u64,
+        data: &[u8],
+    ) -> buffer::Result<()> {
+        let buffer::Buffer::WebGPU(buffer) = buffer;
+
+        self.queue.write_buffer(&buffer.wgpu_buffer, offset, data);
+
+        Ok(())
+    }
+
+    fn create_texture(
+        &self,
+        descriptor: texture::TextureDescriptor,
+    ) -> texture::Result<texture::Texture> {
+        Ok(texture::Texture::WebGPU(super::texture::Texture::new(
+            descriptor,
+            &self.device,
+        )?))
+    }
+
+    fn clear_texture(&self, texture: &texture::Texture, color: [f64; 4]) -> texture::Result<()> {
+        let texture::Texture::WebGPU(texture) = texture;
+
+        let view = texture
+            .wgpu_texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+
+        // the pass only clears, it is ended right away
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: color[0],
+                        g: color[1],
+                        b: color[2],
+                        a: color[3],
+                    }),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        self.queue.submit(Some(encoder.finish()));
+
+        Ok(())
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn read_texture(&self, texture: &texture::Texture) -> texture::Result<Vec<u8>> {
+        let texture::Texture::WebGPU(texture) = texture;
+        let descriptor = &texture.descriptor;
+
+        // rows in the staging buffer need to be aligned, the padding is stripped again after mapping
+        let row_size = descriptor.width * descriptor.format.bytes_per_texel();
+        let padded_row_size = row_size.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: padded_row_size as u64 * descriptor.height as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_texture_to_buffer(
+            texture.wgpu_texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &staging,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_size),
+                    rows_per_image: Some(descriptor.height),
+                },
+            },
+            super::texture::extent(descriptor),
+        );
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = staging.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+
+        receiver
+            .recv()
+            .map_err(|_| texture::TextureError::ReadbackFailed)?
+            .map_err(|_| texture::TextureError::ReadbackFailed)?;
+
+        let texels = slice
+            .get_mapped_range()
+            .chunks(padded_row_size as usize)
+            .flat_map(|row| &row[..row_size as usize])
+            .copied()
+            .collect();
+        staging.unmap();
+
+        Ok(texels)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn read_texture(&self, _texture: &texture::Texture) -> texture::Result<Vec<u8>> {
+        Err(texture::TextureError::BlockingUnsupported)
+    }
+
+    fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Acquire)
+    }
+
     fn info(&self) -> String {
         let info = self.adapter.get_info();
 
         format!(
-            "Device Name: {}\nDevice Type: {:?}",
-            info.name, info.device_type
+            "Device Name: {}\nDevice Type: {:?}\nBackend: {:?}",
+            info.name, info.device_type, info.backend
         )
     }
+
+    fn memory_info(&self) -> device::MemoryInfo {
+        #[cfg(not(any(target_arch = "wasm32", target_os = "macos", target_os = "ios")))]
+        if let Some(info) = self.vulkan_memory_info() {
+            return info;
+        }
+
+        // wgpu has no way to query the memory of the other backends
+        device::MemoryInfo::default()
+    }
+}
+
+impl Device {
+    /// Query the memory of the underlying Vulkan device. Returns `None` if the adapter isn't a Vulkan one.
+    #[cfg(not(any(target_arch = "wasm32", target_os = "macos", target_os = "ios")))]
+    fn vulkan_memory_info(&self) -> Option<device::MemoryInfo> {
+        use ash::vk;
+
+        let query = |adapter: Option<&wgpu::hal::vulkan::Adapter>| {
+            let adapter = adapter?;
+            let instance = adapter.shared_instance();
+            let physical_device = adapter.raw_physical_device();
+
+            // the budget needs the extension, and the Vulkan 1.1 query to chain it to
+            let supports_budget = instance.instance_api_version() >= vk::API_VERSION_1_1
+                && adapter
+                    .physical_device_capabilities()
+                    .supports_extension(vk::ExtMemoryBudgetFn::name());
+
+            let mut budget = vk::PhysicalDeviceMemoryBudgetPropertiesEXT::default();
+            let properties = if supports_budget {
+                let mut properties =
+                    vk::PhysicalDeviceMemoryProperties2::builder().push_next(&mut budget);
+                // SAFETY: the physical device belongs to the instance, and Vulkan 1.1 is supported
+                unsafe {
+                    instance
+                        .raw_instance()
+                        .get_physical_device_memory_properties2(physical_device, &mut properties);
+                }
+                properties.memory_properties
+            } else {
+                // SAFETY: the physical device belongs to the instance
+                unsafe {
+                    instance
+                        .raw_instance()
+                        .get_physical_device_memory_properties(physical_device)
+                }
+            };
+
+            let heaps = (0..properties.memory_heap_count as usize).filter(|&heap| {
+                properties.memory_heaps[heap]
+                    .flags
+                    .contains(vk::MemoryHeapFlags::DEVICE_LOCAL)
+            });
+            let sum = |size: &dyn Fn(usize) -> u64| heaps.clone().map(size).sum::<u64>();
+
+            Some(device::MemoryInfo {
+                total: Some(sum(&|heap| properties.memory_heaps[heap].size)),
+                used: supports_budget.then(|| sum(&|heap| budget.heap_usage[heap])),
+                budget: supports_budget.then(|| sum(&|heap| budget.heap_budget[heap])),
+            })
+        };
+
+        // SAFETY: the raw handles are only used for queries, and don't escape the callback
+        unsafe { self.adapter.as_hal::<wgpu::hal::api::Vulkan, _, _>(query) }
+    }
 }
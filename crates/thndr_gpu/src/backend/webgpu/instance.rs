@@ -22,6 +22,24 @@ impl Instance {
             config,
         })
     }
+
+    /// Creates a new [device::Device] using the given [device::DeviceConfig], without blocking.
+    pub async fn create_device_async(
+        &self,
+        config: device::DeviceConfig,
+        compatible_surface: Option<&surface::Surface<'_>>,
+    ) -> device::Result<device::Device> {
+        let compatible_surface = match compatible_surface {
+            Some(surface::Surface::WebGPU(surface)) => Some(surface),
+            None => None,
+            #[allow(unreachable_patterns)]
+            _ => return Err(device::DeviceError::InitializationFailed),
+        };
+
+        Ok(device::Device::WebGPU(
+            super::device::Device::new(config, &self.wgpu_instance, compatible_surface).await?,
+        ))
+    }
 }
 
 impl instance::InstanceFunctions for Instance {
@@ -37,16 +55,16 @@ impl instance::InstanceFunctions for Instance {
         config: device::DeviceConfig,
         compatible_surface: Option<&surface::Surface>,
     ) -> device::Result<device::Device> {
-        match compatible_surface {
-            Some(surface::Surface::WebGPU(surface)) => {
-                Ok(device::Device::WebGPU(pollster::block_on(
-                    super::device::Device::new(config, &this.wgpu_instance, Some(surface)),
-                )?))
-            }
-            None => Ok(device::Device::WebGPU(pollster::block_on(
-                super::device::Device::new(config, &this.wgpu_instance, None),
-            )?)),
-            _ => Err(device::DeviceError::InitializationFailed),
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            pollster::block_on(this.create_device_async(config, compatible_surface))
+        }
+
+        // the browser can't be blocked, the async version has to be used there
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = (this, config, compatible_surface);
+            Err(device::DeviceError::BlockingUnsupported)
         }
     }
 
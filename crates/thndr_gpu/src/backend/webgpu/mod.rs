@@ -9,3 +9,6 @@ pub mod surface;
 
 /// The implementation for the WebGPU buffer.
 pub mod buffer;
+
+/// The implementation for the WebGPU texture.
+pub mod texture;
@@ -1,4 +1,6 @@
-use crate::interface::surface;
+use crate::interface::{device, surface, texture::TextureFormat};
+
+use super::texture::{texture_format, wgpu_texture_format};
 
 /// The WebGPU surface.
 #[derive(Debug)]
@@ -19,3 +21,116 @@ impl<'surface> Surface<'surface> {
         Ok(Self { wgpu_surface })
     }
 }
+
+/// The WebGPU surface texture.
+#[derive(Debug)]
+pub struct SurfaceTexture {
+    /// The inner wgpu surface texture.
+    pub wgpu_texture: wgpu::SurfaceTexture,
+}
+
+impl surface::SurfaceFunctions for Surface<'_> {
+    fn capabilities(&self, device: &device::Device) -> surface::SurfaceCapabilities {
+        let device::Device::WebGPU(device) = device;
+
+        let capabilities = self.wgpu_surface.get_capabilities(device.wgpu_adapter());
+
+        surface::SurfaceCapabilities {
+            formats: capabilities
+                .formats
+                .into_iter()
+                .filter_map(texture_format)
+                .collect(),
+            present_modes: capabilities
+                .present_modes
+                .into_iter()
+                .filter_map(present_mode)
+                .collect(),
+            alpha_modes: capabilities
+                .alpha_modes
+                .into_iter()
+                .filter_map(alpha_mode)
+                .collect(),
+        }
+    }
+
+    fn configure(
+        &self,
+        device: &device::Device,
+        config: &surface::SurfaceConfiguration,
+        format: TextureFormat,
+    ) -> surface::Result<()> {
+        let device::Device::WebGPU(device) = device;
+
+        self.wgpu_surface.configure(
+            device.wgpu_device(),
+            &wgpu::SurfaceConfiguration {
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                format: wgpu_texture_format(format),
+                width: config.width,
+                height: config.height,
+                present_mode: wgpu_present_mode(config.present_mode),
+                desired_maximum_frame_latency: 2,
+                alpha_mode: wgpu_alpha_mode(config.alpha_mode),
+                view_formats: Vec::new(),
+            },
+        );
+
+        Ok(())
+    }
+
+    fn get_current_texture(&self) -> surface::Result<surface::SurfaceTexture> {
+        let wgpu_texture =
+            self.wgpu_surface
+                .get_current_texture()
+                .map_err(|error| match error {
+                    wgpu::SurfaceError::Timeout => surface::SurfaceError::Timeout,
+                    wgpu::SurfaceError::Outdated => surface::SurfaceError::Outdated,
+                    wgpu::SurfaceError::Lost => surface::SurfaceError::Lost,
+                    wgpu::SurfaceError::OutOfMemory => surface::SurfaceError::OutOfMemory,
+                })?;
+
+        Ok(surface::SurfaceTexture::WebGPU(SurfaceTexture {
+            wgpu_texture,
+        }))
+    }
+}
+
+fn wgpu_present_mode(mode: surface::PresentMode) -> wgpu::PresentMode {
+    match mode {
+        surface::PresentMode::Fifo => wgpu::PresentMode::Fifo,
+        surface::PresentMode::FifoRelaxed => wgpu::PresentMode::FifoRelaxed,
+        surface::PresentMode::Immediate => wgpu::PresentMode::Immediate,
+        surface::PresentMode::Mailbox => wgpu::PresentMode::Mailbox,
+    }
+}
+
+fn present_mode(mode: wgpu::PresentMode) -> Option<surface::PresentMode> {
+    match mode {
+        wgpu::PresentMode::Fifo => Some(surface::PresentMode::Fifo),
+        wgpu::PresentMode::FifoRelaxed => Some(surface::PresentMode::FifoRelaxed),
+        wgpu::PresentMode::Immediate => Some(surface::PresentMode::Immediate),
+        wgpu::PresentMode::Mailbox => Some(surface::PresentMode::Mailbox),
+        // the automatic modes are only used for configuring, they are never reported
+        wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync => None,
+    }
+}
+
+fn wgpu_alpha_mode(mode: surface::AlphaMode) -> wgpu::CompositeAlphaMode {
+    match mode {
+        surface::AlphaMode::Opaque => wgpu::CompositeAlphaMode::Opaque,
+        surface::AlphaMode::PreMultiplied => wgpu::CompositeAlphaMode::PreMultiplied,
+        surface::AlphaMode::PostMultiplied => wgpu::CompositeAlphaMode::PostMultiplied,
+        surface::AlphaMode::Inherit => wgpu::CompositeAlphaMode::Inherit,
+    }
+}
+
+fn alpha_mode(mode: wgpu::CompositeAlphaMode) -> Option<surface::AlphaMode> {
+    match mode {
+        wgpu::CompositeAlphaMode::Opaque => Some(surface::AlphaMode::Opaque),
+        wgpu::CompositeAlphaMode::PreMultiplied => Some(surface::AlphaMode::PreMultiplied),
+        wgpu::CompositeAlphaMode::PostMultiplied => Some(surface::AlphaMode::PostMultiplied),
+        wgpu::CompositeAlphaMode::Inherit => Some(surface::AlphaMode::Inherit),
+        wgpu::CompositeAlphaMode::Auto => None,
+    }
+}
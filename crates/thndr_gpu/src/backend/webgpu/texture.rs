@@ -0,0 +1,90 @@
+use crate::interface::texture::{self, TextureFormat, TextureUsage};
+
+/// Converts the [TextureUsage] to the wgpu usages.
+fn usages(usage: TextureUsage) -> wgpu::TextureUsages {
+    let mut usages = wgpu::TextureUsages::empty();
+
+    if usage.contains(TextureUsage::RENDER_ATTACHMENT) {
+        usages |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+    }
+    if usage.contains(TextureUsage::COPY_SRC) {
+        usages |= wgpu::TextureUsages::COPY_SRC;
+    }
+    if usage.contains(TextureUsage::COPY_DST) {
+        usages |= wgpu::TextureUsages::COPY_DST;
+    }
+    if usage.contains(TextureUsage::TEXTURE_BINDING) {
+        usages |= wgpu::TextureUsages::TEXTURE_BINDING;
+    }
+
+    usages
+}
+
+/// Converts a [TextureFormat] into a wgpu texture format.
+///
+/// An escape hatch for drawing with wgpu directly, e.g. to create a pipeline for the format of a surface.
+pub fn wgpu_texture_format(format: TextureFormat) -> wgpu::TextureFormat {
+    match format {
+        TextureFormat::Rgba8Unorm => wgpu::TextureFormat::Rgba8Unorm,
+        TextureFormat::Rgba8UnormSrgb => wgpu::TextureFormat::Rgba8UnormSrgb,
+        TextureFormat::Bgra8Unorm => wgpu::TextureFormat::Bgra8Unorm,
+        TextureFormat::Bgra8UnormSrgb => wgpu::TextureFormat::Bgra8UnormSrgb,
+        TextureFormat::Rgba16Float => wgpu::TextureFormat::Rgba16Float,
+        TextureFormat::Rgb10a2Unorm => wgpu::TextureFormat::Rgb10a2Unorm,
+    }
+}
+
+/// Converts a wgpu texture format into a [TextureFormat], if it is representable.
+pub(crate) fn texture_format(format: wgpu::TextureFormat) -> Option<TextureFormat> {
+    match format {
+        wgpu::TextureFormat::Rgba8Unorm => Some(TextureFormat::Rgba8Unorm),
+        wgpu::TextureFormat::Rgba8UnormSrgb => Some(TextureFormat::Rgba8UnormSrgb),
+        wgpu::TextureFormat::Bgra8Unorm => Some(TextureFormat::Bgra8Unorm),
+        wgpu::TextureFormat::Bgra8UnormSrgb => Some(TextureFormat::Bgra8UnormSrgb),
+        wgpu::TextureFormat::Rgba16Float => Some(TextureFormat::Rgba16Float),
+        wgpu::TextureFormat::Rgb10a2Unorm => Some(TextureFormat::Rgb10a2Unorm),
+        _ => None,
+    }
+}
+
+/// A WebGPU texture.
+#[derive(Debug)]
+pub struct Texture {
+    /// The inner wgpu texture.
+    pub wgpu_texture: wgpu::Texture,
+    /// The descriptor the texture was created with.
+    pub descriptor: texture::TextureDescriptor,
+}
+
+impl Texture {
+    /// Creates a new [Texture] with the given [texture::TextureDescriptor].
+    pub fn new(
+        descriptor: texture::TextureDescriptor,
+        device: &wgpu::Device,
+    ) -> texture::Result<Self> {
+        let wgpu_texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: extent(&descriptor),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu_texture_format(descriptor.format),
+            usage: usages(descriptor.usage),
+            view_formats: &[],
+        });
+
+        Ok(Self {
+            wgpu_texture,
+            descriptor,
+        })
+    }
+}
+
+/// The size of the texture, as a wgpu extent.
+pub(crate) fn extent(descriptor: &texture::TextureDescriptor) -> wgpu::Extent3d {
+    wgpu::Extent3d {
+        width: descriptor.width,
+        height: descriptor.height,
+        depth_or_array_layers: 1,
+    }
+}
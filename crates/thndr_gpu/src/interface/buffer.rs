@@ -8,8 +8,17 @@ pub enum BufferError {
     /// Not enough memory.
     #[error("Not enough memory.")]
     OutOfMemory,
+    /// The offset or size of a write is not a multiple of [COPY_ALIGNMENT].
+    #[error("The offset or size of the write is not a multiple of {COPY_ALIGNMENT} bytes.")]
+    Unaligned,
+    /// The write does not fit in the buffer.
+    #[error("The write does not fit in the buffer.")]
+    OutOfBounds,
 }
 
+/// The alignment, in bytes, of the offset and size of every write to a [Buffer].
+pub const COPY_ALIGNMENT: u64 = 4;
+
 /// The result of a buffer operation.
 pub type Result<T, E = BufferError> = std::result::Result<T, E>;
 
@@ -51,3 +60,13 @@ pub enum Buffer {
     /// The WebGPU buffer.
     WebGPU(webgpu::buffer::Buffer),
 }
+
+impl Buffer {
+    /// Get the size of the buffer in bytes.
+    pub fn size(&self) -> u64 {
+        match self {
+            #[cfg(feature = "webgpu")]
+            Buffer::WebGPU(buffer) => buffer.wgpu_buffer.size(),
+        }
+    }
+}
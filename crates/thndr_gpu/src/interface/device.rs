@@ -1,8 +1,9 @@
+use bytemuck::Pod;
 use thiserror::Error;
 
 use crate::backend::webgpu;
 
-use super::buffer;
+use super::{buffer, texture};
 
 /// Represents the power preference of a [Device].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -41,6 +42,31 @@ pub enum DeviceError {
     /// The initialisation of the device failed.
     #[error("The initialisation of the device failed.")]
     InitializationFailed,
+    /// Blocking device creation is not possible on this platform (e.g. the web).
+    #[error("Blocking device creation is not supported on this platform, use `create_device_async` instead.")]
+    BlockingUnsupported,
+}
+
+/// How much memory a [Device] has, see [Device::memory_info]. All sizes are in bytes.
+///
+/// The values are best-effort, and `None` where the backend can't report them:
+///
+/// | Backend | Reports |
+/// | --- | --- |
+/// | WebGPU on Vulkan | `total`, and `used` and `budget` if the driver supports `VK_EXT_memory_budget` |
+/// | WebGPU on anything else | Nothing |
+///
+/// *Note*: Integrated GPUs (and software renderers) share the system memory, so their `total` is the memory
+/// the driver makes available to the GPU, which is usually a large part of the system memory, and shared with the CPU.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryInfo {
+    /// The total device-local memory.
+    pub total: Option<u64>,
+    /// The device-local memory in use, by this process and every other process on the system.
+    pub used: Option<u64>,
+    /// The device-local memory this process can use before performance suffers, e.g. because memory is paged out.
+    /// This is what a streaming cache should size itself against.
+    pub budget: Option<u64>,
 }
 
 /// The result of creating a [Device].
@@ -58,8 +84,33 @@ pub trait DeviceFunctions {
         descriptor: buffer::BufferDataDescriptor<'_>,
     ) -> buffer::Result<buffer::Buffer>;
 
+    /// Writes `data` into the [buffer::Buffer], starting at `offset` bytes.
+    /// The bounds and alignment are already checked by [Device::write_buffer].
+    fn write_buffer(&self, buffer: &buffer::Buffer, offset: u64, data: &[u8])
+        -> buffer::Result<()>;
+
+    /// Creates a new [texture::Texture] with the given [texture::TextureDescriptor].
+    fn create_texture(
+        &self,
+        descriptor: texture::TextureDescriptor,
+    ) -> texture::Result<texture::Texture>;
+
+    /// Clears the [texture::Texture] to `color`, in a render pass.
+    /// The usage is already checked by [Device::clear_texture].
+    fn clear_texture(&self, texture: &texture::Texture, color: [f64; 4]) -> texture::Result<()>;
+
+    /// Copies the contents of the [texture::Texture] back to the CPU, blocking until the GPU is done.
+    /// The usage is already checked by [Device::read_texture].
+    fn read_texture(&self, texture: &texture::Texture) -> texture::Result<Vec<u8>>;
+
+    /// Check if the device was lost.
+    fn is_lost(&self) -> bool;
+
     /// Get a [String] with some information about the [Device].
     fn info(&self) -> String;
+
+    /// Get the [MemoryInfo] of the [Device].
+    fn memory_info(&self) -> MemoryInfo;
 }
 
 /// A [Device] allows for direct interaction with the GPU.
@@ -93,6 +144,162 @@ impl Device {
         }
     }
 
+    /// Create a new [buffer::Buffer] with the given usage, initialised with a slice of typed data.
+    ///
+    /// This is the typed version of [Device::create_buffer_with_data], the data is cast to bytes with [bytemuck].
+    /// `T` needs to be [Pod], so it can't contain padding bytes. Remember to make it `#[repr(C)]`, so the layout
+    /// matches what the shader expects.
+    ///
+    /// ```no_run
+    /// # use thndr_gpu::interface::{buffer::BufferUsage, device::Device};
+    /// use bytemuck::{Pod, Zeroable};
+    ///
+    /// #[repr(C)]
+    /// #[derive(Debug, Clone, Copy, Pod, Zeroable)]
+    /// struct Vertex {
+    ///     position: [f32; 3],
+    ///     color: [f32; 3],
+    /// }
+    ///
+    /// # fn example(device: &Device) {
+    /// let vertices = [
+    ///     Vertex { position: [0.0, 0.5, 0.0], color: [1.0, 0.0, 0.0] },
+    ///     Vertex { position: [-0.5, -0.5, 0.0], color: [0.0, 1.0, 0.0] },
+    ///     Vertex { position: [0.5, -0.5, 0.0], color: [0.0, 0.0, 1.0] },
+    /// ];
+    ///
+    /// let buffer = device
+    ///     .create_buffer_with_slice(BufferUsage::Vertex, &vertices)
+    ///     .unwrap();
+    /// # }
+    /// ```
+    pub fn create_buffer_with_slice<T: Pod>(
+        &self,
+        usage: buffer::BufferUsage,
+        data: &[T],
+    ) -> buffer::Result<buffer::Buffer> {
+        self.create_buffer_with_data(buffer::BufferDataDescriptor {
+            usage,
+            data: bytemuck::cast_slice(data),
+        })
+    }
+
+    /// Write `data` into the [buffer::Buffer], starting at `offset` bytes.
+    ///
+    /// Both `offset` and the size of `data` need to be a multiple of [buffer::COPY_ALIGNMENT],
+    /// and the write needs to fit in the buffer.
+    pub fn write_buffer(
+        &self,
+        buffer: &buffer::Buffer,
+        offset: u64,
+        data: &[u8],
+    ) -> buffer::Result<()> {
+        let size = data.len() as u64;
+        if !offset.is_multiple_of(buffer::COPY_ALIGNMENT)
+            || !size.is_multiple_of(buffer::COPY_ALIGNMENT)
+        {
+            return Err(buffer::BufferError::Unaligned);
+        }
+        if offset
+            .checked_add(size)
+            .is_none_or(|end| end > buffer.size())
+        {
+            return Err(buffer::BufferError::OutOfBounds);
+        }
+
+        match self {
+            #[cfg(feature = "webgpu")]
+            Device::WebGPU(device) => device.write_buffer(buffer, offset, data),
+        }
+    }
+
+    /// Write a slice of typed data into the [buffer::Buffer], starting at `offset` bytes.
+    ///
+    /// This is the typed version of [Device::write_buffer], with the same alignment requirements.
+    /// See [Device::create_buffer_with_slice] for the requirements on `T`.
+    pub fn write_buffer_slice<T: Pod>(
+        &self,
+        buffer: &buffer::Buffer,
+        offset: u64,
+        data: &[T],
+    ) -> buffer::Result<()> {
+        self.write_buffer(buffer, offset, bytemuck::cast_slice(data))
+    }
+
+    /// Create a new [texture::Texture] with the given [texture::TextureDescriptor].
+    ///
+    /// Textures don't need a window, so a [Device] created without a compatible surface can render
+    /// into a texture with [texture::TextureUsage::RENDER_ATTACHMENT] fully offscreen,
+    /// and read the result back with [Device::read_texture].
+    pub fn create_texture(
+        &self,
+        descriptor: texture::TextureDescriptor,
+    ) -> texture::Result<texture::Texture> {
+        if descriptor.width == 0 || descriptor.height == 0 {
+            return Err(texture::TextureError::InvalidSize);
+        }
+
+        match self {
+            #[cfg(feature = "webgpu")]
+            Device::WebGPU(device) => device.create_texture(descriptor),
+        }
+    }
+
+    /// Clear the [texture::Texture] to `color`, given as linear RGBA.
+    ///
+    /// The texture needs the [texture::TextureUsage::RENDER_ATTACHMENT] usage.
+    pub fn clear_texture(
+        &self,
+        texture: &texture::Texture,
+        color: [f64; 4],
+    ) -> texture::Result<()> {
+        Self::require_usage(texture, texture::TextureUsage::RENDER_ATTACHMENT)?;
+
+        match self {
+            #[cfg(feature = "webgpu")]
+            Device::WebGPU(device) => device.clear_texture(texture, color),
+        }
+    }
+
+    /// Read the contents of the [texture::Texture] back to the CPU.
+    ///
+    /// The texels are returned tightly packed, row by row, in the format of the texture.
+    /// This blocks until all work submitted to the GPU so far is done, so it's meant for tests, screenshots and tools,
+    /// not for every frame. The texture needs the [texture::TextureUsage::COPY_SRC] usage.
+    ///
+    /// *Note*: Blocking isn't possible on the web, so this always returns [texture::TextureError::BlockingUnsupported] there.
+    pub fn read_texture(&self, texture: &texture::Texture) -> texture::Result<Vec<u8>> {
+        Self::require_usage(texture, texture::TextureUsage::COPY_SRC)?;
+
+        match self {
+            #[cfg(feature = "webgpu")]
+            Device::WebGPU(device) => device.read_texture(texture),
+        }
+    }
+
+    /// Returns an error if the [texture::Texture] wasn't created with `usage`.
+    fn require_usage(
+        texture: &texture::Texture,
+        usage: texture::TextureUsage,
+    ) -> texture::Result<()> {
+        if texture.descriptor().usage.contains(usage) {
+            Ok(())
+        } else {
+            Err(texture::TextureError::MissingUsage(usage))
+        }
+    }
+
+    /// Check if the device was lost, e.g. after a driver update or a GPU reset (TDR on Windows).
+    ///
+    /// A lost device stays lost. Everything created with it is unusable, and a new device has to be created
+    /// with the [super::instance::Instance].
+    pub fn is_lost(&self) -> bool {
+        match self {
+            #[cfg(feature = "webgpu")]
+            Device::WebGPU(device) => device.is_lost(),
+        }
+    }
+
     /// Get a [String] with some information about the [Device].
     pub fn info(&self) -> String {
         match self {
@@ -100,11 +307,22 @@ impl Device {
             Device::WebGPU(device) => device.info(),
         }
     }
+
+    /// Get how much memory the [Device] has, e.g. for a texture streaming system to size its budget.
+    /// See [MemoryInfo] for what each backend reports.
+    ///
+    /// The usage changes all the time, so query it again instead of keeping it around.
+    pub fn memory_info(&self) -> MemoryInfo {
+        match self {
+            #[cfg(feature = "webgpu")]
+            Device::WebGPU(device) => device.memory_info(),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::super::{buffer, device, instance};
+    use super::super::{buffer, device, instance, texture};
 
     #[cfg(feature = "webgpu")]
     #[test]
@@ -171,4 +389,138 @@ mod tests {
 
         assert!(matches!(buffer, buffer::Buffer::WebGPU(_)));
     }
+
+    #[cfg(feature = "webgpu")]
+    #[test]
+    fn test_buffer_slices() {
+        let instance = instance::Instance::new(instance::InstanceConfig {
+            backend: crate::backend::Backend::WebGPU,
+            app_name: "Test".to_string(),
+            app_version: instance::Version {
+                major: 1,
+                minor: 0,
+                patch: 0,
+            },
+        })
+        .unwrap();
+
+        let device = instance
+            .create_device(
+                device::DeviceConfig {
+                    power_preference: device::PowerPreference::Low,
+                },
+                None,
+            )
+            .unwrap();
+
+        let buffer = device
+            .create_buffer_with_slice(buffer::BufferUsage::Vertex, &[[0.0f32; 3]; 4])
+            .unwrap();
+        assert_eq!(buffer.size(), 48);
+
+        assert!(device
+            .write_buffer_slice(&buffer, 12, &[[1.0f32; 3]; 2])
+            .is_ok());
+        assert_eq!(
+            device.write_buffer_slice(&buffer, 2, &[1.0f32]),
+            Err(buffer::BufferError::Unaligned)
+        );
+        assert_eq!(
+            device.write_buffer_slice(&buffer, 40, &[[1.0f32; 3]]),
+            Err(buffer::BufferError::OutOfBounds)
+        );
+    }
+
+    #[cfg(feature = "webgpu")]
+    #[test]
+    fn test_read_texture() {
+        let instance = instance::Instance::new(instance::InstanceConfig {
+            backend: crate::backend::Backend::WebGPU,
+            app_name: "Test".to_string(),
+            app_version: instance::Version {
+                major: 1,
+                minor: 0,
+                patch: 0,
+            },
+        })
+        .unwrap();
+
+        let device = instance
+            .create_device(
+                device::DeviceConfig {
+                    power_preference: device::PowerPreference::Low,
+                },
+                None,
+            )
+            .unwrap();
+
+        // 20 texels of 4 bytes don't fill a row of 256 bytes, so the padding has to be stripped
+        let texture = device
+            .create_texture(texture::TextureDescriptor {
+                width: 20,
+                height: 3,
+                format: texture::TextureFormat::Rgba8Unorm,
+                usage: texture::TextureUsage::RENDER_ATTACHMENT | texture::TextureUsage::COPY_SRC,
+            })
+            .unwrap();
+
+        device
+            .clear_texture(&texture, [0.0, 1.0, 0.0, 1.0])
+            .unwrap();
+        let texels = device.read_texture(&texture).unwrap();
+
+        assert_eq!(texels.len(), 20 * 3 * 4);
+        assert!(texels.chunks(4).all(|texel| texel == [0, 255, 0, 255]));
+
+        let write_only = device
+            .create_texture(texture::TextureDescriptor {
+                width: 1,
+                height: 1,
+                format: texture::TextureFormat::Rgba8Unorm,
+                usage: texture::TextureUsage::RENDER_ATTACHMENT,
+            })
+            .unwrap();
+        assert_eq!(
+            device.read_texture(&write_only),
+            Err(texture::TextureError::MissingUsage(
+                texture::TextureUsage::COPY_SRC
+            ))
+        );
+    }
+
+    // only Vulkan reports the memory, elsewhere there is nothing to check
+    #[cfg(all(
+        feature = "webgpu",
+        not(any(target_arch = "wasm32", target_os = "macos", target_os = "ios"))
+    ))]
+    #[test]
+    fn test_memory_info() {
+        let instance = instance::Instance::new(instance::InstanceConfig {
+            backend: crate::backend::Backend::WebGPU,
+            app_name: "Test".to_string(),
+            app_version: instance::Version {
+                major: 1,
+                minor: 0,
+                patch: 0,
+            },
+        })
+        .unwrap();
+
+        let device = instance
+            .create_device(
+                device::DeviceConfig {
+                    power_preference: device::PowerPreference::Low,
+                },
+                None,
+            )
+            .unwrap();
+
+        let memory = device.memory_info();
+        if device.info().contains("Vulkan") {
+            assert!(memory.total.is_some_and(|total| total > 0));
+        }
+        if let (Some(total), Some(used)) = (memory.total, memory.used) {
+            assert!(used <= total);
+        }
+    }
 }
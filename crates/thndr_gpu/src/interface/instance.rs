@@ -1,4 +1,5 @@
 use thiserror::Error;
+use tracing::{info, warn};
 
 use crate::backend::{webgpu, Backend};
 use std::{fmt::Debug, sync::Arc};
@@ -36,6 +37,23 @@ pub enum InstanceError {
     /// The backend failed to create the instance.
     #[error("The backend failed to create the instance.")]
     BackendError,
+    /// None of the backends passed to [Instance::new_with_fallback] could create an instance.
+    /// Contains the error of every backend that was tried, in order.
+    #[error("No backend could create an instance: {}", describe_failures(.0))]
+    AllBackendsFailed(Vec<(Backend, InstanceError)>),
+}
+
+/// Lists the failures of [InstanceError::AllBackendsFailed] in a single line.
+fn describe_failures(failures: &[(Backend, InstanceError)]) -> String {
+    if failures.is_empty() {
+        return "no backends were given.".to_string();
+    }
+
+    failures
+        .iter()
+        .map(|(backend, error)| format!("{backend:?}: {error}"))
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 /// The result of creating an [Instance].
@@ -90,7 +108,36 @@ impl Instance {
         }
     }
 
+    /// Creates a new [Instance], trying each of the `preferred` backends in order, and returning the first that works.
+    /// The backend in the `config` is ignored.
+    ///
+    /// Returns [InstanceError::AllBackendsFailed] with every failure if none of them work.
+    pub fn new_with_fallback(config: InstanceConfig, preferred: &[Backend]) -> Result<Self> {
+        let mut failures = Vec::new();
+
+        for &backend in preferred {
+            match Self::new(InstanceConfig {
+                backend,
+                ..config.clone()
+            }) {
+                Ok(instance) => {
+                    info!("Using the {backend:?} backend");
+                    return Ok(instance);
+                }
+                Err(error) => {
+                    warn!("The {backend:?} backend is unavailable: {error}");
+                    failures.push((backend, error));
+                }
+            }
+        }
+
+        Err(InstanceError::AllBackendsFailed(failures))
+    }
+
     /// Creates a new [device::Device] using the given [device::DeviceConfig].
+    ///
+    /// This blocks until the device is ready. Blocking is impossible on the web (wasm32),
+    /// so there this always returns [device::DeviceError::BlockingUnsupported], use [Instance::create_device_async] instead.
     pub fn create_device(
         &self,
         config: device::DeviceConfig,
@@ -105,6 +152,38 @@ impl Instance {
             ),
         }
     }
+
+    /// Creates a new [surface::Surface] for the window in the given [surface::SurfaceConfig].
+    ///
+    /// Pass it to [Instance::create_device] as the compatible surface, so the device can present to it.
+    pub fn create_surface<'a, W: surface::WindowHandle + 'a>(
+        &self,
+        config: surface::SurfaceConfig<W>,
+    ) -> surface::Result<surface::Surface<'a>> {
+        match self {
+            #[cfg(feature = "webgpu")]
+            Instance::WebGPU(instance) => {
+                webgpu::instance::Instance::create_surface(instance.clone(), config)
+            }
+        }
+    }
+
+    /// Creates a new [device::Device] using the given [device::DeviceConfig], without blocking.
+    /// This works everywhere, but is the only option on the web (wasm32), where the browser's event loop can't be blocked.
+    pub async fn create_device_async(
+        &self,
+        config: device::DeviceConfig,
+        compatible_surface: Option<&surface::Surface<'_>>,
+    ) -> device::Result<device::Device> {
+        match self {
+            #[cfg(feature = "webgpu")]
+            Instance::WebGPU(instance) => {
+                instance
+                    .create_device_async(config, compatible_surface)
+                    .await
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -136,4 +215,79 @@ mod tests {
 
         assert!(matches!(device, device::Device::WebGPU(_)));
     }
+
+    #[cfg(feature = "webgpu")]
+    #[test]
+    fn test_fallback_errors() {
+        let config = InstanceConfig {
+            backend: Backend::WebGPU,
+            app_name: "Test".to_string(),
+            app_version: Version {
+                major: 1,
+                minor: 0,
+                patch: 0,
+            },
+        };
+
+        let error = Instance::new_with_fallback(config, &[]).unwrap_err();
+        assert_eq!(error, InstanceError::AllBackendsFailed(Vec::new()));
+        assert_eq!(
+            error.to_string(),
+            "No backend could create an instance: no backends were given."
+        );
+
+        assert_eq!(
+            InstanceError::AllBackendsFailed(vec![(
+                Backend::WebGPU,
+                InstanceError::UnsupportedBackend
+            )])
+            .to_string(),
+            "No backend could create an instance: WebGPU: The backend is not supported."
+        );
+    }
+
+    #[cfg(feature = "webgpu")]
+    #[test]
+    fn test_webgpu_instance_fallback() {
+        let instance = Instance::new_with_fallback(
+            InstanceConfig {
+                backend: Backend::WebGPU,
+                app_name: "Test".to_string(),
+                app_version: Version {
+                    major: 1,
+                    minor: 0,
+                    patch: 0,
+                },
+            },
+            &[Backend::WebGPU],
+        )
+        .unwrap();
+
+        assert!(matches!(instance, Instance::WebGPU(_)));
+    }
+
+    #[cfg(feature = "webgpu")]
+    #[test]
+    fn test_webgpu_instance_async() {
+        let instance = Instance::new(InstanceConfig {
+            backend: Backend::WebGPU,
+            app_name: "Test".to_string(),
+            app_version: Version {
+                major: 1,
+                minor: 0,
+                patch: 0,
+            },
+        })
+        .unwrap();
+
+        let device = pollster::block_on(instance.create_device_async(
+            device::DeviceConfig {
+                power_preference: device::PowerPreference::High,
+            },
+            None,
+        ))
+        .unwrap();
+
+        assert!(matches!(device, device::Device::WebGPU(_)));
+    }
 }
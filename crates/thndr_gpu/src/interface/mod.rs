@@ -9,3 +9,6 @@ pub mod surface;
 
 /// Defines everything related to buffers.
 pub mod buffer;
+
+/// Defines everything related to textures.
+pub mod texture;
@@ -8,8 +8,15 @@ pub trait WindowHandle:
 {
 }
 
+impl<W> WindowHandle for W where
+    W: raw_window_handle::HasWindowHandle + raw_window_handle::HasDisplayHandle + Send + Sync
+{
+}
+
 use crate::backend::webgpu;
 
+use super::{device, texture::TextureFormat};
+
 /// The error type used by the [Surface] type.
 #[derive(Debug, Clone, PartialEq, Eq, Error)]
 pub enum SurfaceError {
@@ -19,6 +26,39 @@ pub enum SurfaceError {
     /// Failed to configure the surface.
     #[error("Failed to configure the surface.")]
     ConfigureError,
+    /// Getting the next frame took too long. Recoverable, skip the frame.
+    #[error("Timed out while getting the next frame.")]
+    Timeout,
+    /// The surface changed (e.g. it was resized) and doesn't match its configuration anymore.
+    /// Recoverable, reconfigure the surface.
+    #[error("The surface is outdated and needs to be reconfigured.")]
+    Outdated,
+    /// The surface was lost, e.g. after a GPU reset. Recoverable, reconfigure the surface.
+    #[error("The surface was lost and needs to be reconfigured.")]
+    Lost,
+    /// There is no memory left for the next frame. Not recoverable.
+    #[error("Not enough memory for the next frame.")]
+    OutOfMemory,
+    /// The device the surface is configured for was lost, e.g. after a driver update or a GPU reset (TDR on Windows).
+    /// Not recoverable with the current device, the device and everything created with it need to be recreated.
+    #[error("The device was lost and needs to be recreated.")]
+    DeviceLost,
+}
+
+impl SurfaceError {
+    /// Check if the error can be recovered from with [Surface::recover], without recreating the device.
+    ///
+    /// | Error | Recovery |
+    /// | --- | --- |
+    /// | [SurfaceError::Timeout] | Skip the frame |
+    /// | [SurfaceError::Outdated], [SurfaceError::Lost] | Reconfigure the surface |
+    /// | Any other error | None, [SurfaceError::DeviceLost] needs a new device |
+    pub fn is_recoverable(&self) -> bool {
+        matches!(
+            self,
+            SurfaceError::Timeout | SurfaceError::Outdated | SurfaceError::Lost
+        )
+    }
 }
 
 /// The result type used by the [Surface] type.
@@ -31,10 +71,346 @@ pub struct SurfaceConfig<W: WindowHandle> {
     pub raw_window_handle: Arc<W>,
 }
 
+/// How presented frames are synced to the display.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PresentMode {
+    /// Wait for the vertical blank, queuing up frames. No tearing, and supported everywhere.
+    #[default]
+    Fifo,
+    /// Like [PresentMode::Fifo], but a late frame is presented right away, which may tear.
+    FifoRelaxed,
+    /// Present right away, without waiting. Lowest latency, but may tear.
+    Immediate,
+    /// Wait for the vertical blank, but replace the queued frame with the newest one. No tearing, low latency.
+    Mailbox,
+}
+
+/// How the alpha channel of a presented frame is composited with whatever is behind the window.
+///
+/// Only a transparent window shows what is behind it, e.g. for a HUD over the desktop. Such an overlay needs
+/// [AlphaMode::PreMultiplied], and every frame needs to be cleared to `[0.0, 0.0, 0.0, 0.0]`,
+/// since any alpha left in the clear color covers the desktop.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlphaMode {
+    /// The alpha channel is ignored, the window is opaque.
+    #[default]
+    Opaque,
+    /// The colors are expected to be already multiplied by the alpha.
+    PreMultiplied,
+    /// The colors are multiplied by the alpha when compositing.
+    PostMultiplied,
+    /// The platform decides, based on settings outside of the application.
+    Inherit,
+}
+
+/// What a [Surface] supports, when used with a specific [device::Device].
+///
+/// The lists are ordered by preference of the platform, and are empty if the surface is incompatible with the device.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SurfaceCapabilities {
+    /// The supported texture formats.
+    /// Formats that aren't representable by [TextureFormat] are left out.
+    pub formats: Vec<TextureFormat>,
+    /// The supported present modes.
+    pub present_modes: Vec<PresentMode>,
+    /// The supported alpha modes.
+    pub alpha_modes: Vec<AlphaMode>,
+}
+
+impl SurfaceCapabilities {
+    /// Pick the alpha mode to configure for the `requested` one: `requested` itself if it's supported,
+    /// or else [AlphaMode::Opaque], or else whatever the platform prefers.
+    /// Returns `None` if no alpha modes are supported.
+    pub fn resolve_alpha_mode(&self, requested: AlphaMode) -> Option<AlphaMode> {
+        [requested, AlphaMode::Opaque]
+            .iter()
+            .find(|mode| self.alpha_modes.contains(mode))
+            .or_else(|| self.alpha_modes.first())
+            .copied()
+    }
+
+    /// Pick the preferred format: the first sRGB format, or else the first format.
+    /// Returns `None` if no formats are supported.
+    pub fn preferred_format(&self) -> Option<TextureFormat> {
+        self.formats
+            .iter()
+            .find(|format| format.is_srgb())
+            .or_else(|| self.formats.first())
+            .copied()
+    }
+}
+
+/// The configuration of a [Surface], see [Surface::configure].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SurfaceConfiguration {
+    /// The width of the surface. In pixels.
+    pub width: u32,
+    /// The height of the surface. In pixels.
+    pub height: u32,
+    /// The format of the surface. If `None`, the [Surface::preferred_format] is used.
+    pub format: Option<TextureFormat>,
+    /// The present mode of the surface.
+    pub present_mode: PresentMode,
+    /// How the frames are composited with what is behind the window, see [AlphaMode] for transparent windows.
+    /// If the platform doesn't support it, a warning is logged, and the surface falls back to [AlphaMode::Opaque].
+    pub alpha_mode: AlphaMode,
+}
+
+impl SurfaceConfiguration {
+    /// Creates a new [SurfaceConfiguration] of the given size, with the preferred format, [PresentMode::Fifo]
+    /// and [AlphaMode::Opaque].
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            format: None,
+            present_mode: PresentMode::default(),
+            alpha_mode: AlphaMode::default(),
+        }
+    }
+}
+
+/// The next frame of a [Surface], see [Surface::get_current_texture].
+#[derive(Debug)]
+pub enum SurfaceTexture {
+    #[cfg(feature = "webgpu")]
+    /// The WebGPU surface texture.
+    WebGPU(webgpu::surface::SurfaceTexture),
+}
+
+impl SurfaceTexture {
+    /// Check if the frame can still be used, but the surface should be reconfigured for the best performance.
+    pub fn is_suboptimal(&self) -> bool {
+        match self {
+            #[cfg(feature = "webgpu")]
+            SurfaceTexture::WebGPU(texture) => texture.wgpu_texture.suboptimal,
+        }
+    }
+
+    /// Present the frame to the window.
+    pub fn present(self) {
+        match self {
+            #[cfg(feature = "webgpu")]
+            SurfaceTexture::WebGPU(texture) => texture.wgpu_texture.present(),
+        }
+    }
+}
+
+/// The required functionality for a [Surface].
+pub trait SurfaceFunctions {
+    /// Get the [SurfaceCapabilities] of the surface, when used with `device`.
+    fn capabilities(&self, device: &device::Device) -> SurfaceCapabilities;
+
+    /// Configure the surface for use with `device`.
+    /// The configuration is already validated by [Surface::configure], and the `format` and alpha mode are resolved.
+    fn configure(
+        &self,
+        device: &device::Device,
+        config: &SurfaceConfiguration,
+        format: TextureFormat,
+    ) -> Result<()>;
+
+    /// Get the next frame of the surface.
+    fn get_current_texture(&self) -> Result<SurfaceTexture>;
+}
+
 /// A [Surface] allows for drawing to a window or other surface.
+///
+/// # Recovering from errors
+///
+/// The GPU can be reset while the game runs, e.g. by a driver update, a laptop switching GPUs or a TDR on Windows.
+/// [Surface::get_current_texture] then returns an error, which should be handled each frame instead of unwrapped:
+///
+/// ```no_run
+/// # use thndr_gpu::interface::{device::Device, surface::{Surface, SurfaceConfiguration, SurfaceError}};
+/// # fn frame(surface: &Surface, device: &Device, config: &SurfaceConfiguration) {
+/// match surface.get_current_texture() {
+///     Ok(frame) => {
+///         // render to the frame ...
+///         frame.present();
+///     }
+///     Err(error) if error.is_recoverable() => {
+///         // reconfigures the surface, or skips the frame on a timeout
+///         if let Err(error) = surface.recover(device, config, &error) {
+///             tracing::error!("Failed to recover the surface: {error}");
+///         }
+///     }
+///     Err(SurfaceError::DeviceLost) => {
+///         // drop the device and everything created with it, and create a new one with the instance
+///     }
+///     Err(error) => panic!("{error}"),
+/// }
+/// # }
+/// ```
 #[derive(Debug)]
 pub enum Surface<'surface> {
     #[cfg(feature = "webgpu")]
     /// The WebGPU surface.
     WebGPU(webgpu::surface::Surface<'surface>),
 }
+
+impl Surface<'_> {
+    /// Get the [SurfaceCapabilities] of the surface, when used with `device`.
+    pub fn capabilities(&self, device: &device::Device) -> SurfaceCapabilities {
+        match self {
+            #[cfg(feature = "webgpu")]
+            Surface::WebGPU(surface) => surface.capabilities(device),
+        }
+    }
+
+    /// Get the preferred format of the surface, when used with `device`. This is an sRGB format whenever possible,
+    /// see [TextureFormat] for why that matters.
+    ///
+    /// Falls back to [TextureFormat::Bgra8UnormSrgb], the most widely supported format, if the surface reports no formats.
+    pub fn preferred_format(&self, device: &device::Device) -> TextureFormat {
+        self.capabilities(device)
+            .preferred_format()
+            .unwrap_or(TextureFormat::Bgra8UnormSrgb)
+    }
+
+    /// Configure the surface for use with `device`. This needs to be done before rendering,
+    /// and again whenever the window is resized.
+    ///
+    /// Returns [SurfaceError::ConfigureError] if the size is zero (e.g. a minimized window),
+    /// or if the format or present mode isn't supported. An unsupported alpha mode falls back to opaque instead,
+    /// since the window is still usable without transparency.
+    pub fn configure(&self, device: &device::Device, config: &SurfaceConfiguration) -> Result<()> {
+        if config.width == 0 || config.height == 0 {
+            return Err(SurfaceError::ConfigureError);
+        }
+
+        let capabilities = self.capabilities(device);
+        let format = match config.format {
+            Some(format) => format,
+            None => capabilities
+                .preferred_format()
+                .ok_or(SurfaceError::ConfigureError)?,
+        };
+        if !capabilities.formats.contains(&format)
+            || !capabilities.present_modes.contains(&config.present_mode)
+        {
+            return Err(SurfaceError::ConfigureError);
+        }
+
+        let alpha_mode = capabilities
+            .resolve_alpha_mode(config.alpha_mode)
+            .ok_or(SurfaceError::ConfigureError)?;
+        if alpha_mode != config.alpha_mode {
+            tracing::warn!(
+                "The alpha mode {:?} isn't supported, falling back to {:?}",
+                config.alpha_mode,
+                alpha_mode
+            );
+        }
+        let config = SurfaceConfiguration {
+            alpha_mode,
+            ..*config
+        };
+
+        match self {
+            #[cfg(feature = "webgpu")]
+            Surface::WebGPU(surface) => surface.configure(device, &config, format),
+        }
+    }
+
+    /// Get the next frame of the surface, to render to and present.
+    ///
+    /// The surface needs to be configured with [Surface::configure] first.
+    /// See [SurfaceError::is_recoverable] for which errors can be handled with [Surface::recover].
+    pub fn get_current_texture(&self) -> Result<SurfaceTexture> {
+        match self {
+            #[cfg(feature = "webgpu")]
+            Surface::WebGPU(surface) => surface.get_current_texture(),
+        }
+    }
+
+    /// Try to recover from an `error` returned by [Surface::get_current_texture], by reconfiguring the surface
+    /// for `device` with `config`. A [SurfaceError::Timeout] needs no recovery, the frame is simply skipped.
+    ///
+    /// Returns [SurfaceError::DeviceLost] if `device` was lost, in which case a new device has to be created,
+    /// and the error itself if it isn't recoverable.
+    pub fn recover(
+        &self,
+        device: &device::Device,
+        config: &SurfaceConfiguration,
+        error: &SurfaceError,
+    ) -> Result<()> {
+        if device.is_lost() {
+            return Err(SurfaceError::DeviceLost);
+        }
+
+        match error {
+            SurfaceError::Timeout => Ok(()),
+            SurfaceError::Outdated | SurfaceError::Lost => self.configure(device, config),
+            error => Err(error.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferred_format() {
+        let mut capabilities = SurfaceCapabilities {
+            formats: vec![
+                TextureFormat::Bgra8Unorm,
+                TextureFormat::Rgba8UnormSrgb,
+                TextureFormat::Bgra8UnormSrgb,
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            capabilities.preferred_format(),
+            Some(TextureFormat::Rgba8UnormSrgb)
+        );
+
+        // without an sRGB format, the platform's preference wins
+        capabilities.formats = vec![TextureFormat::Rgba16Float, TextureFormat::Bgra8Unorm];
+        assert_eq!(
+            capabilities.preferred_format(),
+            Some(TextureFormat::Rgba16Float)
+        );
+
+        capabilities.formats.clear();
+        assert_eq!(capabilities.preferred_format(), None);
+    }
+
+    #[test]
+    fn test_alpha_mode_fallback() {
+        let mut capabilities = SurfaceCapabilities {
+            alpha_modes: vec![AlphaMode::Opaque, AlphaMode::PreMultiplied],
+            ..Default::default()
+        };
+        assert_eq!(
+            capabilities.resolve_alpha_mode(AlphaMode::PreMultiplied),
+            Some(AlphaMode::PreMultiplied)
+        );
+        assert_eq!(
+            capabilities.resolve_alpha_mode(AlphaMode::PostMultiplied),
+            Some(AlphaMode::Opaque)
+        );
+
+        // without opaque, the platform's preference wins
+        capabilities.alpha_modes = vec![AlphaMode::Inherit];
+        assert_eq!(
+            capabilities.resolve_alpha_mode(AlphaMode::PreMultiplied),
+            Some(AlphaMode::Inherit)
+        );
+
+        capabilities.alpha_modes.clear();
+        assert_eq!(capabilities.resolve_alpha_mode(AlphaMode::Opaque), None);
+    }
+
+    #[test]
+    fn test_recoverable_errors() {
+        assert!(SurfaceError::Timeout.is_recoverable());
+        assert!(SurfaceError::Outdated.is_recoverable());
+        assert!(SurfaceError::Lost.is_recoverable());
+
+        assert!(!SurfaceError::OutOfMemory.is_recoverable());
+        assert!(!SurfaceError::DeviceLost.is_recoverable());
+        assert!(!SurfaceError::ConfigureError.is_recoverable());
+    }
+}
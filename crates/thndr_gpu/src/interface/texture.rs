@@ -0,0 +1,139 @@
+use std::ops::BitOr;
+
+use thiserror::Error;
+
+use crate::backend::webgpu;
+
+/// All texture-related errors.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TextureError {
+    /// The width or height of the texture is zero.
+    #[error("The width and height of a texture must be greater than zero.")]
+    InvalidSize,
+    /// The texture wasn't created with the usage the operation needs.
+    #[error("The texture is missing the {0:?} usage.")]
+    MissingUsage(TextureUsage),
+    /// Reading the texture back failed.
+    #[error("Failed to read back the texture.")]
+    ReadbackFailed,
+    /// Blocking read-back is not possible on this platform (e.g. the web).
+    #[error("Blocking texture read-back is not supported on this platform.")]
+    BlockingUnsupported,
+}
+
+/// The result of a texture operation.
+pub type Result<T, E = TextureError> = std::result::Result<T, E>;
+
+/// The format of the texels in a texture.
+///
+/// Formats ending in `Srgb` store colors gamma encoded, and the GPU converts them to and from linear values
+/// when they are sampled or rendered to. Shaders always work with linear colors, so rendering to an sRGB surface
+/// gives correct output on a regular monitor. Rendering to a linear (`Unorm`) surface instead displays the linear values
+/// as is, which looks too dark, unless the shader does the conversion itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextureFormat {
+    /// Red, green, blue and alpha, 8 bits each, linear.
+    Rgba8Unorm,
+    /// Red, green, blue and alpha, 8 bits each, sRGB encoded.
+    Rgba8UnormSrgb,
+    /// Blue, green, red and alpha, 8 bits each, linear.
+    Bgra8Unorm,
+    /// Blue, green, red and alpha, 8 bits each, sRGB encoded.
+    Bgra8UnormSrgb,
+    /// Red, green, blue and alpha, as 16 bit floats. Used for HDR.
+    Rgba16Float,
+    /// Red, green and blue with 10 bits each, and 2 bits of alpha, linear.
+    Rgb10a2Unorm,
+}
+
+impl TextureFormat {
+    /// Check if the format is sRGB encoded.
+    pub fn is_srgb(&self) -> bool {
+        matches!(
+            self,
+            TextureFormat::Rgba8UnormSrgb | TextureFormat::Bgra8UnormSrgb
+        )
+    }
+
+    /// Get the size of a single texel, in bytes.
+    pub fn bytes_per_texel(&self) -> u32 {
+        match self {
+            TextureFormat::Rgba16Float => 8,
+            _ => 4,
+        }
+    }
+}
+
+/// The ways a [Texture] can be used. Combine them with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureUsage(u32);
+
+impl TextureUsage {
+    /// The texture can be rendered to.
+    pub const RENDER_ATTACHMENT: Self = Self(1);
+    /// The texture can be copied from, e.g. to read it back with [super::device::Device::read_texture].
+    pub const COPY_SRC: Self = Self(1 << 1);
+    /// The texture can be copied to.
+    pub const COPY_DST: Self = Self(1 << 2);
+    /// The texture can be sampled in a shader.
+    pub const TEXTURE_BINDING: Self = Self(1 << 3);
+
+    /// Check if all usages in `other` are part of these usages.
+    pub fn contains(&self, other: TextureUsage) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for TextureUsage {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// The descriptor for creating a new [Texture].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureDescriptor {
+    /// The width of the texture. In texels.
+    pub width: u32,
+    /// The height of the texture. In texels.
+    pub height: u32,
+    /// The format of the texture.
+    pub format: TextureFormat,
+    /// The usage of the texture.
+    pub usage: TextureUsage,
+}
+
+/// A [Texture] is an image on the GPU, that can be rendered to and sampled from.
+#[derive(Debug)]
+pub enum Texture {
+    #[cfg(feature = "webgpu")]
+    /// The WebGPU texture.
+    WebGPU(webgpu::texture::Texture),
+}
+
+impl Texture {
+    /// Get the descriptor the texture was created with.
+    pub fn descriptor(&self) -> &TextureDescriptor {
+        match self {
+            #[cfg(feature = "webgpu")]
+            Texture::WebGPU(texture) => &texture.descriptor,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_texture_usage() {
+        let usage = TextureUsage::RENDER_ATTACHMENT | TextureUsage::COPY_SRC;
+
+        assert!(usage.contains(TextureUsage::RENDER_ATTACHMENT));
+        assert!(usage.contains(TextureUsage::COPY_SRC));
+        assert!(!usage.contains(TextureUsage::COPY_DST));
+        assert!(!usage.contains(TextureUsage::COPY_SRC | TextureUsage::TEXTURE_BINDING));
+    }
+}
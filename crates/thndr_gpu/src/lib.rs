@@ -5,10 +5,28 @@
 //!
 //! The plan is to support multiple backends, staring with WGPU.
 //!
+//! ## Features
+//!
+//! At least one backend feature is required, the crate doesn't compile without one.
+//!
+//! | Feature | Default | Backend | Pulls in |
+//! | --- | --- | --- | --- |
+//! | `webgpu` | yes | [Backend::WebGPU](backend::Backend::WebGPU) | `wgpu` |
+//!
+
+// every interface type is an enum with a variant per backend, so without a backend nothing can be created,
+// and the errors the compiler gives for the empty enums are anything but helpful
+#[cfg(not(feature = "webgpu"))]
+compile_error!(
+    "thndr_gpu needs at least one backend: enable the `webgpu` feature, which is on by default, \
+     or remove `default-features = false`."
+);
 
 /// Defines what backend is being used for the GPU.
 pub mod backend;
 
+// left out without a backend, so the error above is the only one
+#[cfg(feature = "webgpu")]
 /// Defines the abstract interface for the GPU.
 /// We are NOT going to use traits, instead we will use an enum for static dispatch.
 /// The reason for this is that the rust compiler can inline those calls,
@@ -0,0 +1,228 @@
+use glam::Vec4;
+
+/// A color with red, green, blue and alpha components from `0.0` to `1.0`.
+///
+/// The color components are stored in sRGB, which is what color pickers, hex codes and image editors use.
+/// Shaders, blending and the GPU's clear color work with linear values though, so use [Color::to_linear] for anything
+/// sent to the GPU. Passing sRGB values where linear ones are expected is the classic cause of washed-out rendering,
+/// as the GPU encodes them to sRGB a second time when writing to an sRGB render target.
+/// Alpha is always linear.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    /// The red component, in sRGB.
+    pub r: f32,
+    /// The green component, in sRGB.
+    pub g: f32,
+    /// The blue component, in sRGB.
+    pub b: f32,
+    /// The alpha component.
+    pub a: f32,
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::WHITE
+    }
+}
+
+impl Color {
+    /// Fully transparent black.
+    pub const TRANSPARENT: Self = Self::rgba(0.0, 0.0, 0.0, 0.0);
+    /// Black.
+    pub const BLACK: Self = Self::rgb(0.0, 0.0, 0.0);
+    /// White.
+    pub const WHITE: Self = Self::rgb(1.0, 1.0, 1.0);
+    /// Gray, halfway between black and white in sRGB.
+    pub const GRAY: Self = Self::rgb(0.5, 0.5, 0.5);
+    /// Red.
+    pub const RED: Self = Self::rgb(1.0, 0.0, 0.0);
+    /// Green.
+    pub const GREEN: Self = Self::rgb(0.0, 1.0, 0.0);
+    /// Blue.
+    pub const BLUE: Self = Self::rgb(0.0, 0.0, 1.0);
+    /// Yellow.
+    pub const YELLOW: Self = Self::rgb(1.0, 1.0, 0.0);
+    /// Cyan.
+    pub const CYAN: Self = Self::rgb(0.0, 1.0, 1.0);
+    /// Magenta.
+    pub const MAGENTA: Self = Self::rgb(1.0, 0.0, 1.0);
+
+    /// Creates an opaque color from sRGB components.
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::rgba(r, g, b, 1.0)
+    }
+
+    /// Creates a color from sRGB components and alpha.
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Creates a color from linear components and alpha, e.g. the result of a lighting calculation.
+    pub fn linear_rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self::rgba(linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), a)
+    }
+
+    /// Parses a hex color, either `#RRGGBB` or `#RRGGBBAA`. The `#` is optional.
+    ///
+    /// Returns `None` if the string isn't a valid hex color.
+    pub fn hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+            return None;
+        }
+
+        let channel = |i: usize| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .ok()
+                .map(|c| c as f32 / 255.0)
+        };
+
+        let alpha = if hex.len() == 8 { channel(6)? } else { 1.0 };
+        Some(Self::rgba(channel(0)?, channel(2)?, channel(4)?, alpha))
+    }
+
+    /// Creates an opaque color from hue, saturation and lightness.
+    ///
+    /// The hue is in degrees and wraps around, saturation and lightness go from `0.0` to `1.0`.
+    pub fn hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+        let saturation = saturation.clamp(0.0, 1.0);
+        let lightness = lightness.clamp(0.0, 1.0);
+
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let m = lightness - chroma / 2.0;
+
+        let (r, g, b) = match hue as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+
+        Self::rgb(r + m, g + m, b + m)
+    }
+
+    /// Returns the same color with a different alpha.
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.a = alpha;
+        self
+    }
+
+    /// Returns the sRGB components and alpha, as `[r, g, b, a]`.
+    pub fn to_array(self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+
+    /// Returns the linear components and alpha, as `[r, g, b, a]`. Use this for anything sent to the GPU.
+    pub fn to_linear(self) -> [f32; 4] {
+        [
+            srgb_to_linear(self.r),
+            srgb_to_linear(self.g),
+            srgb_to_linear(self.b),
+            self.a,
+        ]
+    }
+}
+
+impl From<Color> for [f32; 4] {
+    fn from(color: Color) -> Self {
+        color.to_array()
+    }
+}
+
+impl From<[f32; 4]> for Color {
+    fn from([r, g, b, a]: [f32; 4]) -> Self {
+        Self::rgba(r, g, b, a)
+    }
+}
+
+/// Converts to the sRGB components and alpha.
+impl From<Color> for Vec4 {
+    fn from(color: Color) -> Self {
+        Vec4::from_array(color.to_array())
+    }
+}
+
+/// Converts a single sRGB component to linear, with the exact piecewise sRGB curve.
+pub fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a single linear component to sRGB, with the exact piecewise sRGB curve.
+pub fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: [f32; 4], b: [f32; 4]) {
+        for (a, b) in a.iter().zip(b) {
+            assert!((a - b).abs() < 1e-5, "{a} != {b}");
+        }
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(Color::hex("#FF0000"), Some(Color::RED));
+        assert_eq!(Color::hex("00ff00"), Some(Color::GREEN));
+        assert_close(
+            Color::hex("#33669980").unwrap().to_array(),
+            [0.2, 0.4, 0.6, 128.0 / 255.0],
+        );
+
+        assert_eq!(Color::hex("#FFF"), None);
+        assert_eq!(Color::hex("#GG0000"), None);
+        assert_eq!(Color::hex("#FF00000"), None);
+        assert_eq!(Color::hex("#€0000"), None);
+    }
+
+    #[test]
+    fn test_hsl() {
+        assert_close(Color::hsl(0.0, 1.0, 0.5).to_array(), Color::RED.to_array());
+        assert_close(
+            Color::hsl(120.0, 1.0, 0.5).to_array(),
+            Color::GREEN.to_array(),
+        );
+        assert_close(
+            Color::hsl(-120.0, 1.0, 0.5).to_array(),
+            Color::BLUE.to_array(),
+        );
+        assert_close(
+            Color::hsl(60.0, 1.0, 0.5).to_array(),
+            Color::YELLOW.to_array(),
+        );
+        assert_close(
+            Color::hsl(200.0, 0.0, 0.5).to_array(),
+            Color::GRAY.to_array(),
+        );
+    }
+
+    #[test]
+    fn test_srgb_round_trip() {
+        // sRGB gray is much darker in linear space
+        assert!((srgb_to_linear(0.5) - 0.21404).abs() < 1e-4);
+
+        for i in 0..=255 {
+            let c = i as f32 / 255.0;
+            assert!((linear_to_srgb(srgb_to_linear(c)) - c).abs() < 1e-5);
+        }
+
+        let color = Color::rgba(0.2, 0.4, 0.6, 0.5);
+        let [r, g, b, a] = color.to_linear();
+        assert_close(Color::linear_rgba(r, g, b, a).to_array(), color.to_array());
+        assert_eq!(color.to_linear()[3], 0.5);
+    }
+}
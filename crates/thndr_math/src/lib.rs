@@ -2,7 +2,12 @@
 //!
 //! `thndr_math` is a collection of math utilities for the Thunder game engine.
 
+/// Provides the [color::Color] type.
+pub mod color;
+
 /// Common math types and functions.
 pub mod prelude {
     pub use glam::f32::*;
+
+    pub use crate::color::Color;
 }
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use thndr_app::prelude::*;
+use thndr_ecs::prelude::*;
+
+/// A stable id of a replicated entity, the same on the server and every client.
+///
+/// An [Entity] depends on the order entities were spawned in, so it differs between machines. Replication refers
+/// to entities by their [NetworkId] instead, and [NetworkEntities] maps it back to the local [Entity].
+///
+/// Ids are generational, like an [Entity]: the low 32 bits are the index and the high 32 bits the generation.
+/// Once an id is freed, its index can be reused, but with the next generation, so a late packet about a despawned
+/// entity never matches the entity that took its place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NetworkId(pub u64);
+
+impl NetworkId {
+    fn from_parts(index: u32, generation: u32) -> Self {
+        Self(((generation as u64) << 32) | index as u64)
+    }
+
+    /// Get the index of the id, which is reused once the id is freed.
+    pub fn index(&self) -> u32 {
+        self.0 as u32
+    }
+
+    /// Get the generation of the id, which is bumped every time its index is freed.
+    pub fn generation(&self) -> u32 {
+        (self.0 >> 32) as u32
+    }
+}
+
+impl fmt::Display for NetworkId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}v{}", self.index(), self.generation())
+    }
+}
+
+/// A resource handing out [NetworkId]s. Only the server, which is the authority on ids, has one.
+/// Clients use the ids sent by the server as they are.
+///
+/// New indices are handed out in increasing order. Indices of freed ids are reused with the next generation,
+/// and an index whose generation would wrap around is retired for good.
+#[derive(Debug, Default)]
+pub struct NetworkIdAllocator {
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+}
+
+/// A slot of a [NetworkIdAllocator].
+#[derive(Debug)]
+struct Slot {
+    generation: u32,
+    alive: bool,
+}
+
+impl NetworkIdAllocator {
+    /// Creates a new [NetworkIdAllocator], without any ids handed out.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a new id, e.g. for an entity about to be replicated.
+    pub fn allocate(&mut self) -> NetworkId {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    alive: false,
+                });
+                (self.slots.len() - 1) as u32
+            }
+        };
+
+        let slot = &mut self.slots[index as usize];
+        slot.alive = true;
+        NetworkId::from_parts(index, slot.generation)
+    }
+
+    /// Free `id`, so its index can be reused with the next generation.
+    /// Returns `false` if it was already freed, or wasn't handed out by this allocator.
+    pub fn free(&mut self, id: NetworkId) -> bool {
+        if !self.is_alive(id) {
+            return false;
+        }
+
+        let slot = &mut self.slots[id.index() as usize];
+        slot.alive = false;
+
+        // an index whose generation would wrap around is retired, so old ids can never match again
+        if let Some(generation) = slot.generation.checked_add(1) {
+            slot.generation = generation;
+            self.free.push(id.index());
+        }
+
+        true
+    }
+
+    /// Check if `id` was handed out, and hasn't been freed since.
+    pub fn is_alive(&self, id: NetworkId) -> bool {
+        self.slots
+            .get(id.index() as usize)
+            .is_some_and(|slot| slot.alive && slot.generation == id.generation())
+    }
+}
+
+/// A resource mapping every [NetworkId] in the world to its local [Entity], and back.
+///
+/// It is kept up to date by [sync_network_ids], so entities spawned or despawned this frame are only looked up
+/// correctly once it ran. Incoming packets look up the entity they are about, outgoing ones the id to send.
+#[derive(Debug, Default)]
+pub struct NetworkEntities {
+    entities: HashMap<NetworkId, Entity>,
+    ids: HashMap<Entity, NetworkId>,
+}
+
+impl NetworkEntities {
+    /// Creates a new, empty [NetworkEntities] lookup.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the local entity of `id`.
+    pub fn entity(&self, id: NetworkId) -> Option<Entity> {
+        self.entities.get(&id).copied()
+    }
+
+    /// Get the id of the local `entity`, if it is replicated.
+    pub fn id(&self, entity: Entity) -> Option<NetworkId> {
+        self.ids.get(&entity).copied()
+    }
+
+    /// Map `id` to `entity`, replacing any previous mapping of either of them.
+    pub fn insert(&mut self, id: NetworkId, entity: Entity) {
+        self.remove(id);
+        self.remove_entity(entity);
+        self.entities.insert(id, entity);
+        self.ids.insert(entity, id);
+    }
+
+    /// Remove the mapping of `id`, and return its entity.
+    pub fn remove(&mut self, id: NetworkId) -> Option<Entity> {
+        let entity = self.entities.remove(&id)?;
+        self.ids.remove(&entity);
+        Some(entity)
+    }
+
+    /// Remove the mapping of `entity`, and return its id.
+    pub fn remove_entity(&mut self, entity: Entity) -> Option<NetworkId> {
+        let id = self.ids.remove(&entity)?;
+        self.entities.remove(&id);
+        Some(id)
+    }
+
+    /// Get the number of mapped ids.
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Check if no ids are mapped.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Iterate over all ids, with their entities.
+    pub fn iter(&self) -> impl Iterator<Item = (NetworkId, Entity)> + '_ {
+        self.entities.iter().map(|(id, entity)| (*id, *entity))
+    }
+}
+
+/// A system that updates the [NetworkEntities] lookup from the [NetworkId] components in the world.
+///
+/// Ids of entities that were despawned, or lost their [NetworkId], are removed from the lookup, and freed
+/// if there is a [NetworkIdAllocator]. Ids are expected to be unique, the last entity wins if they aren't.
+pub fn sync_network_ids(
+    world: SubWorld<(&NetworkId, &mut NetworkEntities, &mut NetworkIdAllocator)>,
+) {
+    let ids = world
+        .query::<&NetworkId>()
+        .iter()
+        .map(|(entity, id)| (entity, *id))
+        .collect::<HashMap<_, _>>();
+
+    let mut q = world.query::<&mut NetworkEntities>();
+    let Ok(entities) = q.single_mut() else {
+        return;
+    };
+    let mut q_allocator = world.query::<&mut NetworkIdAllocator>();
+    let mut allocator = q_allocator.single_mut().ok();
+
+    let gone = entities
+        .iter()
+        .filter(|(id, entity)| ids.get(entity) != Some(id))
+        .collect::<Vec<_>>();
+    for (id, _) in gone {
+        entities.remove(id);
+        if let Some(allocator) = allocator.as_mut() {
+            allocator.free(id);
+        }
+    }
+
+    for (entity, id) in ids {
+        entities.insert(id, entity);
+    }
+}
+
+/// A plugin that keeps track of the [NetworkId]s of replicated entities, see [NetworkEntities].
+///
+/// Set [NetworkIdPlugin::server] on the server, to add the [NetworkIdAllocator] it hands out ids with.
+/// The lookup is updated in `post_update`, after the systems spawning and despawning entities ran.
+#[derive(Default, Debug)]
+pub struct NetworkIdPlugin {
+    /// Whether this is the server, which is the authority on ids.
+    pub server: bool,
+}
+
+impl Plugin for NetworkIdPlugin {
+    fn build(&self, app: &mut App) -> PluginResult {
+        app.world.spawn((NetworkEntities::new(),));
+        app.register_resource::<NetworkEntities>();
+
+        if self.server {
+            app.world.spawn((NetworkIdAllocator::new(),));
+            app.register_resource::<NetworkIdAllocator>();
+        }
+
+        app.add_post_update_system(sync_network_ids);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_are_not_reused() {
+        let mut allocator = NetworkIdAllocator::new();
+        let player = allocator.allocate();
+        let crate_ = allocator.allocate();
+        assert!(player < crate_);
+
+        let mut world = World::new();
+        world.spawn((allocator,));
+        world.spawn((NetworkEntities::new(),));
+        let player_entity = world.spawn((player,));
+        let crate_entity = world.spawn((crate_,));
+
+        let mut schedule = ScheduleBuilder::new().add_system(sync_network_ids).build();
+        schedule.execute((&mut world,)).unwrap();
+
+        {
+            let mut q = world.query::<&NetworkEntities>();
+            let entities = q.single_mut().unwrap();
+            assert_eq!(entities.entity(player), Some(player_entity));
+            assert_eq!(entities.id(crate_entity), Some(crate_));
+        }
+
+        world.despawn(crate_entity).unwrap();
+        schedule.execute((&mut world,)).unwrap();
+
+        {
+            let mut q = world.query::<&NetworkEntities>();
+            let entities = q.single_mut().unwrap();
+            assert_eq!(entities.entity(crate_), None);
+            assert_eq!(entities.len(), 1);
+        }
+
+        let mut q = world.query::<&mut NetworkIdAllocator>();
+        let allocator = q.single_mut().unwrap();
+        assert!(!allocator.is_alive(crate_));
+        assert!(!allocator.free(crate_));
+
+        // the index is reused, but the old id still doesn't match
+        let barrel = allocator.allocate();
+        assert_eq!(barrel.index(), crate_.index());
+        assert_ne!(barrel, crate_);
+        assert!(allocator.is_alive(player));
+        assert!(allocator.is_alive(barrel));
+    }
+}
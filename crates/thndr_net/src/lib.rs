@@ -0,0 +1,24 @@
+//! # thndr_net
+//!
+//! `thndr_net` provides the building blocks for networked games, for the Thunder Engine.
+//!
+//! It doesn't send anything over the network itself, that's up to the transport of your choice.
+//! Instead, it applies what the transport received to the world, e.g. the [NetworkTransform](transform::NetworkTransform)
+//! of a remote player. Entities are referred to by their [NetworkId](id::NetworkId), which is the same on every machine.
+//!
+
+/// Provides the [id::NetworkId] component, for referring to the same entity on the server and every client.
+pub mod id;
+/// Provides the [transform::NetworkTransform] component, for correcting entities towards the server's transforms.
+pub mod transform;
+
+/// Common types, traits, and functions.
+pub mod prelude {
+    pub use crate::id::{
+        sync_network_ids, NetworkEntities, NetworkId, NetworkIdAllocator, NetworkIdPlugin,
+    };
+    pub use crate::transform::{
+        correct_network_transforms, Correction, NetworkTransform, NetworkTransformPlugin,
+        DEFAULT_SNAP_DISTANCE,
+    };
+}
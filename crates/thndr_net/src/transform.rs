@@ -0,0 +1,266 @@
+use std::collections::{HashMap, HashSet};
+
+use thndr_app::prelude::*;
+use thndr_ecs::prelude::*;
+use thndr_math::prelude::*;
+use thndr_time::prelude::*;
+use thndr_transform::prelude::*;
+
+/// How a [NetworkTransform] corrects the entity towards the target.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Correction {
+    /// Jump to the target right away.
+    Snap,
+    /// Move towards the target smoothly, hiding small corrections from the player.
+    ///
+    /// Every second, the fraction `1 - e^-rate` of the error is corrected, so after `1 / rate` seconds
+    /// about 63% of it is gone. The correction is frame-rate independent.
+    Smooth {
+        /// How fast the error is corrected, per second.
+        rate: f32,
+    },
+}
+
+impl Default for Correction {
+    fn default() -> Self {
+        Correction::Smooth { rate: 10.0 }
+    }
+}
+
+/// The default [NetworkTransform::snap_distance].
+pub const DEFAULT_SNAP_DISTANCE: f32 = 4.0;
+
+/// The authoritative world space transform of a networked entity, as last received from the server.
+///
+/// Attach it to remote players and other replicated entities, and call [NetworkTransform::set_target]
+/// whenever a packet arrives. The [correct_network_transforms] system then moves the entity towards the target.
+///
+/// The first target is always snapped to, as there is nothing to smooth from yet, and so are targets further away
+/// than [NetworkTransform::snap_distance], e.g. after a respawn. Call [NetworkTransform::teleport] to snap on purpose.
+///
+/// The target is in world space, even if the entity has a parent: the local [Position] and [Rotation] are
+/// back-solved from the parent's [GlobalTransform]. For an entity with a [FrozenGlobalTransform],
+/// the [GlobalTransform] is set directly instead, keeping its scale.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetworkTransform {
+    /// The target position, in world space.
+    pub position: Vec3,
+    /// The target rotation, in world space.
+    pub rotation: Quat,
+    /// How the entity is corrected towards the target.
+    pub correction: Correction,
+    /// Errors larger than this distance are snapped, instead of smoothed. Defaults to [DEFAULT_SNAP_DISTANCE].
+    pub snap_distance: f32,
+    /// Whether the entity has been snapped to a target yet.
+    synced: bool,
+}
+
+impl NetworkTransform {
+    /// Creates a new [NetworkTransform] with the first target, which is snapped to.
+    pub fn new(position: Vec3, rotation: Quat, correction: Correction) -> Self {
+        Self {
+            position,
+            rotation,
+            correction,
+            snap_distance: DEFAULT_SNAP_DISTANCE,
+            synced: false,
+        }
+    }
+
+    /// Sets the [NetworkTransform::snap_distance].
+    pub fn with_snap_distance(mut self, snap_distance: f32) -> Self {
+        self.snap_distance = snap_distance;
+        self
+    }
+
+    /// Set a new target, e.g. when a packet arrives. It is corrected towards with the [Correction].
+    pub fn set_target(&mut self, position: Vec3, rotation: Quat) {
+        self.position = position;
+        self.rotation = rotation;
+    }
+
+    /// Set a new target, and snap to it no matter the [Correction], e.g. when the server teleports the entity.
+    pub fn teleport(&mut self, position: Vec3, rotation: Quat) {
+        self.set_target(position, rotation);
+        self.synced = false;
+    }
+
+    /// Get the world space position and rotation to move to this frame, from the `current` ones.
+    /// Without a `current` transform, e.g. on the frame the entity was spawned, the target is snapped to.
+    pub fn step(&mut self, current: Option<(Vec3, Quat)>, delta: f32) -> (Vec3, Quat) {
+        let target = (self.position, self.rotation);
+        let Some((position, rotation)) = current else {
+            self.synced = true;
+            return target;
+        };
+
+        if !self.synced || position.distance(self.position) > self.snap_distance {
+            self.synced = true;
+            return target;
+        }
+
+        match self.correction {
+            Correction::Snap => target,
+            Correction::Smooth { rate } => {
+                let t = 1.0 - (-rate * delta).exp();
+                (
+                    position.lerp(self.position, t),
+                    rotation.slerp(self.rotation, t),
+                )
+            }
+        }
+    }
+}
+
+/// A system that moves every entity with a [NetworkTransform] towards its target, see [NetworkTransform].
+///
+/// The entity's current transform is taken from its [GlobalTransform], so it is the one of the last propagation,
+/// and changes to the [Position] and [Rotation] of the entity made by other systems this frame are overwritten.
+///
+/// Corrections use the unscaled [DeltaTime], so entities keep following the server while the local time is paused.
+pub fn correct_network_transforms(
+    world: SubWorld<(
+        &DeltaTime,
+        &mut NetworkTransform,
+        &Child<Tree>,
+        &mut GlobalTransform,
+        &FrozenGlobalTransform,
+        &mut Position,
+        &mut Rotation,
+    )>,
+) {
+    let Ok(delta) = world
+        .query::<&DeltaTime>()
+        .single_mut()
+        .map(|delta| delta.0)
+    else {
+        return;
+    };
+
+    // read the parents' transforms up front, as the global transforms are borrowed mutably below
+    let parents = world
+        .query::<&Child<Tree>>()
+        .with::<&NetworkTransform>()
+        .iter()
+        .map(|(entity, child)| (entity, child.parent()))
+        .collect::<HashMap<_, _>>();
+    let parent_entities = parents.values().copied().collect::<HashSet<_>>();
+    let parent_transforms = world
+        .query::<&GlobalTransform>()
+        .iter()
+        .filter(|(entity, _)| parent_entities.contains(entity))
+        .map(|(entity, transform)| (entity, *transform))
+        .collect::<HashMap<_, _>>();
+
+    let mut q = world.query::<(
+        &mut NetworkTransform,
+        Option<&mut GlobalTransform>,
+        &mut Position,
+        &mut Rotation,
+        Satisfies<&FrozenGlobalTransform>,
+    )>();
+
+    for (entity, (network, global_transform, position, rotation, frozen)) in q.iter() {
+        let current = global_transform.as_ref().map(|transform| {
+            let (_, rotation, translation) = transform.to_scale_rotation_translation();
+            (translation, rotation)
+        });
+        let (world_position, world_rotation) = network.step(current, delta);
+
+        if let Some(global_transform) = global_transform.filter(|_| frozen) {
+            let (scale, _, _) = global_transform.to_scale_rotation_translation();
+            *global_transform = GlobalTransform::new(Mat4::from_scale_rotation_translation(
+                scale,
+                world_rotation,
+                world_position,
+            ));
+            continue;
+        }
+
+        match parents
+            .get(&entity)
+            .and_then(|parent| parent_transforms.get(parent))
+        {
+            Some(parent) => {
+                *position = parent.to_local_position(world_position);
+                *rotation = parent.to_local_rotation(world_rotation);
+            }
+            None => {
+                *position = Position(world_position);
+                *rotation = Rotation(world_rotation);
+            }
+        }
+    }
+}
+
+/// A plugin that corrects networked entities towards the transforms received from the server, see [NetworkTransform].
+///
+/// The corrections are made in `update`, so the [TransformPlugin] propagates them in the same frame.
+///
+/// *Note*: Requires the [TimePlugin] and the [TransformPlugin].
+#[derive(Default, Debug)]
+pub struct NetworkTransformPlugin;
+
+impl Plugin for NetworkTransformPlugin {
+    fn build(&self, app: &mut App) -> PluginResult {
+        app.add_update_system(correct_network_transforms);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snap_then_smooth() {
+        let mut network = NetworkTransform::new(
+            Vec3::new(10.0, 0.0, 0.0),
+            Quat::IDENTITY,
+            Correction::Smooth { rate: 10.0 },
+        );
+
+        // the first target is snapped to, no matter how far away it is
+        let current = Some((Vec3::ZERO, Quat::IDENTITY));
+        let (position, _) = network.step(current, 0.1);
+        assert_eq!(position, Vec3::new(10.0, 0.0, 0.0));
+
+        // after that, small errors are smoothed
+        network.set_target(Vec3::new(11.0, 0.0, 0.0), Quat::from_rotation_y(1.0));
+        let (position, rotation) = network.step(Some((position, Quat::IDENTITY)), 0.1);
+        let t = 1.0 - (-1.0f32).exp();
+        assert!(position.abs_diff_eq(Vec3::new(10.0 + t, 0.0, 0.0), 1e-5));
+        assert!(rotation.abs_diff_eq(Quat::from_rotation_y(t), 1e-5));
+
+        // while large ones, and teleports, are snapped
+        network.set_target(Vec3::new(20.0, 0.0, 0.0), Quat::IDENTITY);
+        let (position, _) = network.step(Some((position, rotation)), 0.1);
+        assert_eq!(position, Vec3::new(20.0, 0.0, 0.0));
+
+        network.teleport(Vec3::new(21.0, 0.0, 0.0), Quat::IDENTITY);
+        let (position, _) = network.step(Some((position, Quat::IDENTITY)), 0.1);
+        assert_eq!(position, Vec3::new(21.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_parented_target() {
+        let mut world = World::new();
+        world.spawn((DeltaTime(0.1),));
+        let ship = world.spawn((GlobalTransform::from_position(Position::new(5.0, 0.0, 0.0)),));
+        let player = world.spawn(TransformBundle::default());
+        let target =
+            NetworkTransform::new(Vec3::new(6.0, 2.0, 0.0), Quat::IDENTITY, Correction::Snap);
+        world.insert_one(player, target).unwrap();
+        world.attach::<Tree>(player, ship).unwrap();
+
+        let mut schedule = ScheduleBuilder::new()
+            .add_system(correct_network_transforms)
+            .build();
+        schedule.execute((&mut world,)).unwrap();
+
+        // the target is in world space, so the local position is relative to the ship
+        let position = *world.get::<&Position>(player).unwrap();
+        assert!(position.approx_eq(&Position::new(1.0, 2.0, 0.0), 1e-5));
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::{HashMap, HashSet};
+
+use thndr_app::prelude::*;
+use thndr_ecs::prelude::*;
+use thndr_event::prelude::*;
+use thndr_math::prelude::*;
+use thndr_transform::prelude::*;
+
+/// An axis aligned box collider, in the local space of the entity. Only detects overlaps, nothing is pushed apart.
+///
+/// The [CollisionPlugin] transforms it with the [GlobalTransform] of the entity, and takes the box around the result,
+/// so a rotated collider grows to keep covering its corners.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    /// The minimum corner.
+    pub min: Vec3,
+    /// The maximum corner.
+    pub max: Vec3,
+}
+
+impl Aabb {
+    /// Creates a new [Aabb] from its corners.
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Self { min, max }
+    }
+
+    /// Creates a new [Aabb] around the origin, e.g. `Aabb::from_half_extents(Vec3::splat(0.5))` for a unit cube.
+    pub fn from_half_extents(half_extents: Vec3) -> Self {
+        Self::new(-half_extents, half_extents)
+    }
+
+    /// Get the center of the box.
+    pub fn center(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Get half of the size of the box along every axis.
+    pub fn half_extents(&self) -> Vec3 {
+        (self.max - self.min) * 0.5
+    }
+
+    /// Check if the box overlaps `other`. Boxes that only touch count as overlapping.
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.cmple(other.max).all() && other.min.cmple(self.max).all()
+    }
+
+    /// Get the box around this box transformed by `transform`.
+    pub fn transformed(&self, transform: &GlobalTransform) -> Aabb {
+        let corners = (0..8).map(|i| {
+            Vec3::select(
+                BVec3::new(i & 1 != 0, i & 2 != 0, i & 4 != 0),
+                self.max,
+                self.min,
+            )
+        });
+
+        let (min, max) = corners
+            .map(|corner| transform.0.transform_point3(corner))
+            .fold((Vec3::MAX, Vec3::MIN), |(min, max), corner| {
+                (min.min(corner), max.max(corner))
+            });
+
+        Aabb::new(min, max)
+    }
+}
+
+/// An event that is spawned when the [Aabb]s of two entities start overlapping. `a` is always the smaller entity id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionEnterEvent {
+    /// The first entity.
+    pub a: Entity,
+    /// The second entity.
+    pub b: Entity,
+}
+
+/// An event that is spawned when the [Aabb]s of two entities stop overlapping, or one of them lost its collider.
+/// `a` is always the smaller entity id.
+///
+/// *Note*: The entities might be despawned already.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionExitEvent {
+    /// The first entity.
+    pub a: Entity,
+    /// The second entity.
+    pub b: Entity,
+}
+
+/// A singleton component with the state of the collision detection, spawned by the [CollisionPlugin].
+#[derive(Debug)]
+pub struct Collisions {
+    /// The cell size of the finest level of the broadphase.
+    cell_size: f32,
+    /// The broadphase, a grid over the centers of the colliders per level, rebuilt every frame.
+    /// The cells of level `n` are `cell_size * 2^n` big.
+    levels: Vec<Level>,
+    /// The pairs that overlapped last frame, to tell enters from persisting overlaps.
+    overlaps: HashSet<(Entity, Entity)>,
+}
+
+/// A single level of the broadphase of [Collisions].
+#[derive(Debug)]
+struct Level {
+    /// The centers of the colliders in this level.
+    index: SpatialIndex,
+    /// The largest half extents of the colliders in this level, along every axis.
+    largest: Vec3,
+}
+
+impl Collisions {
+    /// Creates a new [Collisions], with a broadphase grid of `cell_size` for its finest level, see [SpatialIndex::new].
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0.0, "Cell size must be positive");
+
+        Self {
+            cell_size,
+            levels: Vec::new(),
+            overlaps: HashSet::new(),
+        }
+    }
+
+    /// Check if the colliders of `a` and `b` overlapped in the last collision update.
+    pub fn overlapping(&self, a: Entity, b: Entity) -> bool {
+        self.overlaps.contains(&(a.min(b), a.max(b)))
+    }
+
+    /// Iterate over all pairs of entities that overlapped in the last collision update.
+    pub fn overlaps(&self) -> impl Iterator<Item = (Entity, Entity)> + '_ {
+        self.overlaps.iter().copied()
+    }
+
+    /// The level of the broadphase a box with `half_extents` goes into, the first one whose cells are at least as big.
+    fn level(&self, half_extents: Vec3) -> usize {
+        let ratio = half_extents.max_element() / self.cell_size;
+
+        ratio.log2().ceil().clamp(0.0, 31.0) as usize
+    }
+
+    /// Clears every level, and makes sure there are at least `count` of them.
+    fn reset_levels(&mut self, count: usize) {
+        for level in &mut self.levels {
+            level.index.clear();
+            level.largest = Vec3::ZERO;
+        }
+
+        while self.levels.len() < count {
+            let cell_size = self.cell_size * 2f32.powi(self.levels.len() as i32);
+            self.levels.push(Level {
+                index: SpatialIndex::new(cell_size),
+                largest: Vec3::ZERO,
+            });
+        }
+    }
+}
+
+/// A system that finds the overlapping [Aabb]s, and spawns a [CollisionEnterEvent] or [CollisionExitEvent] for every pair
+/// that started or stopped overlapping since the last frame.
+///
+/// The centers of the boxes are put in a hierarchical grid, so only boxes in nearby cells are compared, instead of every pair.
+/// Every box goes into the first level whose cells are at least as big as the box, each level doubling the cell size.
+/// A box is then compared against the nearby boxes of its own and all coarser levels, with the search widened by the largest box of that level.
+/// So a few big boxes, like a ground plane, sit alone on a coarse level and cost every box a single cell lookup,
+/// instead of widening the search of every box in the grid.
+pub fn detect_collisions(
+    world: SubWorld<(&Aabb, &GlobalTransform, &mut Collisions)>,
+    mut commands: Commands,
+) {
+    let mut collisions = world.query::<&mut Collisions>();
+    let Ok(collisions) = collisions.single_mut() else {
+        return;
+    };
+
+    let boxes = world
+        .query::<(&Aabb, &GlobalTransform)>()
+        .iter()
+        .map(|(entity, (aabb, transform))| {
+            let aabb = aabb.transformed(transform);
+            (entity, (aabb, collisions.level(aabb.half_extents())))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let count = boxes
+        .values()
+        .map(|(_, level)| level + 1)
+        .max()
+        .unwrap_or(0);
+    collisions.reset_levels(count);
+    for (entity, (aabb, level)) in &boxes {
+        let level = &mut collisions.levels[*level];
+        level.index.insert(*entity, aabb.center());
+        level.largest = level.largest.max(aabb.half_extents());
+    }
+
+    let mut overlaps = HashSet::new();
+    for (entity, (aabb, own_level)) in &boxes {
+        for (level, Level { index, largest }) in
+            collisions.levels.iter().enumerate().skip(*own_level)
+        {
+            for other in index.query_aabb(aabb.min - *largest, aabb.max + *largest) {
+                // pairs in the same level are found from both sides, only keep them once
+                if level == *own_level && other <= *entity {
+                    continue;
+                }
+
+                if boxes
+                    .get(&other)
+                    .is_some_and(|(other, _)| aabb.intersects(other))
+                {
+                    overlaps.insert(((*entity).min(other), (*entity).max(other)));
+                }
+            }
+        }
+    }
+
+    for &(a, b) in overlaps.difference(&collisions.overlaps) {
+        commands.spawn((Event::default(), CollisionEnterEvent { a, b }));
+    }
+    for &(a, b) in collisions.overlaps.difference(&overlaps) {
+        commands.spawn((Event::default(), CollisionExitEvent { a, b }));
+    }
+
+    collisions.overlaps = overlaps;
+}
+
+/// A plugin that detects overlapping [Aabb] colliders, see [detect_collisions].
+/// Gameplay listens for the [CollisionEnterEvent]s, e.g. to apply damage or pick something up.
+///
+/// *Note*: This needs to be added after the [TransformPlugin], so the collisions are detected after the transforms are propagated.
+/// The events are spawned at the end of `post_update`, so they can be handled in the next frame.
+#[derive(Debug)]
+pub struct CollisionPlugin {
+    /// The cell size of the finest level of the broadphase grid, see [SpatialIndex::new].
+    /// Around the size of the most common colliders works best.
+    pub cell_size: f32,
+}
+
+impl Default for CollisionPlugin {
+    fn default() -> Self {
+        Self { cell_size: 4.0 }
+    }
+}
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) -> PluginResult {
+        app.world.spawn((Collisions::new(self.cell_size),));
+        app.register_resource::<Collisions>();
+
+        app.add_post_update_system(detect_collisions);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collision_events() {
+        let mut world = World::new();
+        let mut schedule = ScheduleBuilder::new().add_system(detect_collisions).build();
+
+        world.spawn((Collisions::new(2.0),));
+        let unit = Aabb::from_half_extents(Vec3::splat(0.5));
+        let still = world.spawn((unit, GlobalTransform::default()));
+        let moving = world.spawn((unit, GlobalTransform::default()));
+        // far away, never touches anything
+        world.spawn((
+            unit,
+            GlobalTransform(Mat4::from_translation(Vec3::X * 50.0)),
+        ));
+
+        let mut move_to = |world: &mut World, x: f32| {
+            world.get::<&mut GlobalTransform>(moving).unwrap().0 =
+                Mat4::from_translation(Vec3::X * x);
+            schedule.execute((world,)).unwrap();
+        };
+
+        // approach, overlap for a few frames, and move away again
+        for x in [3.0, 2.0, 0.9, 0.5, 0.0, 0.5, 0.9, 2.0, 3.0] {
+            move_to(&mut world, x);
+        }
+
+        assert_eq!(event_count::<CollisionEnterEvent>(&world), 1);
+        assert_eq!(event_count::<CollisionExitEvent>(&world), 1);
+
+        let (a, b) = (still.min(moving), still.max(moving));
+        let enter = *world.query::<&CollisionEnterEvent>().single_mut().unwrap();
+        assert_eq!(enter, CollisionEnterEvent { a, b });
+        let exit = *world.query::<&CollisionExitEvent>().single_mut().unwrap();
+        assert_eq!(exit, CollisionExitEvent { a, b });
+    }
+
+    #[test]
+    fn test_oversized_colliders() {
+        let mut world = World::new();
+        let mut schedule = ScheduleBuilder::new().add_system(detect_collisions).build();
+
+        world.spawn((Collisions::new(2.0),));
+        let ground = world.spawn((
+            Aabb::new(
+                Vec3::new(-500.0, -1.0, -500.0),
+                Vec3::new(500.0, 0.0, 500.0),
+            ),
+            GlobalTransform::default(),
+        ));
+        let wall = world.spawn((
+            Aabb::new(Vec3::new(-500.0, 0.0, 10.0), Vec3::new(500.0, 20.0, 11.0)),
+            GlobalTransform::default(),
+        ));
+        let unit = Aabb::from_half_extents(Vec3::splat(0.5));
+        let far = world.spawn((
+            unit,
+            GlobalTransform(Mat4::from_translation(Vec3::new(400.0, 0.0, 0.0))),
+        ));
+        world.spawn((
+            unit,
+            GlobalTransform(Mat4::from_translation(Vec3::new(0.0, 5.0, 0.0))),
+        ));
+        schedule.execute((&mut world,)).unwrap();
+
+        // the ground and the wall are alone on a coarse level, but still found from the far side of the world
+        let mut q = world.query::<&Collisions>();
+        let collisions = q.single_mut().unwrap();
+        assert_eq!(collisions.levels.len(), 9);
+        assert!(collisions.overlapping(ground, far));
+        assert!(collisions.overlapping(ground, wall));
+        assert_eq!(collisions.overlaps().count(), 2);
+    }
+
+    #[test]
+    fn test_transformed_aabb() {
+        let aabb = Aabb::new(Vec3::ZERO, Vec3::new(2.0, 1.0, 1.0));
+        let rotated = aabb.transformed(&GlobalTransform(Mat4::from_rotation_z(
+            std::f32::consts::FRAC_PI_2,
+        )));
+
+        assert!(rotated.min.abs_diff_eq(Vec3::new(-1.0, 0.0, 0.0), 1e-5));
+        assert!(rotated.max.abs_diff_eq(Vec3::new(0.0, 2.0, 1.0), 1e-5));
+        assert!(rotated.intersects(&Aabb::from_half_extents(Vec3::splat(0.5))));
+        assert!(!aabb.intersects(&Aabb::new(Vec3::splat(1.5), Vec3::splat(2.0))));
+    }
+}
@@ -0,0 +1,137 @@
+//! # thndr_physics
+//!
+//! `thndr_physics` provides simple kinematics and collision detection, for the Thunder Game Engine.
+//!
+//! It is not a physics engine: there are no forces, masses or collision responses.
+//! Entities simply move with the velocity they are given, and overlapping colliders are only reported.
+//!
+
+use thndr_app::prelude::*;
+use thndr_ecs::prelude::*;
+use thndr_math::prelude::*;
+use thndr_time::prelude::*;
+use thndr_transform::prelude::*;
+
+/// Provides box colliders, and events for when they start or stop overlapping.
+pub mod collision;
+
+/// The linear velocity of an entity, in units per second. Integrated into its [Position] by the [KinematicsPlugin].
+///
+/// Like the [Position], it is relative to the parent if the entity is in a [Tree].
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct Velocity(pub Vec3);
+
+/// The angular velocity of an entity. Integrated into its [Rotation] by the [KinematicsPlugin].
+///
+/// The direction is the axis of rotation, and the length is the speed in radians per second.
+/// E.g. `AngularVelocity(Vec3::Y * PI)` spins half a turn per second around the Y axis.
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct AngularVelocity(pub Vec3);
+
+/// Moves `position` with `velocity` for `delta` seconds.
+pub fn integrate_velocity(position: &mut Position, velocity: Velocity, delta: f32) {
+    position.0 += velocity.0 * delta;
+}
+
+/// Rotates `rotation` with `angular_velocity` for `delta` seconds.
+pub fn integrate_angular_velocity(
+    rotation: &mut Rotation,
+    angular_velocity: AngularVelocity,
+    delta: f32,
+) {
+    // renormalize, so rounding errors don't add up over many frames
+    rotation.0 = (Quat::from_scaled_axis(angular_velocity.0 * delta) * rotation.0).normalize();
+}
+
+/// A system that integrates the [Velocity] and [AngularVelocity] of every entity into its [Position] and [Rotation],
+/// using the [ScaledDeltaTime]. So pausing the time with [Time::set_scale] also stops everything from moving.
+pub fn kinematics_system(
+    world: SubWorld<(
+        &ScaledDeltaTime,
+        &Velocity,
+        &AngularVelocity,
+        &mut Position,
+        &mut Rotation,
+    )>,
+) {
+    let Ok(delta) = world
+        .query::<&ScaledDeltaTime>()
+        .single_mut()
+        .map(|delta| delta.0)
+    else {
+        return;
+    };
+
+    for (_, (position, velocity)) in world.query::<(&mut Position, &Velocity)>().iter() {
+        integrate_velocity(position, *velocity, delta);
+    }
+
+    for (_, (rotation, angular_velocity)) in
+        world.query::<(&mut Rotation, &AngularVelocity)>().iter()
+    {
+        integrate_angular_velocity(rotation, *angular_velocity, delta);
+    }
+}
+
+/// A plugin that moves entities with a [Velocity] or [AngularVelocity], see [kinematics_system].
+///
+/// This is purely kinematic: nothing applies forces, and nothing stops entities from moving through each other.
+/// A projectile e.g. only needs a [Position] and a [Velocity] to fly.
+///
+/// *Note*: The velocities are integrated in `update`, before the transforms are propagated in `post_update`,
+/// so the [GlobalTransform] reflects the new positions in the same frame. Requires the [TimePlugin].
+#[derive(Default, Debug)]
+pub struct KinematicsPlugin;
+
+impl Plugin for KinematicsPlugin {
+    fn build(&self, app: &mut App) -> PluginResult {
+        app.add_update_system(kinematics_system);
+
+        Ok(())
+    }
+}
+
+/// Common types, traits, and functions.
+pub mod prelude {
+    pub use crate::collision::{
+        detect_collisions, Aabb, CollisionEnterEvent, CollisionExitEvent, CollisionPlugin,
+        Collisions,
+    };
+    pub use crate::{
+        integrate_angular_velocity, integrate_velocity, kinematics_system, AngularVelocity,
+        KinematicsPlugin, Velocity,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kinematics() {
+        let mut world = World::new();
+        let mut schedule = ScheduleBuilder::new().add_system(kinematics_system).build();
+
+        world.spawn((ScaledDeltaTime(0.1),));
+        let projectile = world.spawn((
+            Position::new(1.0, 0.0, 0.0),
+            Rotation::default(),
+            Velocity(Vec3::new(2.0, 0.0, -1.0)),
+            AngularVelocity(Vec3::Y * std::f32::consts::PI),
+        ));
+
+        // ten fixed steps make a second
+        for _ in 0..10 {
+            schedule.execute((&mut world,)).unwrap();
+        }
+
+        assert!(world
+            .get::<&Position>(projectile)
+            .unwrap()
+            .approx_eq(&Position::new(3.0, 0.0, -1.0), 1e-5));
+        assert!(world
+            .get::<&Rotation>(projectile)
+            .unwrap()
+            .approx_eq(&Rotation(Quat::from_rotation_y(std::f32::consts::PI)), 1e-5));
+    }
+}
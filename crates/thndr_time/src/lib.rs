@@ -3,7 +3,10 @@
 //! `thndr_time` provides time related functionality, for the Thunder Game Engine.
 //!
 
-use std::{fmt, time::Instant};
+use std::{
+    fmt,
+    time::{Instant, SystemTime},
+};
 use thndr_app::prelude::*;
 use thndr_ecs::prelude::*;
 
@@ -11,7 +14,12 @@ use thndr_ecs::prelude::*;
 #[derive(Debug)]
 pub struct Time {
     startup: Instant,
+    startup_timestamp: SystemTime,
     last_update: Instant,
+    scale: f32,
+    fixed_delta: f32,
+    overstep: f32,
+    fixed_steps: u32,
 }
 
 impl Default for Time {
@@ -25,7 +33,12 @@ impl Time {
     pub fn new() -> Self {
         Self {
             startup: Instant::now(),
+            startup_timestamp: SystemTime::now(),
             last_update: Instant::now(),
+            scale: 1.0,
+            fixed_delta: 1.0 / 60.0,
+            overstep: 0.0,
+            fixed_steps: 0,
         }
     }
 
@@ -39,6 +52,91 @@ impl Time {
         self.last_update.elapsed().as_secs_f64()
     }
 
+    /// Returns the delta time since the last update, multiplied by the time scale. In f32 seconds.
+    ///
+    /// Use this for gameplay that should slow down or pause with the game, see [Time::set_scale].
+    pub fn scaled_delta_seconds(&self) -> f32 {
+        self.delta_seconds() * self.scale
+    }
+
+    /// Returns the delta time since the last update, multiplied by the time scale. In f64 seconds.
+    pub fn scaled_delta_seconds_f64(&self) -> f64 {
+        self.delta_seconds_f64() * self.scale as f64
+    }
+
+    /// Returns the time scale. Defaults to `1.0`.
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Sets the time scale, e.g. `0.5` for slow motion or `0.0` to pause.
+    /// Negative scales are clamped to `0.0`.
+    ///
+    /// *Note*: This only affects [Time::scaled_delta_seconds], the unscaled delta and the time since startup are untouched.
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.max(0.0);
+    }
+
+    /// Returns the size of a fixed step, in f32 seconds. Defaults to 1/60th of a second.
+    ///
+    /// Every frame, the scaled delta time is accumulated, and whenever a full fixed step fits,
+    /// a step is due. Simulations that need a stable rate (e.g. physics) run once per step, see [Time::fixed_steps].
+    ///
+    /// *Note*: There is no fixed update schedule. Systems that need fixed steps loop over [Time::fixed_steps] themselves.
+    pub fn fixed_delta(&self) -> f32 {
+        self.fixed_delta
+    }
+
+    /// Sets the size of a fixed step, in f32 seconds. E.g. `1.0 / 30.0` for a 30Hz simulation.
+    pub fn set_fixed_delta(&mut self, seconds: f32) {
+        assert!(seconds > 0.0, "Fixed delta must be positive");
+
+        self.fixed_delta = seconds;
+    }
+
+    /// Returns the number of fixed steps that are due in the current frame. Can be zero on fast frames, or several on slow ones.
+    ///
+    /// ```ignore
+    /// for _ in 0..time.fixed_steps() {
+    ///     step_simulation(time.fixed_delta());
+    /// }
+    /// ```
+    pub fn fixed_steps(&self) -> u32 {
+        self.fixed_steps
+    }
+
+    /// Returns the time accumulated towards the next fixed step, in f32 seconds. Always less than [Time::fixed_delta].
+    pub fn overstep(&self) -> f32 {
+        self.overstep
+    }
+
+    /// Returns how far the current frame is into the next fixed step, from `0.0` to `1.0`.
+    ///
+    /// Render systems use this to interpolate between the state of the last two fixed steps, so movement looks smooth
+    /// even when the simulation runs slower than the frame rate. E.g. blend from the `PreviousGlobalTransform` to the
+    /// `GlobalTransform` by this amount.
+    ///
+    /// *Note*: This is only meaningful if the simulation actually runs in fixed steps, i.e. its systems loop over
+    /// [Time::fixed_steps]. Systems using the [DeltaTime] have nothing to interpolate.
+    pub fn overstep_percentage(&self) -> f32 {
+        self.overstep / self.fixed_delta
+    }
+
+    /// Accumulates `delta` seconds, and updates the fixed steps due and the overstep. Returns the number of fixed steps due.
+    ///
+    /// The [TimePlugin] does this with the scaled delta time at the start of every frame,
+    /// so a runner only needs it when driving [Time] by itself.
+    pub fn accumulate(&mut self, delta: f32) -> u32 {
+        self.overstep += delta.max(0.0);
+        self.fixed_steps = (self.overstep / self.fixed_delta) as u32;
+        self.overstep -= self.fixed_steps as f32 * self.fixed_delta;
+
+        // rounding can leave a tiny negative remainder
+        self.overstep = self.overstep.max(0.0);
+
+        self.fixed_steps
+    }
+
     /// Returns the time since the game started. In f32 seconds.
     pub fn seconds_since_startup(&self) -> f32 {
         self.startup.elapsed().as_secs_f32()
@@ -49,6 +147,37 @@ impl Time {
         self.startup.elapsed().as_secs_f64()
     }
 
+    /// Returns the current wall-clock time, e.g. for stamping save files.
+    ///
+    /// *Note*: The wall-clock can jump (even backwards) when the system clock is adjusted,
+    /// so never use it to measure durations. Use [Time::delta_seconds] and friends for that.
+    pub fn now_utc() -> SystemTime {
+        SystemTime::now()
+    }
+
+    /// Returns the wall-clock time at which the game started. See [Time::now_utc] for the caveats.
+    pub fn startup_timestamp(&self) -> SystemTime {
+        self.startup_timestamp
+    }
+
+    /// Returns the current date and time in UTC. See [Time::now_utc] for the caveats.
+    #[cfg(feature = "chrono")]
+    pub fn now_datetime() -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc::now()
+    }
+
+    /// Returns the current date and time in the local timezone. See [Time::now_utc] for the caveats.
+    #[cfg(feature = "chrono")]
+    pub fn now_local() -> chrono::DateTime<chrono::Local> {
+        chrono::Local::now()
+    }
+
+    /// Returns the date and time at which the game started, in UTC. See [Time::now_utc] for the caveats.
+    #[cfg(feature = "chrono")]
+    pub fn startup_datetime(&self) -> chrono::DateTime<chrono::Utc> {
+        self.startup_timestamp.into()
+    }
+
     pub(crate) fn update(&mut self) {
         self.last_update = Instant::now();
     }
@@ -60,25 +189,228 @@ impl fmt::Display for Time {
     }
 }
 
+/// The delta time of the current frame, in f32 seconds. A singleton component, spawned by the [TimePlugin].
+///
+/// Unlike [Time::delta_seconds], which keeps growing while the frame runs, it is written once in pre-update
+/// and stays fixed for the whole frame. Use [Time] for anything else, like the time since startup.
+///
+/// ```ignore
+/// fn movement(world: SubWorld<(&DeltaTime, &mut Position, &Velocity)>) {
+///     let Ok(delta) = world.query::<&DeltaTime>().single_mut().copied() else {
+///         return;
+///     };
+///
+///     for (_, (position, velocity)) in world.query::<(&mut Position, &Velocity)>().iter() {
+///         position.0 += velocity.0 * delta.0;
+///     }
+/// }
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct DeltaTime(pub f32);
+
+/// The delta time of the current frame multiplied by the time scale, in f32 seconds. See [DeltaTime] and [Time::set_scale].
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct ScaledDeltaTime(pub f32);
+
+/// The durations of the most recent frames, in f32 seconds. A singleton component, spawned by the [TimePlugin].
+///
+/// Meant for plotting frame times in a debug overlay, where spikes are visible that an FPS counter averages away.
+/// Keeps the last 240 frames by default, see [FrameTimeHistory::set_capacity].
+#[derive(Debug, Clone)]
+pub struct FrameTimeHistory {
+    // holds up to twice the capacity, so the last `capacity` durations are always contiguous,
+    // and dropping the oldest ones only moves memory every `capacity` frames
+    durations: Vec<f32>,
+    capacity: usize,
+}
+
+impl Default for FrameTimeHistory {
+    fn default() -> Self {
+        Self::new(240)
+    }
+}
+
+impl FrameTimeHistory {
+    /// Creates a new, empty [FrameTimeHistory], keeping the last `capacity` frames. A capacity of `0` is raised to `1`.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+
+        Self {
+            durations: Vec::with_capacity(capacity * 2),
+            capacity,
+        }
+    }
+
+    /// Get the number of frames that are kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Change the number of frames that are kept. Shrinking drops the oldest frames.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        let kept = self.as_slice().len().min(capacity.max(1));
+        let oldest = self.durations.len() - kept;
+        self.durations.drain(..oldest);
+
+        self.capacity = capacity.max(1);
+    }
+
+    /// Add the duration of a frame, dropping the oldest one if the history is full.
+    pub fn push(&mut self, duration: f32) {
+        if self.durations.len() == self.capacity * 2 {
+            self.durations.drain(..self.capacity);
+        }
+
+        self.durations.push(duration);
+    }
+
+    /// Get the kept durations, from oldest to newest.
+    pub fn as_slice(&self) -> &[f32] {
+        &self.durations[self.durations.len().saturating_sub(self.capacity)..]
+    }
+
+    /// Iterate over the kept durations, from oldest to newest.
+    pub fn iter(&self) -> impl Iterator<Item = f32> + '_ {
+        self.as_slice().iter().copied()
+    }
+
+    /// Get the shortest kept duration, or `None` if there are none yet.
+    pub fn min(&self) -> Option<f32> {
+        self.iter().reduce(f32::min)
+    }
+
+    /// Get the longest kept duration, or `None` if there are none yet.
+    pub fn max(&self) -> Option<f32> {
+        self.iter().reduce(f32::max)
+    }
+
+    /// Get the `p`th percentile of the kept durations, with `p` from `0.0` to `100.0`, or `None` if there are none yet.
+    /// E.g. the 99th percentile is the duration that 99% of the frames stayed within.
+    ///
+    /// Uses the nearest-rank method, so the result is always one of the kept durations.
+    /// This copies the durations and partially sorts them, which takes linear time.
+    pub fn percentile(&self, p: f32) -> Option<f32> {
+        let mut durations = self.as_slice().to_vec();
+        if durations.is_empty() {
+            return None;
+        }
+
+        let rank = (p.clamp(0.0, 100.0) / 100.0 * durations.len() as f32).ceil() as usize;
+        let index = rank.clamp(1, durations.len()) - 1;
+
+        let (_, duration, _) = durations.select_nth_unstable_by(index, f32::total_cmp);
+        Some(*duration)
+    }
+}
+
 /// A plugin that simply keeps the time up to date.
 #[derive(Default, Debug)]
 pub struct TimePlugin;
 
 impl Plugin for TimePlugin {
-    fn build(&self, app: &mut App) {
+    fn build(&self, app: &mut App) -> PluginResult {
+        app.register_resource::<Time>();
         app.add_startup_system(|mut commands: Commands| {
-            commands.spawn((Time::new(),));
+            commands.spawn((
+                Time::new(),
+                DeltaTime::default(),
+                ScaledDeltaTime::default(),
+                FrameTimeHistory::default(),
+            ));
         });
 
+        app.add_pre_update_system(
+            |world: SubWorld<(
+                &mut Time,
+                &mut DeltaTime,
+                &mut ScaledDeltaTime,
+                &mut FrameTimeHistory,
+            )>| {
+                for (_, (time, delta, scaled_delta, history)) in world
+                    .query::<(
+                        &mut Time,
+                        &mut DeltaTime,
+                        &mut ScaledDeltaTime,
+                        &mut FrameTimeHistory,
+                    )>()
+                    .iter()
+                {
+                    delta.0 = time.delta_seconds();
+                    scaled_delta.0 = time.scaled_delta_seconds();
+                    history.push(delta.0);
+                    time.accumulate(scaled_delta.0);
+                }
+            },
+        );
+
         app.add_update_system(|world: SubWorld<&mut Time>| {
             for (_, time) in world.query::<&mut Time>().iter() {
                 time.update();
             }
         });
+
+        Ok(())
     }
 }
 
 /// Common types, traits, and functions.
 pub mod prelude {
-    pub use crate::{Time, TimePlugin};
+    pub use crate::{DeltaTime, FrameTimeHistory, ScaledDeltaTime, Time, TimePlugin};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_time_history() {
+        let mut history = FrameTimeHistory::new(4);
+        assert_eq!(history.percentile(50.0), None);
+        assert_eq!(history.max(), None);
+
+        for duration in [0.016, 0.017, 0.050, 0.016, 0.015, 0.033] {
+            history.push(duration);
+        }
+
+        // only the last 4 are kept, oldest first
+        assert_eq!(history.as_slice(), &[0.050, 0.016, 0.015, 0.033]);
+        assert_eq!(history.min(), Some(0.015));
+        assert_eq!(history.max(), Some(0.050));
+
+        // nearest rank: 50% of 4 is the 2nd smallest, anything above 75% is the largest
+        assert_eq!(history.percentile(50.0), Some(0.016));
+        assert_eq!(history.percentile(76.0), Some(0.050));
+        assert_eq!(history.percentile(0.0), Some(0.015));
+        assert_eq!(history.percentile(100.0), Some(0.050));
+
+        // wraps around many times without growing
+        for i in 0..100 {
+            history.push(i as f32);
+        }
+        assert_eq!(history.as_slice(), &[96.0, 97.0, 98.0, 99.0]);
+
+        history.set_capacity(2);
+        assert_eq!(history.as_slice(), &[98.0, 99.0]);
+        history.push(100.0);
+        assert_eq!(history.as_slice(), &[99.0, 100.0]);
+    }
+
+    #[test]
+    fn test_fixed_steps_overstep() {
+        let mut time = Time::new();
+        time.set_fixed_delta(0.1);
+
+        // two full steps, and halfway into the third
+        assert_eq!(time.accumulate(0.25), 2);
+        assert!((time.overstep() - 0.05).abs() < 1e-5);
+        assert!((time.overstep_percentage() - 0.5).abs() < 1e-4);
+
+        // a fast frame doesn't complete a step
+        assert_eq!(time.accumulate(0.04), 0);
+        assert_eq!(time.fixed_steps(), 0);
+        assert!((time.overstep_percentage() - 0.9).abs() < 1e-4);
+
+        assert_eq!(time.accumulate(0.02), 1);
+        assert!((time.overstep_percentage() - 0.1).abs() < 1e-3);
+    }
 }
@@ -0,0 +1,160 @@
+use std::{cell::RefCell, fmt};
+
+use thndr_app::prelude::*;
+use thndr_ecs::prelude::*;
+use thndr_time::prelude::*;
+use tracing::info;
+
+/// Performance numbers, aggregated over an interval by the [DiagnosticsOverlayPlugin].
+/// The latest ones are kept in a singleton component, e.g. for a debug menu.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct Diagnostics {
+    /// The average frames per second.
+    pub fps: f32,
+    /// The average duration of a frame, in milliseconds.
+    pub frame_time: f32,
+    /// The longest frame, in milliseconds. Spikes show up here, while the averages smooth them away.
+    pub max_frame_time: f32,
+    /// The number of frames since the app started.
+    pub frame_count: u64,
+    /// The number of entities at the end of the interval.
+    pub entity_count: u32,
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "FPS: {:.1}, frame time: {:.2}ms (max {:.2}ms), frames: {}, entities: {}",
+            self.fps, self.frame_time, self.max_frame_time, self.frame_count, self.entity_count
+        )
+    }
+}
+
+/// Aggregates frame times into [Diagnostics], once every interval.
+#[derive(Debug)]
+pub struct DiagnosticsAggregator {
+    interval: f32,
+    elapsed: f32,
+    max_frame_time: f32,
+    frames: u32,
+    frame_count: u64,
+}
+
+impl DiagnosticsAggregator {
+    /// Creates a new [DiagnosticsAggregator], producing [Diagnostics] every `interval` seconds.
+    pub fn new(interval: f32) -> Self {
+        Self {
+            interval,
+            elapsed: 0.0,
+            max_frame_time: 0.0,
+            frames: 0,
+            frame_count: 0,
+        }
+    }
+
+    /// Adds a frame that took `delta` seconds, with `entity_count` entities at its end.
+    /// Returns the [Diagnostics] of the interval, once it is over.
+    pub fn push(&mut self, delta: f32, entity_count: u32) -> Option<Diagnostics> {
+        self.elapsed += delta;
+        self.max_frame_time = self.max_frame_time.max(delta);
+        self.frames += 1;
+        self.frame_count += 1;
+
+        if self.elapsed < self.interval {
+            return None;
+        }
+
+        let diagnostics = Diagnostics {
+            fps: self.frames as f32 / self.elapsed,
+            frame_time: self.elapsed / self.frames as f32 * 1000.0,
+            max_frame_time: self.max_frame_time * 1000.0,
+            frame_count: self.frame_count,
+            entity_count,
+        };
+
+        self.elapsed = 0.0;
+        self.max_frame_time = 0.0;
+        self.frames = 0;
+
+        Some(diagnostics)
+    }
+}
+
+/// A plugin that shows the FPS, frame time and entity count. Add it, and the numbers show up in the log.
+///
+/// For now, the [Diagnostics] are logged with `info!` once every [DiagnosticsOverlayPlugin::interval].
+/// Once text rendering exists, they will be drawn on screen as well.
+///
+/// *Note*: Requires the [TimePlugin], and the [TracingPlugin](crate::TracingPlugin) to see the log.
+#[derive(Debug)]
+pub struct DiagnosticsOverlayPlugin {
+    /// How often the diagnostics are updated and logged, in seconds.
+    pub interval: f32,
+}
+
+impl Default for DiagnosticsOverlayPlugin {
+    fn default() -> Self {
+        Self { interval: 1.0 }
+    }
+}
+
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) -> PluginResult {
+        app.world.spawn((Diagnostics::default(),));
+        app.register_resource::<Diagnostics>();
+
+        // a direct system, as counting all entities needs the whole world
+        let aggregator = RefCell::new(DiagnosticsAggregator::new(self.interval));
+        app.add_direct_post_update_system(move |world| {
+            let Some(delta) = world
+                .query_mut::<&DeltaTime>()
+                .into_iter()
+                .next()
+                .map(|(_, delta)| delta.0)
+            else {
+                return;
+            };
+
+            let entity_count = world.len();
+            let Some(diagnostics) = aggregator.borrow_mut().push(delta, entity_count) else {
+                return;
+            };
+
+            info!("{diagnostics}");
+            for (_, latest) in world.query_mut::<&mut Diagnostics>() {
+                *latest = diagnostics;
+            }
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostics_aggregation() {
+        let mut aggregator = DiagnosticsAggregator::new(1.0);
+
+        for _ in 0..3 {
+            assert_eq!(aggregator.push(0.25, 10), None);
+        }
+        let diagnostics = aggregator.push(0.25, 12).unwrap();
+        assert_eq!(diagnostics.fps, 4.0);
+        assert_eq!(diagnostics.frame_time, 250.0);
+        assert_eq!(diagnostics.max_frame_time, 250.0);
+        assert_eq!(diagnostics.frame_count, 4);
+        assert_eq!(diagnostics.entity_count, 12);
+
+        // the next interval starts from scratch, except for the frame count
+        assert_eq!(aggregator.push(0.5, 12), None);
+        let diagnostics = aggregator.push(0.75, 12).unwrap();
+        assert_eq!(diagnostics.fps, 1.6);
+        assert_eq!(diagnostics.frame_time, 625.0);
+        assert_eq!(diagnostics.max_frame_time, 750.0);
+        assert_eq!(diagnostics.frame_count, 6);
+    }
+}
@@ -7,25 +7,66 @@
 use thndr_app::prelude::*;
 use tracing::Level;
 use tracing_subscriber::{
-    filter::EnvFilter, fmt, layer::SubscriberExt as _, util::SubscriberInitExt as _,
+    filter::EnvFilter, fmt, layer::SubscriberExt as _, util::SubscriberInitExt as _, Layer,
+    Registry,
 };
 
+/// Provides performance diagnostics, like the frame rate and entity count.
+pub mod diagnostics;
+
+/// The output format of the [TracingPlugin].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// The default, full format.
+    #[default]
+    Full,
+    /// A compact, single line format.
+    Compact,
+    /// A multi-line format, nice for local debugging.
+    Pretty,
+    /// Newline-delimited JSON, for ingestion into log aggregators. This always disables ANSI colors.
+    Json,
+}
+
 /// A plugin that initializes the tracing subscriber.
-#[derive(Default, Debug)]
-pub struct TracingPlugin;
+#[derive(Debug)]
+pub struct TracingPlugin {
+    /// The output format.
+    pub format: LogFormat,
+    /// Whether to use ANSI colors. Ignored for [LogFormat::Json].
+    pub ansi: bool,
+}
+
+impl Default for TracingPlugin {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Full,
+            ansi: true,
+        }
+    }
+}
 
 impl Plugin for TracingPlugin {
-    fn build(&self, _app: &mut App) {
+    fn build(&self, _app: &mut App) -> PluginResult {
+        let layer: Box<dyn Layer<Registry> + Send + Sync> = match self.format {
+            LogFormat::Full => fmt::layer().with_ansi(self.ansi).boxed(),
+            LogFormat::Compact => fmt::layer().compact().with_ansi(self.ansi).boxed(),
+            LogFormat::Pretty => fmt::layer().pretty().with_ansi(self.ansi).boxed(),
+            LogFormat::Json => fmt::layer().json().with_ansi(false).boxed(),
+        };
+
+        let filter = EnvFilter::builder()
+            .with_default_directive(Level::DEBUG.into())
+            .with_env_var("THNDR_LOG")
+            .from_env()
+            .map_err(|error| PluginError::Custom(format!("Invalid THNDR_LOG filter: {error}")))?;
+
+        // fails if a global subscriber was already set, e.g. when the plugin is added twice
         tracing_subscriber::registry()
-            .with(fmt::layer())
-            .with(
-                EnvFilter::builder()
-                    .with_default_directive(Level::DEBUG.into())
-                    .with_env_var("THNDR_LOG")
-                    .from_env()
-                    .expect("Failed to build filter"),
-            )
-            .init();
+            .with(layer)
+            .with(filter)
+            .try_init()
+            .map_err(|error| PluginError::Custom(error.to_string()))
     }
 }
 
@@ -35,5 +76,6 @@ pub mod prelude {
     pub use tracing::{debug, error, info, instrument, trace, warn};
     pub use tracing::{debug_span, error_span, info_span, trace_span, warn_span};
 
-    pub use crate::TracingPlugin;
+    pub use crate::diagnostics::{Diagnostics, DiagnosticsAggregator, DiagnosticsOverlayPlugin};
+    pub use crate::{LogFormat, TracingPlugin};
 }
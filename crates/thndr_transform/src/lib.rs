@@ -1,12 +1,29 @@
 //! # thndr_transform
 //!
 //! `thndr_transform` is a collection of transform utilities for the Thunder game engine.
+//!
+//! # Coordinate system
+//!
+//! Thunder uses a right-handed coordinate system with Y up, like glam's `*_rh` functions:
+//! X points right, Y points up, and the camera looks down -Z, so -Z is forward.
+//! Positive rotations turn counter-clockwise when looking down the axis towards the origin.
+//!
+//! Assets authored in a left-handed, Y up tool (e.g. anything following the DirectX convention) have their Z axis mirrored.
+//! Convert their transforms once when importing, with [GlobalTransform::from_left_handed], or use
+//! [GlobalTransform::change_basis] for other conventions, e.g. Z up.
 use std::ops::{Deref, DerefMut};
 
 use thndr_app::prelude::*;
 use thndr_ecs::prelude::*;
+use thndr_event::prelude::*;
 use thndr_math::prelude::*;
 
+// same fix as in thndr_event, so the Bundle derive can find hecs
+extern crate self as hecs;
+
+/// Provides spatial queries over the positions of entities.
+pub mod spatial;
+
 /// A Position in 3D space. If in a [Tree], this is relative to the parent entity.
 #[derive(Default, Debug, Copy, Clone, PartialEq)]
 pub struct Position(pub Vec3);
@@ -16,6 +33,14 @@ impl Position {
     pub fn new(x: f32, y: f32, z: f32) -> Self {
         Position(Vec3::new(x, y, z))
     }
+
+    /// Check if every component differs by at most `epsilon` from `other`.
+    ///
+    /// `==` is still available, but compares exactly, so positions that only differ by rounding errors are not equal.
+    /// Prefer this for anything computed, e.g. an "is at rest" check: `position.approx_eq(&last, 0.001)`.
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.0.abs_diff_eq(other.0, epsilon)
+    }
 }
 
 impl Deref for Position {
@@ -53,6 +78,13 @@ impl Rotation {
     pub fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
         Rotation(Quat::from_xyzw(x, y, z, w))
     }
+
+    /// Check if every component differs by at most `epsilon` from `other`. Prefer this over the exact `==`.
+    ///
+    /// *Note*: `q` and `-q` describe the same rotation, but are not approximately equal.
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.0.abs_diff_eq(other.0, epsilon)
+    }
 }
 
 impl Deref for Rotation {
@@ -96,6 +128,11 @@ impl Scale {
     pub fn new(x: f32, y: f32, z: f32) -> Self {
         Scale(Vec3::new(x, y, z))
     }
+
+    /// Check if every component differs by at most `epsilon` from `other`. Prefer this over the exact `==`.
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.0.abs_diff_eq(other.0, epsilon)
+    }
 }
 
 impl Deref for Scale {
@@ -134,6 +171,11 @@ impl GlobalTransform {
         GlobalTransform(matrix)
     }
 
+    /// Check if every element of the matrix differs by at most `epsilon` from `other`. Prefer this over the exact `==`.
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        self.0.abs_diff_eq(other.0, epsilon)
+    }
+
     /// Creates a new global transform from a position, rotation, and scale.
     pub fn from_position_rotation_scale(
         position: Position,
@@ -179,6 +221,63 @@ impl GlobalTransform {
     pub fn from_scale(scale: Scale) -> Self {
         GlobalTransform(Mat4::from_scale(scale.into()))
     }
+
+    /// Converts a world space position into the local space of this transform.
+    /// Used to compute the [Position] of a child of this transform.
+    ///
+    /// *Note*: A transform with a (near) zero scale can't be inverted, and produces non-finite values.
+    pub fn to_local_position(&self, world_position: Vec3) -> Position {
+        Position(self.inverse().transform_point3(world_position))
+    }
+
+    /// Converts a world space rotation into the local space of this transform.
+    /// Used to compute the [Rotation] of a child of this transform.
+    ///
+    /// *Note*: If this transform has a non-uniform scale, the rotation extracted from it is only an
+    /// approximation, so the result will be slightly off.
+    pub fn to_local_rotation(&self, world_rotation: Quat) -> Rotation {
+        let (_, rotation, _) = self.to_scale_rotation_translation();
+        Rotation(rotation.inverse() * world_rotation)
+    }
+
+    /// Express this transform in another coordinate system, where `basis` maps Thunder's axes to the other system's axes.
+    /// Computes `basis * self * basis⁻¹`, so transforming a converted point gives the converted result.
+    ///
+    /// *Note*: `basis` has to be invertible, a mirroring `basis` (negative determinant) also flips the winding of triangles.
+    pub fn change_basis(&self, basis: Mat3) -> Self {
+        let basis = Mat4::from_mat3(basis);
+        GlobalTransform(basis * self.0 * basis.inverse())
+    }
+
+    /// Converts this transform to a left-handed, Y up coordinate system, by mirroring the Z axis. See the [crate] docs.
+    ///
+    /// Positions get their Z negated, and rotations around X and Y turn the other way. Rotations around Z are unchanged,
+    /// as both the rotation and the axis are mirrored. Converting twice gives the original transform.
+    pub fn to_left_handed(&self) -> Self {
+        self.change_basis(Mat3::from_diagonal(Vec3::new(1.0, 1.0, -1.0)))
+    }
+
+    /// Converts a transform from a left-handed, Y up coordinate system, e.g. of an imported model. The inverse of
+    /// [GlobalTransform::to_left_handed], which is the same operation, as mirroring is its own inverse.
+    pub fn from_left_handed(transform: GlobalTransform) -> Self {
+        transform.to_left_handed()
+    }
+
+    /// Computes the normal matrix of this transform: the inverse-transpose of its upper-left 3x3.
+    /// Transforming normals with this keeps them perpendicular to the surface, even under non-uniform scale.
+    ///
+    /// *Note*: If the 3x3 can't be inverted (e.g. a zero scale on some axis), the plain 3x3 is returned instead.
+    /// Small scales are fine, only an exactly zero (or non-finite) determinant falls back.
+    pub fn normal_matrix(&self) -> Mat3 {
+        let matrix = Mat3::from_mat4(self.0);
+        let determinant = matrix.determinant();
+
+        if determinant.is_finite() && determinant != 0.0 {
+            matrix.inverse().transpose()
+        } else {
+            matrix
+        }
+    }
 }
 
 impl Deref for GlobalTransform {
@@ -207,7 +306,192 @@ impl From<GlobalTransform> for Mat4 {
     }
 }
 
+/// A marker component that keeps the [GlobalTransform] of an entity as it is, instead of computing it from the
+/// [Position], [Rotation] and [Scale]. E.g. for a camera driven by a matrix, or a networked entity whose transform is authoritative.
+///
+/// Write the [GlobalTransform] directly, and the propagation won't overwrite it.
+/// A frozen entity in a [Tree] still acts as a parent: its children are transformed by the frozen [GlobalTransform].
+#[derive(Default, Debug, Clone, Copy)]
+pub struct FrozenGlobalTransform;
+
+/// The largest difference in any element of a [GlobalTransform] that doesn't count as a change,
+/// see [TransformPlugin::change_events].
+pub const GLOBAL_TRANSFORM_EPSILON: f32 = 1e-5;
+
+/// The [GlobalTransform] of an entity, as it was when a [GlobalTransformChangedEvent] was last spawned for it.
+///
+/// Maintained when [TransformPlugin::change_events] is enabled. Since it is only updated on a change, movement
+/// smaller than [GLOBAL_TRANSFORM_EPSILON] per frame still adds up, and is reported once it exceeds the epsilon.
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct PreviousGlobalTransform(pub GlobalTransform);
+
+/// An event that is spawned when the [GlobalTransform] of an entity changed, e.g. to only update the moved entities
+/// in a spatial index. Also spawned the first time an entity gets a [GlobalTransform].
+///
+/// Only spawned when [TransformPlugin::change_events] is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlobalTransformChangedEvent {
+    /// The entity whose [GlobalTransform] changed.
+    pub entity: Entity,
+}
+
+/// Spawns a [GlobalTransformChangedEvent] for every entity whose [GlobalTransform] differs by more than
+/// [GLOBAL_TRANSFORM_EPSILON] from its [PreviousGlobalTransform].
+///
+/// Runs after the propagation, so the events are for the transforms of the current frame.
+pub fn detect_global_transform_changes(
+    world: SubWorld<(&GlobalTransform, &mut PreviousGlobalTransform)>,
+    mut commands: Commands,
+) {
+    for (entity, (global_transform, previous)) in world
+        .query::<(&GlobalTransform, &mut PreviousGlobalTransform)>()
+        .iter()
+    {
+        if !global_transform.approx_eq(&previous.0, GLOBAL_TRANSFORM_EPSILON) {
+            previous.0 = *global_transform;
+            commands.spawn((Event::default(), GlobalTransformChangedEvent { entity }));
+        }
+    }
+
+    for (entity, global_transform) in world
+        .query::<&GlobalTransform>()
+        .lacks::<PreviousGlobalTransform>()
+        .iter()
+    {
+        commands.insert_one(entity, PreviousGlobalTransform(*global_transform));
+        commands.spawn((Event::default(), GlobalTransformChangedEvent { entity }));
+    }
+}
+
+/// A bundle with all transform components, to spawn a fully positioned entity in one go.
+///
+/// ```ignore
+/// commands.spawn(TransformBundle {
+///     position: Position::new(0.0, 1.0, 0.0),
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Default, Clone, Bundle, DynamicBundleClone)]
+pub struct TransformBundle {
+    /// The position.
+    pub position: Position,
+    /// The rotation.
+    pub rotation: Rotation,
+    /// The scale.
+    pub scale: Scale,
+    /// The global transform, computed from the others by the [TransformPlugin].
+    pub global_transform: GlobalTransform,
+}
+
+impl TransformBundle {
+    /// Creates a new [TransformBundle] at `position`.
+    pub fn from_position(position: Position) -> Self {
+        Self {
+            position,
+            global_transform: GlobalTransform::from_position(position),
+            ..Default::default()
+        }
+    }
+}
+
+/// The components a [TransformHelper] needs access to.
+pub type TransformHelperAccess = (
+    &'static GlobalTransform,
+    &'static Child<Tree>,
+    &'static mut Position,
+    &'static mut Rotation,
+);
+
+/// Helps with setting world space positions and rotations, for entities that might be part of a [Tree].
+///
+/// [Position] and [Rotation] are relative to the parent, so setting them to a world space value
+/// requires inverting the parent's [GlobalTransform]. Take a `SubWorld<TransformHelperAccess>`
+/// in a system, and wrap it with [TransformHelper::new] to let it do the math.
+///
+/// *Note*: The parent's [GlobalTransform] is the one computed by the last propagation,
+/// so if the parent itself moved this frame, the result is relative to where it was last frame.
+pub struct TransformHelper<'a, 'w> {
+    world: &'a SubWorld<'w, TransformHelperAccess>,
+}
+
+impl<'a, 'w> TransformHelper<'a, 'w> {
+    /// Creates a new [TransformHelper].
+    pub fn new(world: &'a SubWorld<'w, TransformHelperAccess>) -> Self {
+        Self { world }
+    }
+
+    /// Returns the [GlobalTransform] of the parent of `entity`, if it has one.
+    pub fn parent_transform(&self, entity: Entity) -> Option<GlobalTransform> {
+        let parent = self
+            .world
+            .query_one::<&Child<Tree>>(entity)
+            .ok()?
+            .get()?
+            .parent();
+
+        let mut transform = self.world.query_one::<&GlobalTransform>(parent).ok()?;
+        transform.get().copied()
+    }
+
+    /// Sets the [Position] of `entity`, so it ends up at `world_position` in world space.
+    /// Without a parent, this is the same as setting the [Position] directly.
+    ///
+    /// Returns `false` if the entity has no [Position].
+    pub fn set_global_position(&self, entity: Entity, world_position: Vec3) -> bool {
+        let local = match self.parent_transform(entity) {
+            Some(parent) => parent.to_local_position(world_position),
+            None => Position(world_position),
+        };
+
+        let Ok(mut q) = self.world.query_one::<&mut Position>(entity) else {
+            return false;
+        };
+
+        match q.get() {
+            Some(position) => {
+                *position = local;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sets the [Rotation] of `entity`, so it ends up with `world_rotation` in world space.
+    /// Without a parent, this is the same as setting the [Rotation] directly.
+    ///
+    /// Returns `false` if the entity has no [Rotation].
+    pub fn set_global_rotation(&self, entity: Entity, world_rotation: Quat) -> bool {
+        let local = match self.parent_transform(entity) {
+            Some(parent) => parent.to_local_rotation(world_rotation),
+            None => Rotation(world_rotation),
+        };
+
+        let Ok(mut q) = self.world.query_one::<&mut Rotation>(entity) else {
+            return false;
+        };
+
+        match q.get() {
+            Some(rotation) => {
+                *rotation = local;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Runs `f` for every item, spread over the rayon thread pool if there are at least `threshold` items.
+fn for_each_maybe_parallel<T: Sync>(items: &[T], threshold: usize, f: impl Fn(&T) + Send + Sync) {
+    if items.len() >= threshold {
+        items.par_iter().for_each(f);
+    } else {
+        items.iter().for_each(f);
+    }
+}
+
 fn recurse_children(
+    mode: PropagationMode,
+    parallel_threshold: usize,
     parent: Parent<Tree>,
     transform: GlobalTransform,
     world: &SubWorld<(
@@ -217,6 +501,7 @@ fn recurse_children(
         &Position,
         &Rotation,
         &Scale,
+        &ComputePool,
     )>,
 ) {
     let mut to_recurse = Vec::new();
@@ -228,56 +513,55 @@ fn recurse_children(
             &Position,
             &Rotation,
             &Scale,
+            Satisfies<&FrozenGlobalTransform>,
         )>();
 
         let mut iter = q.iter();
 
-        let last_child = iter.find_map(|(e, (g, pa, c, p, r, s))| {
+        let last_child = iter.find_map(|(e, (g, pa, c, p, r, s, f))| {
             if e == parent.last_child() {
-                Some((g, pa, c, p, r, s))
+                Some((g, pa, c, p, r, s, f))
             } else {
                 None
             }
         });
 
         if let Some(last_child) = last_child {
-            let (global_transform, parent, child, position, rotation, scale) = last_child;
+            let (global_transform, parent, child, position, rotation, scale, frozen) = last_child;
 
             // add the parent's transform, and use the position, rotation, and scale to calculate the new global transform
-            *global_transform = GlobalTransform::new(
-                *transform
-                    * *GlobalTransform::from_position_rotation_scale(*position, *rotation, *scale),
-            );
+            if !frozen {
+                *global_transform = mode.combine(transform, *position, *rotation, *scale);
+            }
 
             let (global_transform, parent, child) = (*global_transform, *parent, *child);
 
             to_recurse.push((parent, global_transform));
 
             // do the other children
-            let mut cur = iter.find_map(|(e, (g, pa, c, p, r, s))| {
+            let mut cur = iter.find_map(|(e, (g, pa, c, p, r, s, f))| {
                 if e == child.prev() {
-                    Some((g, *pa, *c, *p, *r, *s))
+                    Some((g, *pa, *c, *p, *r, *s, f))
                 } else {
                     None
                 }
             });
 
             loop {
-                if let Some((global_transform, parent, child, position, rotation, scale)) = cur {
-                    *global_transform = GlobalTransform::new(
-                        *transform
-                            * *GlobalTransform::from_position_rotation_scale(
-                                position, rotation, scale,
-                            ),
-                    );
+                if let Some((global_transform, parent, child, position, rotation, scale, frozen)) =
+                    cur
+                {
+                    if !frozen {
+                        *global_transform = mode.combine(transform, position, rotation, scale);
+                    }
 
                     let global_transform = *global_transform;
 
                     to_recurse.push((parent, global_transform));
 
-                    cur = iter.find_map(|(e, (g, pa, c, p, r, s))| {
+                    cur = iter.find_map(|(e, (g, pa, c, p, r, s, f))| {
                         if e == child.prev() {
-                            Some((g, *pa, *c, *p, *r, *s))
+                            Some((g, *pa, *c, *p, *r, *s, f))
                         } else {
                             None
                         }
@@ -298,52 +582,49 @@ fn recurse_children(
                 &Position,
                 &Rotation,
                 &Scale,
+                Satisfies<&FrozenGlobalTransform>,
             )>()
-            .without::<&Parent<Tree>>();
+            .lacks::<Parent<Tree>>();
 
         let mut iter = q.iter();
 
-        let last_child = iter.find_map(|(e, (g, c, p, r, s))| {
+        let last_child = iter.find_map(|(e, (g, c, p, r, s, f))| {
             if e == parent.last_child() {
-                Some((g, c, p, r, s))
+                Some((g, c, p, r, s, f))
             } else {
                 None
             }
         });
 
         if let Some(last_child) = last_child {
-            let (global_transform, child, position, rotation, scale) = last_child;
+            let (global_transform, child, position, rotation, scale, frozen) = last_child;
 
-            *global_transform = GlobalTransform::new(
-                *transform
-                    * *GlobalTransform::from_position_rotation_scale(*position, *rotation, *scale),
-            );
+            if !frozen {
+                *global_transform = mode.combine(transform, *position, *rotation, *scale);
+            }
 
-            let (_, child) = (*global_transform, *child);
+            let child = *child;
 
             // no need to recurse, as there are no children
             // we do need to update the other children though
 
-            let mut cur = iter.find_map(|(e, (g, c, p, r, s))| {
+            let mut cur = iter.find_map(|(e, (g, c, p, r, s, f))| {
                 if e == child.prev() {
-                    Some((g, *c, *p, *r, *s))
+                    Some((g, *c, *p, *r, *s, f))
                 } else {
                     None
                 }
             });
 
             loop {
-                if let Some((global_transform, child, position, rotation, scale)) = cur {
-                    *global_transform = GlobalTransform::new(
-                        *transform
-                            * *GlobalTransform::from_position_rotation_scale(
-                                position, rotation, scale,
-                            ),
-                    );
-
-                    cur = iter.find_map(|(e, (g, c, p, r, s))| {
+                if let Some((global_transform, child, position, rotation, scale, frozen)) = cur {
+                    if !frozen {
+                        *global_transform = mode.combine(transform, position, rotation, scale);
+                    }
+
+                    cur = iter.find_map(|(e, (g, c, p, r, s, f))| {
                         if e == child.prev() {
-                            Some((g, *c, *p, *r, *s))
+                            Some((g, *c, *p, *r, *s, f))
                         } else {
                             None
                         }
@@ -356,12 +637,27 @@ fn recurse_children(
     }
 
     // recurse through the children
-    to_recurse.par_iter().for_each(|(parent, transform)| {
-        recurse_children(*parent, *transform, world);
+    for_each_maybe_parallel(&to_recurse, parallel_threshold, |(parent, transform)| {
+        recurse_children(mode, parallel_threshold, *parent, *transform, world);
     });
 }
 
+fn update_global_transform(
+    (_, (global_transform, position, rotation, scale)): (
+        Entity,
+        (&mut GlobalTransform, &Position, &Rotation, &Scale),
+    ),
+) {
+    *global_transform = GlobalTransform::from_position_rotation_scale(*position, *rotation, *scale);
+}
+
+/// Computes the [GlobalTransform]s of all entities, see [TransformPlugin::parallel_threshold] for the threading.
+///
+/// Every [GlobalTransform] only depends on the local transforms of the entity and its ancestors, and is written exactly once,
+/// so the results are the same no matter how many threads there are, or how the work is split between them.
 fn propagate_transforms(
+    mode: PropagationMode,
+    parallel_threshold: usize,
     world: SubWorld<(
         &mut GlobalTransform,
         &Parent<Tree>,
@@ -369,88 +665,532 @@ fn propagate_transforms(
         &Position,
         &Rotation,
         &Scale,
+        &ComputePool,
     )>,
 ) {
-    // first, we just set all GlobalTransforms based on the Position, Rotation and Scale.
-    // afterwards, we ensure that if they are part of a Tree, they are transformed to the global space.
-    // TODO: Properly handle gaps in the tree (where a parent doesnt have a transform, but a grandparent does)
-    {
-        let mut q = world.query::<(&mut GlobalTransform, &Position, &Rotation, &Scale)>();
-
-        q.iter()
-            .par_bridge()
-            .for_each(|(_, (global_transform, position, rotation, scale))| {
-                *global_transform =
-                    GlobalTransform::from_position_rotation_scale(*position, *rotation, *scale);
-            });
-    }
+    // spread over the engine's pool, if the app has one, see `App::set_thread_pool_size`
+    let mut pool = world.query::<&ComputePool>();
+    ComputePool::install_in(pool.single_mut().ok(), || {
+        // first, we just set all GlobalTransforms based on the Position, Rotation and Scale.
+        // afterwards, we ensure that if they are part of a Tree, they are transformed to the global space.
+        // TODO: Properly handle gaps in the tree (where a parent doesnt have a transform, but a grandparent does)
+        {
+            let mut q = world
+                .query::<(&mut GlobalTransform, &Position, &Rotation, &Scale)>()
+                .lacks::<FrozenGlobalTransform>();
+
+            let iter = q.iter();
+            if iter.len() >= parallel_threshold {
+                iter.par_bridge().for_each(update_global_transform);
+            } else {
+                iter.for_each(update_global_transform);
+            }
+        }
 
-    let mut q = world
-        .query::<(&GlobalTransform, &Parent<Tree>)>()
-        .without::<&Child<Tree>>();
+        let mut q = world
+            .query::<(&GlobalTransform, &Parent<Tree>)>()
+            .lacks::<Child<Tree>>();
 
-    let iter = q.iter().map(|(_, (t, p))| (*t, *p)).collect::<Vec<_>>();
+        let iter = q.iter().map(|(_, (t, p))| (*t, *p)).collect::<Vec<_>>();
 
-    drop(q); // prevent double borrow
+        drop(q); // prevent double borrow
 
-    iter.par_iter().for_each(|(transform, parent)| {
-        recurse_children(*parent, *transform, &world);
+        for_each_maybe_parallel(&iter, parallel_threshold, |(transform, parent)| {
+            recurse_children(mode, parallel_threshold, *parent, *transform, &world);
+        });
     });
 }
 
 fn insert_transforms(mut commands: Commands, world: SubWorld<(&Position, &Rotation, &Scale)>) {
     // every entity that doesn't have a global transform, but does have a position, rotation, OR scale, should get a global transform
-    let mut q = world.query::<&Position>().without::<&GlobalTransform>();
+    let mut q = world.query::<&Position>().lacks::<GlobalTransform>();
 
     for (entity, _) in q.iter() {
         commands.insert_one(entity, GlobalTransform::default());
     }
 
-    let mut q = world.query::<&Rotation>().without::<&GlobalTransform>();
+    let mut q = world.query::<&Rotation>().lacks::<GlobalTransform>();
 
     for (entity, _) in q.iter() {
         commands.insert_one(entity, GlobalTransform::default());
     }
 
-    let mut q = world.query::<&Scale>().without::<&GlobalTransform>();
+    let mut q = world.query::<&Scale>().lacks::<GlobalTransform>();
 
     for (entity, _) in q.iter() {
         commands.insert_one(entity, GlobalTransform::default());
     }
 
-    // now ensure that they also have all 3 components
-    let mut q = world.query::<()>().without::<&Position>();
-
-    for (entity, _) in q.iter() {
+    // now ensure that they also have all 3 components.
+    // only entities that already have one of them are filled in, anything else is left alone.
+    let mut missing = Vec::new();
+
+    missing.extend(
+        world
+            .query::<&Rotation>()
+            .lacks::<Position>()
+            .iter()
+            .map(|(entity, _)| entity),
+    );
+    missing.extend(
+        world
+            .query::<&Scale>()
+            .lacks::<Position>()
+            .iter()
+            .map(|(entity, _)| entity),
+    );
+    missing.sort();
+    missing.dedup();
+
+    for entity in missing.drain(..) {
         commands.insert_one(entity, Position::default());
     }
 
-    let mut q = world.query::<()>().without::<&Rotation>();
-
-    for (entity, _) in q.iter() {
+    missing.extend(
+        world
+            .query::<&Position>()
+            .lacks::<Rotation>()
+            .iter()
+            .map(|(entity, _)| entity),
+    );
+    missing.extend(
+        world
+            .query::<&Scale>()
+            .lacks::<Rotation>()
+            .iter()
+            .map(|(entity, _)| entity),
+    );
+    missing.sort();
+    missing.dedup();
+
+    for entity in missing.drain(..) {
         commands.insert_one(entity, Rotation::default());
     }
 
-    let mut q = world.query::<()>().without::<&Scale>();
-
-    for (entity, _) in q.iter() {
+    missing.extend(
+        world
+            .query::<&Position>()
+            .lacks::<Scale>()
+            .iter()
+            .map(|(entity, _)| entity),
+    );
+    missing.extend(
+        world
+            .query::<&Rotation>()
+            .lacks::<Scale>()
+            .iter()
+            .map(|(entity, _)| entity),
+    );
+    missing.sort();
+    missing.dedup();
+
+    for entity in missing.drain(..) {
         commands.insert_one(entity, Scale::default());
     }
 }
 
+/// How the [GlobalTransform] of a parent is combined with the [Position], [Rotation] and [Scale] of its children.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropagationMode {
+    /// Multiply the parent's matrix with the child's matrix.
+    ///
+    /// This is the standard approach, but a non-uniform parent scale is baked into the children,
+    /// so a rotated child of a stretched parent gets sheared.
+    #[default]
+    Matrix,
+    /// Combine the position, rotation and scale separately.
+    ///
+    /// The parent's scale only scales the child's position, and is multiplied component-wise with the child's scale,
+    /// so children never get sheared. The downside is that a rotated child of a stretched parent isn't stretched
+    /// along the parent's axes, but along its own.
+    ScaleSeparated,
+}
+
+impl PropagationMode {
+    /// Computes the [GlobalTransform] of a child of `parent`.
+    pub fn combine(
+        self,
+        parent: GlobalTransform,
+        position: Position,
+        rotation: Rotation,
+        scale: Scale,
+    ) -> GlobalTransform {
+        match self {
+            PropagationMode::Matrix => GlobalTransform::new(
+                *parent * *GlobalTransform::from_position_rotation_scale(position, rotation, scale),
+            ),
+            PropagationMode::ScaleSeparated => {
+                // the parent was combined the same way, so it has no shear, and decomposing it is lossless
+                let (parent_scale, parent_rotation, parent_position) =
+                    parent.to_scale_rotation_translation();
+
+                GlobalTransform::from_position_rotation_scale(
+                    Position(parent_position + parent_rotation * (parent_scale * *position)),
+                    Rotation(parent_rotation * *rotation),
+                    Scale(parent_scale * *scale),
+                )
+            }
+        }
+    }
+}
+
 /// A plugin that simply updates the GlobalTransform component of all entities with a Position, Rotation, and Scale component.
 /// Also translates from local to global space.
-#[derive(Default, Debug)]
-pub struct TransformPlugin;
+///
+/// By default, children are transformed by multiplying the matrices, see [PropagationMode] for the alternative.
+#[derive(Debug)]
+pub struct TransformPlugin {
+    /// How transforms are propagated to children.
+    pub propagation: PropagationMode,
+    /// The least number of entities that are spread over threads, instead of being handled on the current thread.
+    /// This applies to the root entities, and to the children of every parent. Defaults to [DEFAULT_PARALLEL_THRESHOLD].
+    ///
+    /// Handing work to rayon has an overhead, which small scenes and parents with a few children don't make up for.
+    /// Use `0` to always go parallel, or `usize::MAX` to never do so. The results are the same either way,
+    /// only the speed differs. Run the `transform_propagation` example to compare them on your machine.
+    /// The work is spread over the [ComputePool] if the app has one, see `App::set_thread_pool_size`.
+    pub parallel_threshold: usize,
+    /// Whether to spawn a [GlobalTransformChangedEvent] for every entity whose [GlobalTransform] changed.
+    ///
+    /// This is off by default, as every frame it compares all 16 elements of the matrix of every entity,
+    /// and keeps a [PreviousGlobalTransform] copy of it, see [detect_global_transform_changes].
+    pub change_events: bool,
+}
+
+/// The default [TransformPlugin::parallel_threshold].
+pub const DEFAULT_PARALLEL_THRESHOLD: usize = 64;
+
+impl Default for TransformPlugin {
+    fn default() -> Self {
+        Self {
+            propagation: PropagationMode::default(),
+            parallel_threshold: DEFAULT_PARALLEL_THRESHOLD,
+            change_events: false,
+        }
+    }
+}
 
 impl Plugin for TransformPlugin {
-    fn build(&self, app: &mut App) {
+    fn build(&self, app: &mut App) -> PluginResult {
+        let mode = self.propagation;
+        let parallel_threshold = self.parallel_threshold;
+
         app.add_update_system(insert_transforms);
-        app.add_post_update_system(propagate_transforms);
+        app.add_post_update_system(
+            move |world: SubWorld<(
+                &mut GlobalTransform,
+                &Parent<Tree>,
+                &Child<Tree>,
+                &Position,
+                &Rotation,
+                &Scale,
+                &ComputePool,
+            )>| propagate_transforms(mode, parallel_threshold, world),
+        );
+
+        // reads what the propagation wrote, so it runs after it
+        if self.change_events {
+            app.add_post_update_system(detect_global_transform_changes);
+        }
+
+        Ok(())
     }
 }
 
 /// Common transform types and functions.
 pub mod prelude {
-    pub use super::{GlobalTransform, Position, Rotation, Scale, TransformPlugin};
+    pub use super::spatial::{update_spatial_index, SpatialIndex, SpatialIndexPlugin};
+    pub use super::{
+        detect_global_transform_changes, FrozenGlobalTransform, GlobalTransform,
+        GlobalTransformChangedEvent, Position, PreviousGlobalTransform, PropagationMode, Rotation,
+        Scale, TransformBundle, TransformHelper, TransformHelperAccess, TransformPlugin,
+        DEFAULT_PARALLEL_THRESHOLD, GLOBAL_TRANSFORM_EPSILON,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Name(&'static str);
+
+    #[test]
+    fn test_normal_matrix() {
+        let transform = GlobalTransform::from_position_rotation_scale(
+            Position::new(1.0, 2.0, 3.0),
+            Rotation(Quat::from_rotation_z(0.5)),
+            Scale::new(1.0, 3.0, 0.5),
+        );
+
+        // a surface at 45 degrees, which non-uniform scale skews
+        let normal = Vec3::new(1.0, 1.0, 0.0).normalize();
+        let tangent = Vec3::new(1.0, -1.0, 0.0).normalize();
+
+        let tangent = Mat3::from_mat4(transform.0) * tangent;
+        assert!((transform.normal_matrix() * normal).dot(tangent).abs() < 1e-5);
+        assert!((Mat3::from_mat4(transform.0) * normal).dot(tangent).abs() > 0.1);
+
+        // a tiny scale has a tiny determinant, but is still inverted
+        let tiny = GlobalTransform::from_scale(Scale::new(0.01, 0.01, 0.01));
+        assert!(tiny
+            .normal_matrix()
+            .abs_diff_eq(Mat3::from_diagonal(Vec3::splat(100.0)), 1e-2));
+
+        // a zero scale can't be inverted, so the plain 3x3 is used
+        let flat = GlobalTransform::from_scale(Scale::new(1.0, 0.0, 1.0));
+        assert_eq!(flat.normal_matrix(), Mat3::from_mat4(flat.0));
+    }
+
+    #[test]
+    fn test_handedness_flip() {
+        let angle = 0.7;
+        let transform = GlobalTransform::from_position_rotation(
+            Position::new(1.0, 2.0, 3.0),
+            Rotation(Quat::from_rotation_y(angle)),
+        );
+        let left_handed = transform.to_left_handed();
+
+        // only Z is mirrored, and the rotation around Y turns the other way
+        assert!(left_handed.approx_eq(
+            &GlobalTransform::from_position_rotation(
+                Position::new(1.0, 2.0, -3.0),
+                Rotation(Quat::from_rotation_y(-angle)),
+            ),
+            1e-5
+        ));
+        assert!(GlobalTransform::from_left_handed(left_handed).approx_eq(&transform, 1e-5));
+
+        // transforming a mirrored point gives the mirrored result
+        let mirror = |v: Vec3| v * Vec3::new(1.0, 1.0, -1.0);
+        let point = Vec3::new(-4.0, 0.5, 2.0);
+        assert!(left_handed
+            .transform_point3(mirror(point))
+            .abs_diff_eq(mirror(transform.transform_point3(point)), 1e-5));
+
+        // rotations around Z keep their direction
+        let roll = GlobalTransform::from_rotation(Rotation(Quat::from_rotation_z(angle)));
+        assert!(roll.to_left_handed().approx_eq(&roll, 1e-5));
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let position = Position::new(0.1, 0.2, 0.3);
+        let sum = Position(Vec3::new(0.1, 0.2, 0.0) + Vec3::new(0.0, 0.0, 0.1) * 3.0);
+        assert!(sum.approx_eq(&position, 1e-6));
+        assert!(!position.approx_eq(&Position::new(0.1, 0.2, 0.31), 1e-3));
+
+        let rotation = Rotation(Quat::from_rotation_y(std::f32::consts::FRAC_PI_2));
+        let half = Quat::from_rotation_y(std::f32::consts::FRAC_PI_4);
+        let composed = Rotation(half * half);
+        assert!(composed.approx_eq(&rotation, 1e-5));
+
+        assert!(Scale::new(1.0, 1.0, 1.0).approx_eq(&Scale::new(1.0, 1.0, 1.0005), 1e-3));
+        assert!(!Scale::new(1.0, 1.0, 1.0).approx_eq(&Scale::new(1.0, 1.0, 1.1), 1e-3));
+
+        let transform = GlobalTransform::from_position_rotation(position, rotation);
+        let roundtrip = GlobalTransform::from_position_rotation(sum, composed);
+        assert!(roundtrip.approx_eq(&transform, 1e-5));
+    }
+
+    #[test]
+    fn test_insert_transforms_scope() {
+        let mut world = World::new();
+        let mut schedule = ScheduleBuilder::new().add_system(insert_transforms).build();
+
+        let bare = world.spawn((Name("UI"),));
+        let positioned = world.spawn((Name("Player"), Position::new(1.0, 2.0, 3.0)));
+        schedule.execute((&mut world,)).unwrap();
+
+        assert!(world.get::<&Position>(bare).is_err());
+        assert!(world.get::<&Rotation>(bare).is_err());
+        assert!(world.get::<&Scale>(bare).is_err());
+        assert!(world.get::<&GlobalTransform>(bare).is_err());
+        assert_eq!(world.get::<&Name>(bare).unwrap().0, "UI");
+
+        assert_eq!(
+            *world.get::<&Position>(positioned).unwrap(),
+            Position::new(1.0, 2.0, 3.0)
+        );
+        assert!(world.get::<&Rotation>(positioned).is_ok());
+        assert!(world.get::<&Scale>(positioned).is_ok());
+        assert!(world.get::<&GlobalTransform>(positioned).is_ok());
+    }
+
+    #[test]
+    fn test_propagation_modes() {
+        let parent = GlobalTransform::from_scale(Scale::new(1.0, 2.5, 1.0));
+        let position = Position::new(1.0, 1.0, 0.0);
+        let rotation = Rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4));
+        let scale = Scale::default();
+
+        let matrix = PropagationMode::Matrix.combine(parent, position, rotation, scale);
+        let separated = PropagationMode::ScaleSeparated.combine(parent, position, rotation, scale);
+
+        // both place the child at the same position
+        assert!(matrix
+            .w_axis
+            .truncate()
+            .abs_diff_eq(Vec3::new(1.0, 2.5, 0.0), 1e-5));
+        assert!(separated
+            .w_axis
+            .truncate()
+            .abs_diff_eq(Vec3::new(1.0, 2.5, 0.0), 1e-5));
+
+        // but multiplying the matrices shears the rotated child, so its axes are no longer perpendicular
+        let dot = |t: GlobalTransform| t.x_axis.truncate().dot(t.y_axis.truncate());
+        assert!(dot(matrix).abs() > 0.1);
+        assert!(dot(separated).abs() < 1e-5);
+
+        let (_, separated_rotation, _) = separated.to_scale_rotation_translation();
+        assert!(separated_rotation.abs_diff_eq(*rotation, 1e-5));
+    }
+
+    fn propagate(
+        world: SubWorld<(
+            &mut GlobalTransform,
+            &Parent<Tree>,
+            &Child<Tree>,
+            &Position,
+            &Rotation,
+            &Scale,
+            &ComputePool,
+        )>,
+    ) {
+        propagate_transforms(PropagationMode::Matrix, DEFAULT_PARALLEL_THRESHOLD, world);
+    }
+
+    /// Spawns a wide tree of `roots` with 100 children each, and a deep chain of 100 entities.
+    fn spawn_trees(world: &mut World, roots: usize) {
+        let local = |i: usize| TransformBundle {
+            position: Position::new(i as f32 * 0.1, 1.0, -0.5),
+            rotation: Rotation(Quat::from_rotation_y(i as f32 * 0.3)),
+            scale: Scale::new(1.0, 1.01, 0.99),
+            ..Default::default()
+        };
+
+        for root in 0..roots {
+            let parent = world.spawn(local(root));
+            for child in 0..100 {
+                let child = world.spawn(local(child));
+                world.attach::<Tree>(child, parent).unwrap();
+            }
+        }
+
+        let mut parent = world.spawn(local(0));
+        for i in 1..100 {
+            let child = world.spawn(local(i));
+            world.attach::<Tree>(child, parent).unwrap();
+            parent = child;
+        }
+    }
+
+    /// Propagates the transforms of the [spawn_trees] with the `parallel_threshold`.
+    fn propagated_with(parallel_threshold: usize) -> Vec<GlobalTransform> {
+        let mut world = World::new();
+        spawn_trees(&mut world, 10);
+
+        let mut schedule = ScheduleBuilder::new()
+            .add_system(
+                move |world: SubWorld<(
+                    &mut GlobalTransform,
+                    &Parent<Tree>,
+                    &Child<Tree>,
+                    &Position,
+                    &Rotation,
+                    &Scale,
+                    &ComputePool,
+                )>| {
+                    propagate_transforms(PropagationMode::Matrix, parallel_threshold, world)
+                },
+            )
+            .build();
+        schedule.execute((&mut world,)).unwrap();
+
+        sorted_query::<&GlobalTransform, _>(&mut world, |entity, _| entity)
+            .into_iter()
+            .map(|(_, transform)| *transform)
+            .collect()
+    }
+
+    #[test]
+    fn test_propagation_deterministic() {
+        let serial = propagated_with(usize::MAX);
+
+        // bit for bit the same, not just approximately
+        assert_eq!(propagated_with(0), serial);
+        assert_eq!(propagated_with(DEFAULT_PARALLEL_THRESHOLD), serial);
+
+        // the end of the chain has been moved by all of its ancestors
+        assert!(serial.last().unwrap().w_axis.y > 99.0);
+    }
+
+    #[test]
+    fn test_frozen_global_transform() {
+        let mut world = World::new();
+        let mut schedule = ScheduleBuilder::new().add_system(propagate).build();
+
+        let frozen = GlobalTransform::from_position(Position::new(10.0, 0.0, 0.0));
+        let parent = world.spawn(TransformBundle {
+            global_transform: frozen,
+            ..Default::default()
+        });
+        world.insert_one(parent, FrozenGlobalTransform).unwrap();
+        let child = world.spawn(TransformBundle::from_position(Position::new(0.0, 1.0, 0.0)));
+        world.attach::<Tree>(child, parent).unwrap();
+
+        schedule.execute((&mut world,)).unwrap();
+
+        // the written transform survives, and is still used for the children
+        assert_eq!(*world.get::<&GlobalTransform>(parent).unwrap(), frozen);
+        assert!(world.get::<&GlobalTransform>(child).unwrap().approx_eq(
+            &GlobalTransform::from_position(Position::new(10.0, 1.0, 0.0)),
+            1e-6
+        ));
+    }
+
+    /// Despawns the change events, and returns the entities they were for.
+    fn take_changes(world: &mut World) -> Vec<Entity> {
+        let events = world
+            .query_mut::<&GlobalTransformChangedEvent>()
+            .into_iter()
+            .map(|(event, changed)| (event, changed.entity))
+            .collect::<Vec<_>>();
+
+        events
+            .into_iter()
+            .map(|(event, entity)| {
+                world.despawn(event).unwrap();
+                entity
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_global_transform_changed_events() {
+        let mut world = World::new();
+        let mut schedule = ScheduleBuilder::new()
+            .add_system(propagate)
+            .add_system(detect_global_transform_changes)
+            .build();
+
+        let stationary = world.spawn(TransformBundle::default());
+        let moving = world.spawn(TransformBundle::default());
+
+        // new entities count as changed
+        schedule.execute((&mut world,)).unwrap();
+        let mut changes = take_changes(&mut world);
+        changes.sort();
+        let mut expected = vec![stationary, moving];
+        expected.sort();
+        assert_eq!(changes, expected);
+
+        world.get::<&mut Position>(moving).unwrap().x += 1.0;
+        schedule.execute((&mut world,)).unwrap();
+        assert_eq!(take_changes(&mut world), vec![moving]);
+
+        // movement within the epsilon isn't a change
+        world.get::<&mut Position>(moving).unwrap().x += GLOBAL_TRANSFORM_EPSILON / 2.0;
+        schedule.execute((&mut world,)).unwrap();
+        assert!(take_changes(&mut world).is_empty());
+    }
 }
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+
+use thndr_app::prelude::*;
+use thndr_ecs::prelude::*;
+use thndr_math::prelude::*;
+
+use crate::GlobalTransform;
+
+/// A uniform grid over the positions of all entities with a [GlobalTransform].
+/// Speeds up finding entities near a point, e.g. for proximity triggers or area of effect.
+///
+/// It is rebuilt every frame by [update_spatial_index], after the transforms have been propagated,
+/// so positions are those of the current frame.
+#[derive(Debug)]
+pub struct SpatialIndex {
+    cell_size: f32,
+    cells: HashMap<(i32, i32, i32), Vec<(Entity, Vec3)>>,
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        Self::new(10.0)
+    }
+}
+
+impl SpatialIndex {
+    /// Creates a new, empty [SpatialIndex].
+    ///
+    /// The `cell_size` should be around the radius of the most common queries.
+    /// Smaller cells mean more cells to visit, bigger cells mean more entities to check per cell.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0.0, "Cell size must be positive");
+
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Get the size of a single cell.
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Removes all entities from the index.
+    ///
+    /// Cells that were filled since the last clear keep their allocations, since the index is rebuilt every frame.
+    /// Cells that stayed empty are dropped, so cells that entities moved away from don't pile up.
+    pub fn clear(&mut self) {
+        self.cells.retain(|_, cell| {
+            let keep = !cell.is_empty();
+            cell.clear();
+            keep
+        });
+    }
+
+    /// Inserts an entity at `position`.
+    pub fn insert(&mut self, entity: Entity, position: Vec3) {
+        self.cells
+            .entry(self.cell(position))
+            .or_default()
+            .push((entity, position));
+    }
+
+    /// Returns all entities within `radius` of `center`.
+    pub fn query_radius(&self, center: Vec3, radius: f32) -> Vec<Entity> {
+        let radius_squared = radius * radius;
+
+        self.query_cells(center - Vec3::splat(radius), center + Vec3::splat(radius))
+            .filter(|(_, position)| position.distance_squared(center) <= radius_squared)
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+
+    /// Returns all entities inside the axis aligned box from `min` to `max`.
+    pub fn query_aabb(&self, min: Vec3, max: Vec3) -> Vec<Entity> {
+        self.query_cells(min, max)
+            .filter(|(_, position)| position.cmpge(min).all() && position.cmple(max).all())
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+
+    /// The cell containing `position`.
+    fn cell(&self, position: Vec3) -> (i32, i32, i32) {
+        let cell = (position / self.cell_size).floor();
+
+        (cell.x as i32, cell.y as i32, cell.z as i32)
+    }
+
+    /// Iterates over all entries in the cells overlapping the box from `min` to `max`.
+    ///
+    /// Big boxes cover more cells than the index has, e.g. a huge radius over a small cell size,
+    /// so those iterate over the cells of the index instead of every cell in the box.
+    fn query_cells(&self, min: Vec3, max: Vec3) -> Box<dyn Iterator<Item = (Entity, Vec3)> + '_> {
+        let (min, max) = (self.cell(min), self.cell(max));
+        let span = |min: i32, max: i32| (max as i64 - min as i64 + 1).max(0) as u128;
+        let volume = span(min.0, max.0) * span(min.1, max.1) * span(min.2, max.2);
+
+        if volume > self.cells.len() as u128 {
+            let contains = move |cell: &(i32, i32, i32)| {
+                (min.0..=max.0).contains(&cell.0)
+                    && (min.1..=max.1).contains(&cell.1)
+                    && (min.2..=max.2).contains(&cell.2)
+            };
+
+            return Box::new(
+                self.cells
+                    .iter()
+                    .filter(move |(cell, _)| contains(cell))
+                    .flat_map(|(_, entries)| entries.iter().copied()),
+            );
+        }
+
+        Box::new(
+            (min.0..=max.0)
+                .flat_map(move |x| (min.1..=max.1).map(move |y| (x, y)))
+                .flat_map(move |(x, y)| (min.2..=max.2).map(move |z| (x, y, z)))
+                .filter_map(|cell| self.cells.get(&cell))
+                .flatten()
+                .copied(),
+        )
+    }
+}
+
+/// A system that rebuilds every [SpatialIndex] from the [GlobalTransform]s.
+pub fn update_spatial_index(world: SubWorld<(&GlobalTransform, &mut SpatialIndex)>) {
+    let positions = world
+        .query::<&GlobalTransform>()
+        .iter()
+        .map(|(entity, transform)| (entity, transform.w_axis.truncate()))
+        .collect::<Vec<_>>();
+
+    for (_, index) in world.query::<&mut SpatialIndex>().iter() {
+        index.clear();
+        for (entity, position) in &positions {
+            index.insert(*entity, *position);
+        }
+    }
+}
+
+/// A plugin that spawns a [SpatialIndex], and keeps it up to date.
+///
+/// *Note*: This needs to be added after the [crate::TransformPlugin], so the index is rebuilt after the transforms are propagated.
+#[derive(Debug)]
+pub struct SpatialIndexPlugin {
+    /// The cell size of the index, see [SpatialIndex::new].
+    pub cell_size: f32,
+}
+
+impl Default for SpatialIndexPlugin {
+    fn default() -> Self {
+        Self { cell_size: 10.0 }
+    }
+}
+
+impl Plugin for SpatialIndexPlugin {
+    fn build(&self, app: &mut App) -> PluginResult {
+        app.world.spawn((SpatialIndex::new(self.cell_size),));
+        app.register_resource::<SpatialIndex>();
+
+        app.add_post_update_system(update_spatial_index);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_radius() {
+        let mut world = World::new();
+        let mut index = SpatialIndex::new(2.0);
+
+        // two clusters, one around the origin and one far away
+        let near = (0..10)
+            .map(|i| {
+                let entity = world.spawn(());
+                index.insert(entity, Vec3::new(i as f32 * 0.3 - 1.5, 0.5, -0.5));
+                entity
+            })
+            .collect::<Vec<_>>();
+        for i in 0..10 {
+            let entity = world.spawn(());
+            index.insert(entity, Vec3::new(100.0 + i as f32 * 0.3, 0.0, 0.0));
+        }
+        // just outside of the radius, but in a visited cell
+        let outside = world.spawn(());
+        index.insert(outside, Vec3::new(0.0, 3.1, 0.0));
+
+        let mut found = index.query_radius(Vec3::ZERO, 3.0);
+        found.sort();
+        let mut expected = near.clone();
+        expected.sort();
+        assert_eq!(found, expected);
+
+        assert_eq!(
+            index.query_radius(Vec3::new(100.0, 0.0, 0.0), 5.0).len(),
+            10
+        );
+        assert_eq!(
+            index
+                .query_aabb(Vec3::new(-2.0, 0.0, -1.0), Vec3::new(2.0, 4.0, 0.0))
+                .len(),
+            11
+        );
+
+        // far more cells than the index has, so this must not visit them one by one
+        assert_eq!(index.query_radius(Vec3::ZERO, 1000.0).len(), 21);
+        assert_eq!(index.query_radius(Vec3::ZERO, f32::MAX).len(), 21);
+
+        index.clear();
+        assert!(index.query_radius(Vec3::ZERO, 1000.0).is_empty());
+
+        // nothing was inserted since, so the cells are dropped
+        index.clear();
+        assert!(index.cells.is_empty());
+    }
+}
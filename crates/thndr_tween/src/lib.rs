@@ -0,0 +1,363 @@
+//! # thndr_tween
+//!
+//! `thndr_tween` provides frame-rate independent tweening of values, for the Thunder Game Engine.
+//!
+
+use std::mem;
+
+use thndr_app::prelude::*;
+use thndr_ecs::prelude::*;
+use thndr_event::prelude::*;
+use thndr_math::prelude::*;
+use thndr_time::prelude::*;
+use thndr_transform::prelude::*;
+
+/// A value that can be linearly interpolated.
+pub trait Lerp {
+    /// Interpolates between `self` and `other`. `t = 0.0` returns `self`, `t = 1.0` returns `other`.
+    fn lerp(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for Vec2 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Vec2::lerp(*self, *other, t)
+    }
+}
+
+impl Lerp for Vec3 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Vec3::lerp(*self, *other, t)
+    }
+}
+
+impl Lerp for Position {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Position(Vec3::lerp(self.0, other.0, t))
+    }
+}
+
+/// An easing curve, mapping the linear progress of a [Tween] to the eased progress.
+#[derive(Default, Debug, Clone, Copy)]
+pub enum Easing {
+    /// Constant speed.
+    #[default]
+    Linear,
+    /// Starts slow, then speeds up (quadratic).
+    EaseIn,
+    /// Starts fast, then slows down (quadratic).
+    EaseOut,
+    /// Starts and ends slow (quadratic).
+    EaseInOut,
+    /// A custom curve. It should map `0.0` to `0.0` and `1.0` to `1.0`, but may overshoot in between.
+    Custom(fn(f32) -> f32),
+}
+
+impl Easing {
+    /// Applies the curve to the progress `t`, which is clamped to `0.0..=1.0` first.
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Easing::Custom(f) => f(t),
+        }
+    }
+}
+
+/// What a [Tween] does once it reaches its end.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TweenMode {
+    /// Stop at the end, or continue with the tween chained with [Tween::then].
+    #[default]
+    Once,
+    /// Jump back to the start and play again, forever.
+    Loop,
+    /// Play backwards to the start, then forwards again, forever.
+    PingPong,
+}
+
+/// A component that animates the `T` component of the same entity from `start` to `end`.
+///
+/// A menu sliding in would e.g. attach a `Tween<Position>`:
+///
+/// ```ignore
+/// commands.spawn((
+///     Position::new(-10.0, 0.0, 0.0),
+///     Tween::new(Position::new(-10.0, 0.0, 0.0), Position::new(0.0, 0.0, 0.0), 0.5)
+///         .with_easing(Easing::EaseOut),
+/// ));
+/// ```
+///
+/// *Note*: Looping tweens never complete, so they never move on to a chained tween.
+#[derive(Debug, Clone)]
+pub struct Tween<T> {
+    /// The value at the start of the tween.
+    pub start: T,
+    /// The value at the end of the tween.
+    pub end: T,
+    /// The duration of the tween. In seconds.
+    pub duration: f32,
+    /// The time elapsed since the tween started. In seconds.
+    pub elapsed: f32,
+    /// The easing curve.
+    pub easing: Easing,
+    /// What happens once the end is reached.
+    pub mode: TweenMode,
+    next: Option<Box<Tween<T>>>,
+    finished: bool,
+}
+
+impl<T: Lerp> Tween<T> {
+    /// Creates a new linear tween from `start` to `end`, taking `duration` seconds.
+    pub fn new(start: T, end: T, duration: f32) -> Self {
+        Self {
+            start,
+            end,
+            duration,
+            elapsed: 0.0,
+            easing: Easing::Linear,
+            mode: TweenMode::Once,
+            next: None,
+            finished: false,
+        }
+    }
+
+    /// Sets the easing curve.
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Sets the mode.
+    pub fn with_mode(mut self, mode: TweenMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Chains `next` to the end of this tween (or the end of the existing chain).
+    /// The whole chain counts as a single tween, so it only completes once the last tween is done.
+    pub fn then(mut self, next: Tween<T>) -> Self {
+        let mut last = &mut self.next;
+        while let Some(tween) = last {
+            last = &mut tween.next;
+        }
+        *last = Some(Box::new(next));
+
+        self
+    }
+
+    /// Returns the linear progress of the current tween, from `0.0` to `1.0`.
+    pub fn progress(&self) -> f32 {
+        if self.duration > 0.0 {
+            (self.elapsed / self.duration).clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// Returns the current interpolated value.
+    pub fn value(&self) -> T {
+        self.start
+            .lerp(&self.end, self.easing.apply(self.progress()))
+    }
+
+    /// Returns `true` once the tween (including anything chained to it) has completed.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Advances the tween by `delta` seconds. Returns `true` if it completed during this call.
+    ///
+    /// Time overshooting the end carries over into the next loop or chained tween,
+    /// so the total duration doesn't depend on the frame rate.
+    pub fn advance(&mut self, delta: f32) -> bool {
+        if self.finished {
+            return false;
+        }
+
+        self.elapsed += delta;
+        while self.elapsed >= self.duration {
+            let overshoot = self.elapsed - self.duration;
+
+            match self.mode {
+                TweenMode::Once => match self.next.take() {
+                    Some(next) => {
+                        *self = *next;
+                        self.elapsed += overshoot;
+                    }
+                    None => {
+                        self.elapsed = self.duration;
+                        self.finished = true;
+                        return true;
+                    }
+                },
+                TweenMode::Loop | TweenMode::PingPong => {
+                    if self.mode == TweenMode::PingPong {
+                        mem::swap(&mut self.start, &mut self.end);
+                    }
+
+                    // a zero length loop would never make any progress
+                    if self.duration <= 0.0 {
+                        self.elapsed = 0.0;
+                        break;
+                    }
+                    self.elapsed = overshoot;
+                }
+            }
+        }
+
+        false
+    }
+}
+
+/// An event that is spawned when the tween of an entity completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TweenCompletedEvent {
+    /// The entity the tween is attached to.
+    pub entity: Entity,
+}
+
+/// A system that advances all `Tween<T>` components with the [ScaledDeltaTime], and writes the value to the `T` component.
+///
+/// *Note*: Completed tweens are kept, so they can be inspected or restarted. Remove them when they are no longer needed.
+pub fn tween_system<T: Lerp + Component>(
+    world: SubWorld<(&ScaledDeltaTime, &mut Tween<T>, &mut T)>,
+    mut commands: Commands,
+) {
+    let Ok(delta) = world
+        .query::<&ScaledDeltaTime>()
+        .single_mut()
+        .map(|delta| delta.0)
+    else {
+        return;
+    };
+
+    for (entity, (tween, value)) in world.query::<(&mut Tween<T>, &mut T)>().iter() {
+        if tween.is_finished() {
+            continue;
+        }
+
+        let completed = tween.advance(delta);
+        *value = tween.value();
+
+        if completed {
+            commands.spawn((Event::default(), TweenCompletedEvent { entity }));
+        }
+    }
+}
+
+/// A plugin that advances tweens of [Position], `f32`, [Vec2] and [Vec3].
+/// For other types, add a [tween_system] for them yourself.
+///
+/// *Note*: Tweens are advanced in `pre_update`, so `update` systems see the new values,
+/// and transforms are propagated in the same frame. Requires the [TimePlugin], added before this plugin
+/// so the [ScaledDeltaTime] is written before the tweens read it.
+#[derive(Default, Debug)]
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) -> PluginResult {
+        app.add_pre_update_system(tween_system::<Position>)
+            .add_pre_update_system(tween_system::<f32>)
+            .add_pre_update_system(tween_system::<Vec2>)
+            .add_pre_update_system(tween_system::<Vec3>);
+
+        Ok(())
+    }
+}
+
+/// Common types, traits, and functions.
+pub mod prelude {
+    pub use crate::{
+        tween_system, Easing, Lerp, Tween, TweenCompletedEvent, TweenMode, TweenPlugin,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 1e-6, "{a} != {b}");
+    }
+
+    #[test]
+    fn test_easings() {
+        let cases = [
+            (Easing::Linear, 0.5),
+            (Easing::EaseIn, 0.25),
+            (Easing::EaseOut, 0.75),
+            (Easing::EaseInOut, 0.5),
+            (Easing::Custom(|t| t * t * t), 0.125),
+        ];
+
+        for (easing, half) in cases {
+            assert_close(easing.apply(0.0), 0.0);
+            assert_close(easing.apply(0.5), half);
+            assert_close(easing.apply(1.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_tween_once() {
+        let mut tween = Tween::new(Vec2::ZERO, Vec2::new(2.0, 4.0), 1.0);
+
+        assert!(!tween.advance(0.5));
+        assert_eq!(tween.value(), Vec2::new(1.0, 2.0));
+
+        assert!(tween.advance(0.75));
+        assert!(tween.is_finished());
+        assert_eq!(tween.value(), Vec2::new(2.0, 4.0));
+
+        // a finished tween only completes once
+        assert!(!tween.advance(1.0));
+    }
+
+    #[test]
+    fn test_tween_modes() {
+        let mut looping = Tween::new(0.0, 1.0, 1.0).with_mode(TweenMode::Loop);
+        assert!(!looping.advance(2.25));
+        assert_close(looping.value(), 0.25);
+
+        let mut ping_pong = Tween::new(0.0, 1.0, 1.0).with_mode(TweenMode::PingPong);
+        assert!(!ping_pong.advance(1.25));
+        assert_close(ping_pong.value(), 0.75);
+        assert!(!ping_pong.advance(1.0));
+        assert_close(ping_pong.value(), 0.25);
+    }
+
+    #[test]
+    fn test_tween_chain() {
+        let mut tween = Tween::new(
+            Position::new(0.0, 0.0, 0.0),
+            Position::new(1.0, 0.0, 0.0),
+            1.0,
+        )
+        .then(Tween::new(
+            Position::new(1.0, 0.0, 0.0),
+            Position::new(1.0, 2.0, 0.0),
+            2.0,
+        ));
+
+        assert!(!tween.advance(1.5));
+        assert_eq!(tween.value().0, Vec3::new(1.0, 0.5, 0.0));
+
+        assert!(tween.advance(1.5));
+        assert_eq!(tween.value().0, Vec3::new(1.0, 2.0, 0.0));
+    }
+}
@@ -1,7 +1,12 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use winit::{event::MouseButton, keyboard::KeyCode};
+use serde::{Deserialize, Serialize};
+use winit::{
+    event::MouseButton,
+    keyboard::{Key, KeyCode},
+};
 
+use thndr_ecs::prelude::Entity;
 use thndr_math::prelude::*;
 
 /// Component for easily checking key state.
@@ -13,6 +18,8 @@ pub struct Keys {
     just_pressed: HashSet<KeyCode>,
     /// The keys that were just released.
     just_released: HashSet<KeyCode>,
+    /// The labels of the keys on the current keyboard layout, learned from key presses.
+    labels: HashMap<KeyCode, String>,
 }
 
 impl Keys {
@@ -31,6 +38,32 @@ impl Keys {
         self.just_released.contains(&key)
     }
 
+    /// Get the label of `key` on the player's keyboard layout, e.g. "A" for [KeyCode::KeyQ] on an AZERTY keyboard.
+    ///
+    /// winit can't query the layout up front, so the labels are learned as keys are pressed,
+    /// and until then the US name from [key_name] is returned. Only letters are learned,
+    /// as the other characters depend on the modifiers held while pressing the key.
+    pub fn label(&self, key: KeyCode) -> &str {
+        self.labels
+            .get(&key)
+            .map(String::as_str)
+            .unwrap_or_else(|| key_name(key))
+    }
+
+    /// Learn the label of `key` on the current layout, from the `logical` key it produced.
+    pub(crate) fn learn_label(&mut self, key: KeyCode, logical: &Key) {
+        let Key::Character(text) = logical else {
+            return;
+        };
+
+        let mut chars = text.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            if c.is_alphabetic() {
+                self.labels.insert(key, c.to_uppercase().collect());
+            }
+        }
+    }
+
     pub(crate) fn press(&mut self, key: KeyCode) {
         self.pressed.insert(key);
         self.just_pressed.insert(key);
@@ -45,6 +78,285 @@ impl Keys {
         self.just_pressed.clear();
         self.just_released.clear();
     }
+
+    /// Take a [KeysSnapshot] of the key state, e.g. to send it to a server, or to replay it for client-side prediction.
+    pub fn snapshot(&self) -> KeysSnapshot {
+        KeysSnapshot {
+            pressed: self.pressed.clone(),
+            just_pressed: self.just_pressed.clone(),
+            just_released: self.just_released.clone(),
+        }
+    }
+
+    /// Forget all key state, as if no key was ever pressed. The learned labels are kept.
+    ///
+    /// *Note*: This does not count as releasing the pressed keys, so they won't show up in [Keys::just_released].
+    pub fn reset(&mut self) {
+        self.pressed.clear();
+        self.just_pressed.clear();
+        self.just_released.clear();
+    }
+}
+
+/// An immutable copy of the [Keys] state at one point in time, see [Keys::snapshot].
+///
+/// A netcode layer takes one every fixed tick, and buffers them to replay the input when the server corrects
+/// the game state. The learned labels are left out, they are only of use to the local player.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeysSnapshot {
+    pressed: HashSet<KeyCode>,
+    just_pressed: HashSet<KeyCode>,
+    just_released: HashSet<KeyCode>,
+}
+
+impl KeysSnapshot {
+    /// Check if a key was pressed.
+    pub fn pressed(&self, key: KeyCode) -> bool {
+        self.pressed.contains(&key)
+    }
+
+    /// Check if a key was just pressed.
+    pub fn just_pressed(&self, key: KeyCode) -> bool {
+        self.just_pressed.contains(&key)
+    }
+
+    /// Check if a key was just released.
+    pub fn just_released(&self, key: KeyCode) -> bool {
+        self.just_released.contains(&key)
+    }
+}
+
+/// Auto-repeat for held keys, like a text field repeats a held key, but controlled by the game and frame-accurate.
+/// A menu uses it to scroll through a list while Down is held.
+///
+/// A key triggers on the frame it's pressed, then once more after [RepeatInput::initial_delay],
+/// and from then on every [RepeatInput::interval], until it's released.
+/// The OS key-repeat plays no role, only [Keys] is used.
+///
+/// ```ignore
+/// repeat.update(keys, delta.0);
+/// if repeat.should_trigger(KeyCode::ArrowDown) {
+///     menu.select_next();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RepeatInput {
+    /// The time a key needs to be held before it repeats, in seconds.
+    pub initial_delay: f32,
+    /// The time between repeats, in seconds.
+    pub interval: f32,
+    /// How long the held keys have been held.
+    held: HashMap<KeyCode, f32>,
+    /// The keys that trigger this frame.
+    triggered: HashSet<KeyCode>,
+}
+
+impl Default for RepeatInput {
+    fn default() -> Self {
+        Self::new(0.4, 0.1)
+    }
+}
+
+impl RepeatInput {
+    /// Creates a new [RepeatInput], repeating after `initial_delay` and then every `interval` seconds.
+    pub fn new(initial_delay: f32, interval: f32) -> Self {
+        Self {
+            initial_delay,
+            interval,
+            held: HashMap::new(),
+            triggered: HashSet::new(),
+        }
+    }
+
+    /// Get the number of times a key held for `time` seconds has repeated.
+    fn repeats(&self, time: f32) -> u32 {
+        if time < self.initial_delay {
+            0
+        } else {
+            ((time - self.initial_delay) / self.interval) as u32 + 1
+        }
+    }
+
+    /// Advance the timers of the held `keys` by `delta` seconds. Call this once per frame, after the input was handled.
+    pub fn update(&mut self, keys: &Keys, delta: f32) {
+        self.triggered.clear();
+        self.held.retain(|key, _| keys.pressed(*key));
+
+        for key in &keys.just_pressed {
+            self.triggered.insert(*key);
+            self.held.insert(*key, 0.0);
+        }
+
+        for key in &keys.pressed {
+            if keys.just_pressed(*key) {
+                continue;
+            }
+
+            let held = self.held.entry(*key).or_default();
+            let previous = *held;
+            let current = previous + delta;
+            *held = current;
+
+            // a long frame can skip over several repeats, they still only trigger once
+            if self.repeats(current) > self.repeats(previous) {
+                self.triggered.insert(*key);
+            }
+        }
+    }
+
+    /// Check if `key` triggers this frame, either because it was just pressed, or because it repeated.
+    pub fn should_trigger(&self, key: KeyCode) -> bool {
+        self.triggered.contains(&key)
+    }
+}
+
+/// Get a human-readable name of `key`, for showing it in the UI, e.g. "Press [E] to interact".
+///
+/// The names are the labels on a US keyboard, since a [KeyCode] is a physical position on the keyboard,
+/// not the character printed on it. Use [Keys::label] for the label on the player's actual layout.
+/// Keys without a common name (e.g. launcher or browser keys) are named `"Unknown"`.
+pub fn key_name(key: KeyCode) -> &'static str {
+    match key {
+        KeyCode::KeyA => "A",
+        KeyCode::KeyB => "B",
+        KeyCode::KeyC => "C",
+        KeyCode::KeyD => "D",
+        KeyCode::KeyE => "E",
+        KeyCode::KeyF => "F",
+        KeyCode::KeyG => "G",
+        KeyCode::KeyH => "H",
+        KeyCode::KeyI => "I",
+        KeyCode::KeyJ => "J",
+        KeyCode::KeyK => "K",
+        KeyCode::KeyL => "L",
+        KeyCode::KeyM => "M",
+        KeyCode::KeyN => "N",
+        KeyCode::KeyO => "O",
+        KeyCode::KeyP => "P",
+        KeyCode::KeyQ => "Q",
+        KeyCode::KeyR => "R",
+        KeyCode::KeyS => "S",
+        KeyCode::KeyT => "T",
+        KeyCode::KeyU => "U",
+        KeyCode::KeyV => "V",
+        KeyCode::KeyW => "W",
+        KeyCode::KeyX => "X",
+        KeyCode::KeyY => "Y",
+        KeyCode::KeyZ => "Z",
+        KeyCode::Digit0 => "0",
+        KeyCode::Digit1 => "1",
+        KeyCode::Digit2 => "2",
+        KeyCode::Digit3 => "3",
+        KeyCode::Digit4 => "4",
+        KeyCode::Digit5 => "5",
+        KeyCode::Digit6 => "6",
+        KeyCode::Digit7 => "7",
+        KeyCode::Digit8 => "8",
+        KeyCode::Digit9 => "9",
+        KeyCode::Backquote => "`",
+        KeyCode::Backslash => "\\",
+        KeyCode::BracketLeft => "[",
+        KeyCode::BracketRight => "]",
+        KeyCode::Comma => ",",
+        KeyCode::Equal => "=",
+        KeyCode::Minus => "-",
+        KeyCode::Period => ".",
+        KeyCode::Quote => "'",
+        KeyCode::Semicolon => ";",
+        KeyCode::Slash => "/",
+        KeyCode::IntlBackslash => "\\",
+        KeyCode::IntlRo => "Ro",
+        KeyCode::IntlYen => "Yen",
+        KeyCode::AltLeft => "Left Alt",
+        KeyCode::AltRight => "Right Alt",
+        KeyCode::Backspace => "Backspace",
+        KeyCode::CapsLock => "Caps Lock",
+        KeyCode::ContextMenu => "Menu",
+        KeyCode::ControlLeft => "Left Ctrl",
+        KeyCode::ControlRight => "Right Ctrl",
+        KeyCode::Enter => "Enter",
+        KeyCode::SuperLeft => "Left Super",
+        KeyCode::SuperRight => "Right Super",
+        KeyCode::ShiftLeft => "Left Shift",
+        KeyCode::ShiftRight => "Right Shift",
+        KeyCode::Space => "Space",
+        KeyCode::Tab => "Tab",
+        KeyCode::Delete => "Delete",
+        KeyCode::End => "End",
+        KeyCode::Help => "Help",
+        KeyCode::Home => "Home",
+        KeyCode::Insert => "Insert",
+        KeyCode::PageDown => "Page Down",
+        KeyCode::PageUp => "Page Up",
+        KeyCode::ArrowDown => "Down",
+        KeyCode::ArrowLeft => "Left",
+        KeyCode::ArrowRight => "Right",
+        KeyCode::ArrowUp => "Up",
+        KeyCode::NumLock => "Num Lock",
+        KeyCode::NumpadAdd => "Numpad +",
+        KeyCode::NumpadBackspace => "Numpad Backspace",
+        KeyCode::NumpadClear => "Numpad Clear",
+        KeyCode::NumpadClearEntry => "Numpad Clear Entry",
+        KeyCode::NumpadComma => "Numpad ,",
+        KeyCode::NumpadDecimal => "Numpad .",
+        KeyCode::NumpadDivide => "Numpad /",
+        KeyCode::NumpadEnter => "Numpad Enter",
+        KeyCode::NumpadEqual => "Numpad =",
+        KeyCode::NumpadHash => "Numpad #",
+        KeyCode::NumpadMultiply => "Numpad *",
+        KeyCode::NumpadParenLeft => "Numpad (",
+        KeyCode::NumpadParenRight => "Numpad )",
+        KeyCode::NumpadStar => "Numpad *",
+        KeyCode::NumpadSubtract => "Numpad -",
+        KeyCode::Escape => "Esc",
+        KeyCode::Fn => "Fn",
+        KeyCode::FnLock => "Fn Lock",
+        KeyCode::PrintScreen => "Print Screen",
+        KeyCode::ScrollLock => "Scroll Lock",
+        KeyCode::Pause => "Pause",
+        KeyCode::MediaPlayPause => "Play/Pause",
+        KeyCode::MediaStop => "Stop",
+        KeyCode::MediaTrackNext => "Next Track",
+        KeyCode::MediaTrackPrevious => "Previous Track",
+        KeyCode::AudioVolumeDown => "Volume Down",
+        KeyCode::AudioVolumeMute => "Mute",
+        KeyCode::AudioVolumeUp => "Volume Up",
+        KeyCode::Numpad0 => "Numpad 0",
+        KeyCode::Numpad1 => "Numpad 1",
+        KeyCode::Numpad2 => "Numpad 2",
+        KeyCode::Numpad3 => "Numpad 3",
+        KeyCode::Numpad4 => "Numpad 4",
+        KeyCode::Numpad5 => "Numpad 5",
+        KeyCode::Numpad6 => "Numpad 6",
+        KeyCode::Numpad7 => "Numpad 7",
+        KeyCode::Numpad8 => "Numpad 8",
+        KeyCode::Numpad9 => "Numpad 9",
+        KeyCode::F1 => "F1",
+        KeyCode::F2 => "F2",
+        KeyCode::F3 => "F3",
+        KeyCode::F4 => "F4",
+        KeyCode::F5 => "F5",
+        KeyCode::F6 => "F6",
+        KeyCode::F7 => "F7",
+        KeyCode::F8 => "F8",
+        KeyCode::F9 => "F9",
+        KeyCode::F10 => "F10",
+        KeyCode::F11 => "F11",
+        KeyCode::F12 => "F12",
+        KeyCode::F13 => "F13",
+        KeyCode::F14 => "F14",
+        KeyCode::F15 => "F15",
+        KeyCode::F16 => "F16",
+        KeyCode::F17 => "F17",
+        KeyCode::F18 => "F18",
+        KeyCode::F19 => "F19",
+        KeyCode::F20 => "F20",
+        KeyCode::F21 => "F21",
+        KeyCode::F22 => "F22",
+        KeyCode::F23 => "F23",
+        KeyCode::F24 => "F24",
+        _ => "Unknown",
+    }
 }
 
 /// A key press event.
@@ -62,6 +374,17 @@ pub struct KeyReleaseEvent {
 }
 
 /// A component for easily checking mouse state.
+///
+/// # Outside the window
+///
+/// Once the cursor leaves the window, most platforms stop reporting where it is. The [Mouse] tells apart:
+/// - [Mouse::position], which keeps tracking during a drag that leaves the window, e.g. a slider dragged past the
+///   window's edge, so it can lie outside the window.
+/// - [Mouse::clamped_position], the same position clamped to the window, e.g. for hovering and picking.
+///
+/// While a button is held outside the window, the position follows the raw motion of the mouse. This is best-effort:
+/// the raw motion doesn't include the pointer acceleration of the OS, so it drifts from the real cursor,
+/// until the cursor re-enters the window and the position is reported again.
 #[derive(Debug, Default)]
 pub struct Mouse {
     /// The position of the mouse.
@@ -76,20 +399,39 @@ pub struct Mouse {
     just_pressed: HashSet<MouseButton>,
     /// The buttons that were just released.
     just_released: HashSet<MouseButton>,
+    /// Whether the cursor is inside the window.
+    inside: bool,
 }
 
 impl Mouse {
-    /// Get the position of the mouse.
+    /// Get the position of the mouse, in physical pixels relative to the top-left corner of the window.
+    /// Keeps tracking during drags outside the window, see [Mouse] for the details.
     pub fn position(&self) -> Vec2 {
         self.position
     }
 
+    /// Get the position of the mouse, clamped to a window of `size` in physical pixels,
+    /// e.g. the size of the last [WindowResizeEvent](crate::WindowResizeEvent).
+    /// Inside the window, this is the same as [Mouse::position].
+    pub fn clamped_position(&self, size: Vec2) -> Vec2 {
+        self.position.clamp(Vec2::ZERO, size)
+    }
+
+    /// Check if the cursor is inside the window.
+    pub fn is_inside(&self) -> bool {
+        self.inside
+    }
+
     /// Get the delta position of the mouse.
     pub fn delta(&self) -> Vec2 {
         self.position - self.last_position
     }
 
-    /// Get the scroll delta of the mouse.
+    /// Get the scroll delta of the mouse, in this frame. Zero on frames without wheel movement.
+    ///
+    /// The scroll is set in `update` by [handle_input](crate::handle_input), and cleared at the end of `post_update`
+    /// by [clear_mouse_scroll](crate::clear_mouse_scroll), which the window plugins add after every other system.
+    /// So read it in `update` or `post_update`; in `pre_update` it is always zero.
     pub fn scroll(&self) -> Vec2 {
         self.scroll
     }
@@ -109,11 +451,42 @@ impl Mouse {
         self.just_released.contains(&button)
     }
 
+    /// Check if the back button (usually the 4th side button) is pressed.
+    pub fn back_pressed(&self) -> bool {
+        self.pressed(MouseButton::Back)
+    }
+
+    /// Check if the forward button (usually the 5th side button) is pressed.
+    pub fn forward_pressed(&self) -> bool {
+        self.pressed(MouseButton::Forward)
+    }
+
+    /// Check if an extra button is pressed, identified by its platform specific id.
+    ///
+    /// *Note*: Buttons with a dedicated variant (e.g. [MouseButton::Back]) are never reported as `Other`,
+    /// and the ids of the remaining buttons depend on the platform and the mouse driver.
+    pub fn other_pressed(&self, id: u16) -> bool {
+        self.pressed(MouseButton::Other(id))
+    }
+
     pub(crate) fn move_to(&mut self, position: Vec2) {
         self.last_position = self.position;
         self.position = position;
     }
 
+    /// Check if a button is held while the cursor is outside the window, the only time raw motion is tracked.
+    pub(crate) fn is_dragging_outside(&self) -> bool {
+        !self.inside && !self.buttons.is_empty()
+    }
+
+    pub(crate) fn set_inside(&mut self, inside: bool) {
+        self.inside = inside;
+    }
+
+    pub(crate) fn move_by(&mut self, delta: Vec2) {
+        self.move_to(self.position + delta);
+    }
+
     pub(crate) fn set_scroll(&mut self, scroll: Vec2) {
         self.scroll = scroll;
     }
@@ -131,15 +504,92 @@ impl Mouse {
     pub(crate) fn update(&mut self) {
         self.just_pressed.clear();
         self.just_released.clear();
+    }
+
+    pub(crate) fn clear_scroll(&mut self) {
+        self.scroll = Vec2::ZERO;
+    }
+
+    /// Take a [MouseSnapshot] of the mouse state, e.g. to send it to a server, or to replay it for client-side prediction.
+    pub fn snapshot(&self) -> MouseSnapshot {
+        MouseSnapshot {
+            position: self.position.to_array(),
+            delta: self.delta().to_array(),
+            scroll: self.scroll.to_array(),
+            buttons: self.buttons.clone(),
+            just_pressed: self.just_pressed.clone(),
+            just_released: self.just_released.clone(),
+        }
+    }
+
+    /// Forget all button state, as if no button was ever pressed. The position is kept.
+    ///
+    /// *Note*: This does not count as releasing the pressed buttons, so they won't show up in [Mouse::just_released].
+    pub fn reset(&mut self) {
+        self.buttons.clear();
+        self.just_pressed.clear();
+        self.just_released.clear();
         self.scroll = Vec2::ZERO;
     }
 }
 
+/// An immutable copy of the [Mouse] state at one point in time, see [Mouse::snapshot] and [KeysSnapshot].
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MouseSnapshot {
+    // stored as arrays, as the math types aren't serializable
+    position: [f32; 2],
+    delta: [f32; 2],
+    scroll: [f32; 2],
+    buttons: HashSet<MouseButton>,
+    just_pressed: HashSet<MouseButton>,
+    just_released: HashSet<MouseButton>,
+}
+
+impl MouseSnapshot {
+    /// Get the position of the mouse.
+    pub fn position(&self) -> Vec2 {
+        Vec2::from(self.position)
+    }
+
+    /// Get the delta position of the mouse.
+    pub fn delta(&self) -> Vec2 {
+        Vec2::from(self.delta)
+    }
+
+    /// Get the scroll delta of the mouse.
+    pub fn scroll(&self) -> Vec2 {
+        Vec2::from(self.scroll)
+    }
+
+    /// Check if a button was pressed.
+    pub fn pressed(&self, button: MouseButton) -> bool {
+        self.buttons.contains(&button)
+    }
+
+    /// Check if a button was just pressed.
+    pub fn just_pressed(&self, button: MouseButton) -> bool {
+        self.just_pressed.contains(&button)
+    }
+
+    /// Check if a button was just released.
+    pub fn just_released(&self, button: MouseButton) -> bool {
+        self.just_released.contains(&button)
+    }
+}
+
 /// A mouse move event.
+///
+/// Moves are coalesced, so at most one of these is spawned per frame, with the final position of the mouse.
+/// No movement is lost though, as the [MouseMoveEvent::delta] accumulates over all coalesced moves.
+///
+/// *Note*: The [WindowRunner](crate::WindowRunner) drops the intermediate positions.
+/// If you need every sub-frame sample (e.g. for smooth drawing), handle `WindowEvent::CursorMoved` in a custom runner.
 #[derive(Default, Debug, Clone)]
 pub struct MouseMoveEvent {
     /// The new position of the mouse.
     pub position: Vec2,
+    /// The movement since the previous [MouseMoveEvent].
+    pub delta: Vec2,
 }
 
 /// A mouse press event.
@@ -156,6 +606,27 @@ pub struct MouseReleaseEvent {
     pub button: MouseButton,
 }
 
+/// Raw motion of the mouse, without the pointer acceleration of the OS. Only spawned while a button is held
+/// with the cursor outside the window, to keep the [Mouse::position] tracking during the drag.
+///
+/// Motion is coalesced, so at most one of these is spawned per frame, with the summed up delta.
+#[derive(Default, Debug, Clone)]
+pub struct MouseMotionEvent {
+    /// The raw movement, roughly in physical pixels.
+    pub delta: Vec2,
+}
+
+/// An event that is spawned when the cursor enters or leaves a window.
+///
+/// Coalesced, so at most one of these is spawned per frame, with the final state.
+#[derive(Debug, Clone)]
+pub struct MouseInsideEvent {
+    /// Whether the cursor is inside the window now.
+    pub inside: bool,
+    /// The window entity the cursor entered or left.
+    pub window: Entity,
+}
+
 /// A mouse scroll event.
 #[derive(Debug, Clone)]
 pub struct MouseScrollEvent {
@@ -166,9 +637,113 @@ pub struct MouseScrollEvent {
 /// Common types, traits, and functions.
 pub mod prelude {
     pub use super::{
-        KeyPressEvent, KeyReleaseEvent, Keys, Mouse, MouseMoveEvent, MousePressEvent,
-        MouseReleaseEvent, MouseScrollEvent,
+        key_name, KeyPressEvent, KeyReleaseEvent, Keys, KeysSnapshot, Mouse, MouseInsideEvent,
+        MouseMotionEvent, MouseMoveEvent, MousePressEvent, MouseReleaseEvent, MouseScrollEvent,
+        MouseSnapshot, RepeatInput,
     };
 
     pub use winit::{event::MouseButton, keyboard::KeyCode};
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_names() {
+        assert_eq!(key_name(KeyCode::KeyE), "E");
+        assert_eq!(key_name(KeyCode::Digit1), "1");
+        assert_eq!(key_name(KeyCode::Space), "Space");
+        assert_eq!(key_name(KeyCode::ArrowUp), "Up");
+        assert_eq!(key_name(KeyCode::F12), "F12");
+        assert_eq!(key_name(KeyCode::ShiftLeft), "Left Shift");
+        assert_eq!(key_name(KeyCode::Escape), "Esc");
+        assert_eq!(key_name(KeyCode::BrowserBack), "Unknown");
+    }
+
+    #[test]
+    fn test_learned_labels() {
+        let mut keys = Keys::default();
+        assert_eq!(keys.label(KeyCode::KeyQ), "Q");
+
+        // an AZERTY keyboard has A where a US keyboard has Q
+        keys.learn_label(KeyCode::KeyQ, &Key::Character("a".into()));
+        assert_eq!(keys.label(KeyCode::KeyQ), "A");
+
+        // shifted characters aren't learned
+        keys.learn_label(KeyCode::Digit1, &Key::Character("!".into()));
+        assert_eq!(keys.label(KeyCode::Digit1), "1");
+
+        keys.reset();
+        assert_eq!(keys.label(KeyCode::KeyQ), "A");
+    }
+
+    #[test]
+    fn test_repeat_input() {
+        let mut keys = Keys::default();
+        let mut repeat = RepeatInput::new(0.5, 0.25);
+        let frame = |keys: &mut Keys, repeat: &mut RepeatInput| {
+            repeat.update(keys, 0.125);
+            keys.update();
+            repeat.should_trigger(KeyCode::ArrowDown)
+        };
+
+        keys.press(KeyCode::ArrowDown);
+        let triggers = (0..10)
+            .map(|_| frame(&mut keys, &mut repeat))
+            .collect::<Vec<_>>();
+        // pressed, the initial delay, the first repeat, and then every interval
+        assert_eq!(
+            triggers,
+            [true, false, false, false, true, false, true, false, true, false]
+        );
+
+        keys.release(KeyCode::ArrowDown);
+        assert!(!frame(&mut keys, &mut repeat));
+
+        // pressing again starts over
+        keys.press(KeyCode::ArrowDown);
+        assert!(frame(&mut keys, &mut repeat));
+        assert!(!frame(&mut keys, &mut repeat));
+    }
+
+    #[test]
+    fn test_input_snapshot() {
+        let mut keys = Keys::default();
+        let mut mouse = Mouse::default();
+        keys.press(KeyCode::KeyW);
+        mouse.move_to(Vec2::new(10.0, 20.0));
+        mouse.press(MouseButton::Left);
+
+        let keys_snapshot = keys.snapshot();
+        let mouse_snapshot = mouse.snapshot();
+
+        keys.update();
+        keys.release(KeyCode::KeyW);
+        keys.press(KeyCode::Space);
+        mouse.move_to(Vec2::new(30.0, 40.0));
+        mouse.update();
+        mouse.release(MouseButton::Left);
+
+        assert!(keys_snapshot.pressed(KeyCode::KeyW));
+        assert!(keys_snapshot.just_pressed(KeyCode::KeyW));
+        assert!(!keys_snapshot.pressed(KeyCode::Space));
+        assert!(!keys_snapshot.just_released(KeyCode::KeyW));
+        assert_eq!(mouse_snapshot.position(), Vec2::new(10.0, 20.0));
+        assert_eq!(mouse_snapshot.delta(), Vec2::new(10.0, 20.0));
+        assert!(mouse_snapshot.pressed(MouseButton::Left));
+        assert!(mouse_snapshot.just_pressed(MouseButton::Left));
+
+        // the snapshots survive the trip to a server
+        let json = serde_json::to_string(&keys_snapshot).unwrap();
+        assert_eq!(
+            serde_json::from_str::<KeysSnapshot>(&json).unwrap(),
+            keys_snapshot
+        );
+        let json = serde_json::to_string(&mouse_snapshot).unwrap();
+        assert_eq!(
+            serde_json::from_str::<MouseSnapshot>(&json).unwrap(),
+            mouse_snapshot
+        );
+    }
+}
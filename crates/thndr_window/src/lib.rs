@@ -3,26 +3,83 @@
 //! `thndr_window` is a simple windowing library for Rust, built for the Thunder Engine.
 //!
 
-use std::sync::{Arc, Mutex};
+use std::{
+    cell::RefCell,
+    path::PathBuf,
+    rc::Rc,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use anyhow::Result;
+use thiserror::Error;
 use thndr_app::prelude::*;
 use thndr_ecs::prelude::*;
 use thndr_event::prelude::*;
 use thndr_math::prelude::*;
 use thndr_tracing::prelude::*;
 use winit::{
-    dpi::PhysicalSize,
-    event::{ElementState, Event as WinitEvent, MouseScrollDelta, WindowEvent},
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize, Size},
+    error::{EventLoopError, ExternalError, OsError},
+    event::{DeviceEvent, ElementState, Event as WinitEvent, Ime, MouseScrollDelta, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     keyboard::PhysicalKey,
-    window::{Window as WinitWindow, WindowBuilder},
+    monitor::MonitorHandle,
+    window::{
+        CursorGrabMode, Fullscreen, UserAttentionType, Window as WinitWindow, WindowBuilder,
+        WindowId,
+    },
 };
 
 use input::prelude::*;
+use record::prelude::*;
 
 /// Provides input functionality.
 pub mod input;
+/// Provides input recording and playback.
+pub mod record;
+
+/// All errors related to windows.
+#[derive(Debug, Error)]
+pub enum WindowError {
+    /// The event loop couldn't be created. This is what happens when there is no display available, e.g. on a headless CI machine.
+    #[error("Failed to create the event loop, is a display available? {0}")]
+    EventLoop(EventLoopError),
+    /// The event loop failed while running.
+    #[error("The event loop failed: {0}")]
+    Run(EventLoopError),
+    /// The window couldn't be created.
+    #[error("Failed to create the window: {0}")]
+    Creation(#[from] OsError),
+    /// The window for a [WindowConfig] wasn't created during startup, the title is included.
+    #[error("The window \"{0}\" wasn't created")]
+    NotCreated(String),
+    /// The monitor can't be determined, e.g. when running headless.
+    #[error("No monitor available")]
+    NoMonitor,
+    /// The platform doesn't support the operation.
+    #[error("Unsupported by the platform: {0}")]
+    Unsupported(#[from] ExternalError),
+    /// Reading or writing a file failed, e.g. an [record::InputRecording].
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    /// A file couldn't be (de)serialized, e.g. an [record::InputRecording].
+    #[error("Serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+}
+
+/// The result of a window operation.
+pub type Result<T, E = WindowError> = std::result::Result<T, E>;
+
+/// How the size in a [WindowConfig] is interpreted.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeMode {
+    /// The size is in physical pixels, so the window looks smaller on high DPI monitors.
+    #[default]
+    Physical,
+    /// The size is in logical pixels, which are scaled by the monitor's scale factor.
+    /// This gives the window the same apparent size on every monitor, and is what most apps want.
+    Logical,
+}
 
 /// A component that represents a window configuration.
 /// If the [WindowPlugin] is added to the [App],  the first entity with a [WindowConfig] component will be used to create the [Window].
@@ -30,10 +87,20 @@ pub mod input;
 pub struct WindowConfig {
     /// The title of the window.
     pub title: String,
-    /// The width of the window. In pixels.
+    /// The width of the window. In pixels, see [WindowConfig::size_mode].
     pub width: u32,
-    /// The height of the window. In pixels.
+    /// The height of the window. In pixels, see [WindowConfig::size_mode].
     pub height: u32,
+    /// Whether the width and height are physical or logical pixels.
+    /// Defaults to [SizeMode::Physical], but [SizeMode::Logical] is recommended.
+    pub size_mode: SizeMode,
+    /// Whether the window is visible when it is created.
+    /// Create it hidden to avoid showing an empty window during a long startup, and show it with [Window::set_visible] once ready.
+    pub visible: bool,
+    /// Whether the window is transparent where nothing is drawn, e.g. for a HUD over the desktop.
+    /// Rendering needs to play along: configure the surface with a pre-multiplied alpha mode,
+    /// and clear every frame to a color with zero alpha. Not every platform supports it.
+    pub transparent: bool,
 }
 
 impl Default for WindowConfig {
@@ -42,166 +109,897 @@ impl Default for WindowConfig {
             title: "App".to_string(),
             width: 1280,
             height: 720,
+            size_mode: SizeMode::Physical,
+            visible: true,
+            transparent: false,
         }
     }
 }
 
+impl WindowConfig {
+    /// Creates a new [WindowConfig], with the defaults. Change what you need with the `with_*` methods,
+    /// e.g. `WindowConfig::new().with_title("Game").with_size(1600, 900)`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a 1280x720 [WindowConfig], in physical pixels.
+    pub fn p720() -> Self {
+        Self::new().with_size(1280, 720)
+    }
+
+    /// Creates a 1920x1080 [WindowConfig], in physical pixels.
+    pub fn p1080() -> Self {
+        Self::new().with_size(1920, 1080)
+    }
+
+    /// Creates a 2560x1440 [WindowConfig], in physical pixels.
+    pub fn p1440() -> Self {
+        Self::new().with_size(2560, 1440)
+    }
+
+    /// Creates a [WindowConfig] with the 1280x800 resolution of the Steam Deck's screen, in physical pixels.
+    pub fn steam_deck() -> Self {
+        Self::new().with_size(1280, 800)
+    }
+
+    /// Set the title of the window.
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+
+        self
+    }
+
+    /// Set the size of the window. In pixels, see [WindowConfig::with_size_mode].
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+
+        self
+    }
+
+    /// Set the height from the width, so the window has the aspect `ratio`, e.g. `16.0 / 9.0`.
+    /// The height is rounded to whole pixels, and is at least one pixel.
+    pub fn with_aspect_ratio(mut self, ratio: f32) -> Self {
+        self.height = ((self.width as f32 / ratio).round() as u32).max(1);
+
+        self
+    }
+
+    /// Set whether the size is in physical or logical pixels.
+    pub fn with_size_mode(mut self, size_mode: SizeMode) -> Self {
+        self.size_mode = size_mode;
+
+        self
+    }
+
+    /// Set whether the window is visible when it is created.
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+
+        self
+    }
+
+    /// Set whether the window is transparent, see [WindowConfig::transparent].
+    pub fn with_transparent(mut self, transparent: bool) -> Self {
+        self.transparent = transparent;
+
+        self
+    }
+}
+
 /// A window resize event.
-#[derive(Debug, Default, Clone)]
+///
+/// Resizes are coalesced, so there is at most one pending event per window, with the final size of the frame.
+/// It is also spawned when the scale factor changes, so render code can reconfigure the surface and UI scaling in one place.
+#[derive(Debug, Clone)]
 pub struct WindowResizeEvent {
-    /// The new width of the window.
+    /// The new width of the window. In physical pixels.
     pub width: u32,
-    /// The new height of the window.
+    /// The new height of the window. In physical pixels.
     pub height: u32,
+    /// The scale factor of the window at the new size.
+    pub scale_factor: f64,
+    /// The window entity that was resized.
+    pub window: Entity,
+}
+
+/// An event with the scale factor of a window, which maps logical pixels to physical pixels.
+/// It is spawned when the window is created, and whenever the scale factor changes (e.g. when moved to another monitor).
+#[derive(Debug, Clone)]
+pub struct WindowScaleFactorEvent {
+    /// The new scale factor.
+    pub scale_factor: f64,
+}
+
+/// An event that is spawned when a file is dropped onto a window.
+///
+/// When multiple files are dropped at once, one event is spawned per file.
+///
+/// *Note*: Windows, macOS and X11 report dropped files. Wayland and the web currently don't report them at all.
+#[derive(Debug, Clone)]
+pub struct FileDroppedEvent {
+    /// The path of the dropped file.
+    pub path: PathBuf,
+    /// The window entity the file was dropped onto.
+    pub window: Entity,
+}
+
+/// An event that is spawned when a file is dragged over a window.
+///
+/// When multiple files are hovered at once, one event is spawned per file.
+///
+/// *Note*: Like [FileDroppedEvent], this is only reported on Windows, macOS and X11.
+/// Don't rely on a hover preceding a drop.
+#[derive(Debug, Clone)]
+pub struct FileHoveredEvent {
+    /// The path of the hovered file.
+    pub path: PathBuf,
+    /// The window entity the file is hovered over.
+    pub window: Entity,
+}
+
+/// An event of the input method editor (IME), used to type e.g. Chinese, Japanese or Korean text.
+/// Only spawned while IME is allowed, see [Window::set_ime_allowed].
+///
+/// While composing, the IME reports the text typed so far with [ImeEvent::Preedit], which a text field shows underlined
+/// at the caret. Once the user picks a candidate, the final text arrives with [ImeEvent::Commit].
+///
+/// *Note*: Take text only from IME events while IME is allowed, not from [KeyPressEvent]s.
+/// The key presses that make up a composition are reported too, so using both inserts the text twice.
+/// Key presses are still the right choice for shortcuts and for moving the caret.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImeEvent {
+    /// IME was enabled, and the other events may follow.
+    Enabled {
+        /// The window entity.
+        window: Entity,
+    },
+    /// The text being composed changed. An empty text means the composition was cleared.
+    Preedit {
+        /// The text being composed.
+        text: String,
+        /// The byte range of the cursor in the `text`, or `None` if it should be hidden.
+        cursor: Option<(usize, usize)>,
+        /// The window entity.
+        window: Entity,
+    },
+    /// The composition is done, `text` should be inserted at the caret.
+    Commit {
+        /// The text to insert.
+        text: String,
+        /// The window entity.
+        window: Entity,
+    },
+    /// IME was disabled, any text being composed should be cleared.
+    Disabled {
+        /// The window entity.
+        window: Entity,
+    },
+}
+
+impl ImeEvent {
+    /// Converts a winit IME event of the `window` entity.
+    fn new(ime: Ime, window: Entity) -> Self {
+        match ime {
+            Ime::Enabled => Self::Enabled { window },
+            Ime::Preedit(text, cursor) => Self::Preedit {
+                text,
+                cursor,
+                window,
+            },
+            Ime::Commit(text) => Self::Commit { text, window },
+            Ime::Disabled => Self::Disabled { window },
+        }
+    }
+
+    /// Get the window entity of the event.
+    pub fn window(&self) -> Entity {
+        match self {
+            Self::Enabled { window }
+            | Self::Preedit { window, .. }
+            | Self::Commit { window, .. }
+            | Self::Disabled { window } => *window,
+        }
+    }
+}
+
+/// An event that is spawned when hovered files leave a window without being dropped.
+/// There is only a single one of these, even if multiple files were hovered.
+#[derive(Debug, Clone)]
+pub struct FileHoverCancelledEvent {
+    /// The window entity the files were hovered over.
+    pub window: Entity,
+}
+
+/// How often the [WindowRunner] updates and redraws. A singleton component, spawned by the [WindowPlugin].
+/// It can be changed at any time, e.g. to only go reactive while an editor is in the background.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateMode {
+    /// Update and redraw as fast as possible, every frame. What most games want.
+    #[default]
+    Continuous,
+    /// Idle until something happens. What editors and other tools want, to save power.
+    ///
+    /// The update schedules still run whenever the event loop wakes up: on every window event (including input),
+    /// and at the latest after `wait`, so timers and the simulation keep ticking. A redraw only happens when it is
+    /// requested with [RequestRedraw], which is done automatically for every window event.
+    Reactive {
+        /// The maximum time to wait for an event before updating anyway.
+        wait: Duration,
+    },
+}
+
+/// A singleton component, spawned by the [WindowPlugin], to request a redraw of the [PrimaryWindow] after the current update.
+///
+/// Only needed in [UpdateMode::Reactive], as [UpdateMode::Continuous] redraws every frame anyway.
+/// A property editor would e.g. request a redraw once a value changes, so the change becomes visible.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct RequestRedraw {
+    requested: bool,
+}
+
+impl RequestRedraw {
+    /// Request a redraw after the current update.
+    pub fn request(&mut self) {
+        self.requested = true;
+    }
+
+    /// Check if a redraw has been requested since the last one.
+    pub fn is_requested(&self) -> bool {
+        self.requested
+    }
+}
+
+/// Request a redraw through every [RequestRedraw] component.
+fn request_redraw(world: &mut World) {
+    for (_, redraw) in world.query_mut::<&mut RequestRedraw>() {
+        redraw.request();
+    }
+}
+
+/// Decides if a redraw is due after an update, and how long the event loop may wait for the next one.
+/// Consumes any pending [RequestRedraw].
+fn redraw_and_control_flow(world: &mut World) -> (bool, ControlFlow) {
+    let requested = world
+        .query_mut::<&mut RequestRedraw>()
+        .into_iter()
+        .fold(false, |requested, (_, redraw)| {
+            std::mem::take(&mut redraw.requested) || requested
+        });
+
+    let mode = world
+        .query::<&UpdateMode>()
+        .single_mut()
+        .map(|mode| *mode)
+        .unwrap_or_default();
+
+    match mode {
+        UpdateMode::Continuous => (true, ControlFlow::Poll),
+        UpdateMode::Reactive { wait } => (requested, ControlFlow::wait_duration(wait)),
+    }
+}
+
+/// A singleton component binding keys to the [StepMode], for stepping through the game one frame at a time while debugging.
+/// Not spawned by default, spawn it together with a [StepMode] to enable the keys.
+///
+/// The keys are handled as soon as the window event arrives, so they keep working while no frames run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepKeys {
+    /// Switches between [StepMode::Running] and [StepMode::Paused].
+    pub toggle_pause: KeyCode,
+    /// Runs a single frame, see [StepMode::StepOnce]. Pauses first, if the game is running.
+    pub step: KeyCode,
+}
+
+impl Default for StepKeys {
+    fn default() -> Self {
+        Self {
+            toggle_pause: KeyCode::Pause,
+            step: KeyCode::F10,
+        }
+    }
+}
+
+/// Update the [StepMode] if the pressed `key` is one of the [StepKeys].
+fn handle_step_keys(world: &mut World, key: KeyCode) {
+    let Ok(step_keys) = world
+        .query::<&StepKeys>()
+        .single_mut()
+        .map(|step_keys| *step_keys)
+    else {
+        return;
+    };
+
+    let mut query = world.query::<&mut StepMode>();
+    let Ok(mode) = query.single_mut() else {
+        return;
+    };
+
+    if key == step_keys.step {
+        *mode = StepMode::StepOnce;
+    } else if key == step_keys.toggle_pause {
+        *mode = match mode {
+            StepMode::Running => StepMode::Paused,
+            StepMode::Paused | StepMode::StepOnce => StepMode::Running,
+        };
+    }
+}
+
+/// Finds the entity of the window with the given id.
+fn window_entity(world: &mut World, window_id: WindowId) -> Option<Entity> {
+    world
+        .query_mut::<&Window>()
+        .into_iter()
+        .find(|(_, window)| window.handle.id() == window_id)
+        .map(|(entity, _)| entity)
 }
 
 /// The window [ScheduleRunner] for the [App].
+///
+/// The update schedules run whenever the event loop is about to wait, after which a redraw of the [PrimaryWindow] is requested.
+/// The render schedule runs in response to that redraw request. In [UpdateMode::Reactive], the event loop instead waits for events,
+/// and only redraws when a [RequestRedraw] is made.
+///
+/// While the [StepMode] is paused, the update schedules are skipped and the event loop waits for events.
+/// Redraws requested by the platform still run the render schedule, so the paused frame stays visible.
+///
+/// If a window couldn't be created during startup, or the event loop fails, the error is logged and the runner returns,
+/// so the app exits cleanly instead of panicking.
 #[derive(Default, Debug)]
 pub struct WindowRunner(Arc<Mutex<Option<EventLoop<()>>>>);
 
 impl ScheduleRunner for WindowRunner {
     fn run_app(&mut self, app: &mut App) {
-        let mut startup = app.startup.build();
-        let mut pre_update = app.pre_update.build();
-        let mut update = app.update.build();
-        let mut post_update = app.post_update.build();
+        let mut schedules = Schedules::new(app);
+
+        schedules.run_startup(app);
 
-        for system in &app.direct_startup {
-            system(&mut app.world);
+        if let Err(error) = ensure_windows_created(&mut app.world) {
+            error!("{error}");
+            return;
         }
-        startup.execute((&mut app.world,)).expect("Startup failed");
 
         // take the event loop, so we can run it.
-        let event_loop = std::mem::take(&mut *self.0.lock().unwrap()).unwrap();
+        let Some(event_loop) = std::mem::take(&mut *self.0.lock().unwrap()) else {
+            error!("The event loop already ran, the window runner can only run once");
+            return;
+        };
 
         event_loop.set_control_flow(ControlFlow::Poll);
 
-        event_loop
-            .run(move |event, elwt| match event {
-                WinitEvent::WindowEvent {
-                    event: WindowEvent::CloseRequested,
-                    ..
-                } => {
-                    debug!("Window closed!");
-                    elwt.exit();
+        let result = event_loop.run(move |event, elwt| match event {
+            WinitEvent::WindowEvent {
+                event: WindowEvent::CloseRequested,
+                ..
+            } => {
+                debug!("Window closed!");
+                elwt.exit();
+            }
+            WinitEvent::AboutToWait => {
+                if !StepMode::advance(&mut app.world) {
+                    elwt.set_control_flow(ControlFlow::Wait);
+                    return;
                 }
-                WinitEvent::AboutToWait => {
-                    for system in &app.direct_pre_update {
-                        system(&mut app.world);
-                    }
-                    pre_update
-                        .execute((&mut app.world,))
-                        .expect("Pre-update failed");
 
-                    for system in &app.direct_update {
-                        system(&mut app.world);
-                    }
-                    update.execute((&mut app.world,)).expect("Update failed");
+                schedules.run_update(app);
+
+                let (redraw, control_flow) = redraw_and_control_flow(&mut app.world);
+                elwt.set_control_flow(control_flow);
 
-                    for system in &app.direct_post_update {
-                        system(&mut app.world);
+                // rendering happens in response to the redraw request
+                if redraw {
+                    for (_, window) in app.world.query_mut::<&Window>().with::<&PrimaryWindow>() {
+                        window.handle.request_redraw();
                     }
-                    post_update
-                        .execute((&mut app.world,))
-                        .expect("Post-update failed");
                 }
-                WinitEvent::WindowEvent {
-                    event: WindowEvent::RedrawRequested,
-                    ..
-                } => {}
-                WinitEvent::WindowEvent {
-                    event: WindowEvent::Resized(size),
-                    ..
-                } => {
-                    app.world.spawn((
-                        Event::default(),
-                        WindowResizeEvent {
-                            width: size.width,
-                            height: size.height,
-                        },
-                    ));
-                }
-                WinitEvent::WindowEvent {
-                    event: WindowEvent::KeyboardInput { event, .. },
-                    ..
-                } => {
-                    if event.state == ElementState::Pressed {
-                        if let PhysicalKey::Code(code) = event.physical_key {
-                            app.world
-                                .spawn((Event::default(), KeyPressEvent { key: code }));
-                        }
-                    } else if let PhysicalKey::Code(code) = event.physical_key {
-                        app.world
-                            .spawn((Event::default(), KeyReleaseEvent { key: code }));
+            }
+            WinitEvent::WindowEvent {
+                event: WindowEvent::RedrawRequested,
+                ..
+            } => {
+                schedules.run_render(app);
+            }
+            WinitEvent::WindowEvent { event, window_id } => {
+                handle_window_event(&mut app.world, window_id, event);
+            }
+            WinitEvent::DeviceEvent { event, .. } => {
+                handle_device_event(&mut app.world, event);
+            }
+            _ => {}
+        });
+
+        if let Err(error) = result {
+            error!("{}", WindowError::Run(error));
+        }
+    }
+}
+
+/// A [ScheduleRunner] for embedding the [App] in a host application that owns the winit event loop,
+/// e.g. an editor showing the game in a viewport. Use it with the [EmbeddedWindowPlugin] instead of the [WindowPlugin].
+///
+/// With this runner, [App::run] only runs startup and returns. After that, the host drives the app from its own event loop:
+/// forward the events of the game's window with [EmbeddedRunner::handle_event], and call [EmbeddedRunner::tick] once per host frame.
+///
+/// ```ignore
+/// let runner = EmbeddedRunner::default();
+/// app.add_plugin(EmbeddedWindowPlugin::new(viewport.clone()))
+///     .set_runner(runner.clone())
+///     .run();
+///
+/// event_loop.run(move |event, elwt| match event {
+///     WinitEvent::WindowEvent { event, window_id } if window_id == viewport.id() => {
+///         runner.handle_event(&mut app, window_id, event);
+///     }
+///     WinitEvent::AboutToWait => {
+///         if runner.tick(&mut app) {
+///             viewport.request_redraw();
+///         }
+///     }
+///     _ => {}
+/// })?;
+/// ```
+///
+/// # Sharing the window
+///
+/// The [Window::handle] is the host's window, shared through an `Arc`. Only one renderer can present to a window,
+/// so either the host or the engine creates a surface for it, never both. An editor that draws its own UI around the game
+/// should give the engine a child window of its own, or have the engine render to a texture instead.
+///
+/// Every call borrows the [App] mutably, so the host can't hold on to anything inside [App::world] across them.
+/// Make all calls from the thread running the event loop, as some platforms only allow windows to be used there.
+/// Closing the window and exiting the event loop is up to the host.
+#[derive(Default, Clone)]
+pub struct EmbeddedRunner(Rc<RefCell<Option<Schedules>>>);
+
+impl ScheduleRunner for EmbeddedRunner {
+    fn run_app(&mut self, app: &mut App) {
+        let mut schedules = Schedules::new(app);
+
+        schedules.run_startup(app);
+
+        *self.0.borrow_mut() = Some(schedules);
+    }
+}
+
+impl EmbeddedRunner {
+    /// Handle an event of the game's window. A [WindowEvent::RedrawRequested] runs the render schedule.
+    pub fn handle_event(&self, app: &mut App, window_id: WindowId, event: WindowEvent) {
+        if matches!(event, WindowEvent::RedrawRequested) {
+            self.render(app);
+        } else {
+            handle_window_event(&mut app.world, window_id, event);
+        }
+    }
+
+    /// Handle a device event, e.g. raw mouse motion, which keeps the [Mouse::position] tracking during drags
+    /// that leave the game's window.
+    pub fn handle_device_event(&self, app: &mut App, event: DeviceEvent) {
+        handle_device_event(&mut app.world, event);
+    }
+
+    /// Run the update schedules, and return whether the game's window should be redrawn.
+    ///
+    /// In [UpdateMode::Reactive], this is only `true` when a redraw was requested. How long to wait between ticks
+    /// is up to the host. Returns `false` without updating if the app wasn't started with [App::run] yet,
+    /// or while the [StepMode] is paused.
+    pub fn tick(&self, app: &mut App) -> bool {
+        let mut schedules = self.0.borrow_mut();
+        let Some(schedules) = schedules.as_mut() else {
+            error!("The app has to be started with `App::run` before it can tick");
+            return false;
+        };
+
+        if !StepMode::advance(&mut app.world) {
+            return false;
+        }
+
+        schedules.run_update(app);
+
+        redraw_and_control_flow(&mut app.world).0
+    }
+
+    /// Run the render schedule. Usually done through [EmbeddedRunner::handle_event] when the window is redrawn.
+    pub fn render(&self, app: &mut App) {
+        let mut schedules = self.0.borrow_mut();
+        let Some(schedules) = schedules.as_mut() else {
+            error!("The app has to be started with `App::run` before it can render");
+            return;
+        };
+
+        schedules.run_render(app);
+    }
+}
+
+/// Turns a window event into events in the `world`, and keeps the [Window] components up to date.
+/// Any event might change what is shown, so every event also requests a redraw.
+///
+/// Redraws and closing the window are up to the caller.
+fn handle_window_event(world: &mut World, window_id: WindowId, event: WindowEvent) {
+    request_redraw(world);
+
+    match event {
+        WindowEvent::Focused(focused) => {
+            for (_, window) in world.query_mut::<&mut Window>() {
+                if window.handle.id() == window_id {
+                    window.focused = focused;
+
+                    // most platforms silently drop the grab, so make sure it is actually released
+                    if !focused && window.cursor_grab != CursorGrab::None {
+                        let _ = window.handle.set_cursor_grab(CursorGrabMode::None);
+                        window.cursor_grab = CursorGrab::None;
                     }
                 }
-                WinitEvent::WindowEvent {
-                    event: WindowEvent::CursorMoved { position, .. },
-                    ..
-                } => {
-                    app.world.spawn((
-                        Event::default(),
-                        MouseMoveEvent {
-                            position: Vec2::new(position.x as f32, position.y as f32),
-                        },
-                    ));
-                }
-                WinitEvent::WindowEvent {
-                    event: WindowEvent::MouseInput { state, button, .. },
-                    ..
-                } => {
-                    if state == ElementState::Pressed {
-                        app.world
-                            .spawn((Event::default(), MousePressEvent { button }));
-                    } else {
-                        app.world
-                            .spawn((Event::default(), MouseReleaseEvent { button }));
+            }
+
+            // the release events for anything that is held down now will never arrive
+            if !focused {
+                reset_input(world);
+            }
+        }
+        WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+            world.spawn((Event::default(), WindowScaleFactorEvent { scale_factor }));
+
+            // winit's suggested size is kept, the resize that usually follows reports it
+            if let Some(entity) = window_entity(world, window_id) {
+                let size = {
+                    let mut window = world.get::<&mut Window>(entity).unwrap();
+                    window.scale_factor = scale_factor;
+                    window.handle.inner_size()
+                };
+
+                coalesce_resize(world, entity, size.width, size.height);
+            }
+        }
+        WindowEvent::Resized(size) => {
+            // minimizing resizes the window to zero
+            if size.width == 0 || size.height == 0 {
+                reset_input(world);
+            }
+
+            if let Some(entity) = window_entity(world, window_id) {
+                coalesce_resize(world, entity, size.width, size.height);
+            }
+        }
+        WindowEvent::DroppedFile(path) => {
+            if let Some(window) = window_entity(world, window_id) {
+                world.spawn((Event::default(), FileDroppedEvent { path, window }));
+            }
+        }
+        WindowEvent::HoveredFile(path) => {
+            if let Some(window) = window_entity(world, window_id) {
+                world.spawn((Event::default(), FileHoveredEvent { path, window }));
+            }
+        }
+        WindowEvent::HoveredFileCancelled => {
+            if let Some(window) = window_entity(world, window_id) {
+                world.spawn((Event::default(), FileHoverCancelledEvent { window }));
+            }
+        }
+        WindowEvent::Ime(ime) => {
+            if let Some(window) = window_entity(world, window_id) {
+                world.spawn((Event::default(), ImeEvent::new(ime, window)));
+            }
+        }
+        WindowEvent::KeyboardInput { event, .. } => {
+            if event.state == ElementState::Pressed {
+                if let PhysicalKey::Code(code) = event.physical_key {
+                    for (_, keys) in world.query_mut::<&mut Keys>() {
+                        keys.learn_label(code, &event.logical_key);
                     }
+                    if !event.repeat {
+                        handle_step_keys(world, code);
+                    }
+                    world.spawn((Event::default(), KeyPressEvent { key: code }));
                 }
-                WinitEvent::WindowEvent {
-                    event: WindowEvent::MouseWheel { delta, .. },
-                    ..
-                } => {
-                    app.world.spawn((
-                        Event::default(),
-                        MouseScrollEvent {
-                            delta: match delta {
-                                // TODO: make this a bit more accurate
-                                MouseScrollDelta::LineDelta(x, y) => Vec2::new(x as f32, y as f32),
-                                MouseScrollDelta::PixelDelta(pos) => {
-                                    Vec2::new(pos.x as f32, pos.y as f32)
-                                }
-                            },
-                        },
-                    ));
-                }
-                _ => {}
-            })
-            .expect("Failed to run event loop");
+            } else if let PhysicalKey::Code(code) = event.physical_key {
+                world.spawn((Event::default(), KeyReleaseEvent { key: code }));
+            }
+        }
+        WindowEvent::CursorMoved { position, .. } => {
+            coalesce_mouse_move(world, Vec2::new(position.x as f32, position.y as f32));
+        }
+        WindowEvent::CursorEntered { .. } => {
+            if let Some(window) = window_entity(world, window_id) {
+                coalesce_mouse_inside(world, window, true);
+            }
+        }
+        WindowEvent::CursorLeft { .. } => {
+            if let Some(window) = window_entity(world, window_id) {
+                coalesce_mouse_inside(world, window, false);
+            }
+        }
+        WindowEvent::MouseInput { state, button, .. } => {
+            if state == ElementState::Pressed {
+                world.spawn((Event::default(), MousePressEvent { button }));
+            } else {
+                world.spawn((Event::default(), MouseReleaseEvent { button }));
+            }
+        }
+        WindowEvent::MouseWheel { delta, .. } => {
+            world.spawn((
+                Event::default(),
+                MouseScrollEvent {
+                    delta: match delta {
+                        // TODO: make this a bit more accurate
+                        MouseScrollDelta::LineDelta(x, y) => Vec2::new(x as f32, y as f32),
+                        MouseScrollDelta::PixelDelta(pos) => Vec2::new(pos.x as f32, pos.y as f32),
+                    },
+                },
+            ));
+        }
+        _ => {}
+    }
+}
+
+/// Turns raw mouse motion into a [MouseMotionEvent], while a drag is outside the window.
+/// Inside the window the cursor moves are reported instead, so the raw motion is ignored.
+fn handle_device_event(world: &mut World, event: DeviceEvent) {
+    let DeviceEvent::MouseMotion { delta } = event else {
+        return;
+    };
+
+    let dragging = world
+        .query_mut::<&Mouse>()
+        .into_iter()
+        .any(|(_, mouse)| mouse.is_dragging_outside());
+    if dragging {
+        request_redraw(world);
+        coalesce_mouse_motion(world, Vec2::new(delta.0 as f32, delta.1 as f32));
     }
 }
 
+/// Returns an error if any [WindowConfig] didn't get a [Window] during startup.
+/// The reason has already been logged by the startup system that tried to create it.
+fn ensure_windows_created(world: &mut World) -> Result<()> {
+    let missing = world
+        .query_mut::<&WindowConfig>()
+        .without::<&Window>()
+        .into_iter()
+        .next()
+        .map(|(_, config)| config.title.clone());
+
+    match missing {
+        Some(title) => Err(WindowError::NotCreated(title)),
+        None => Ok(()),
+    }
+}
+
+/// Information about a monitor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorInfo {
+    /// The human readable name of the monitor, if the platform provides one.
+    pub name: Option<String>,
+    /// The width of the monitor. In pixels.
+    pub width: u32,
+    /// The height of the monitor. In pixels.
+    pub height: u32,
+    /// The refresh rate of the monitor. In millihertz.
+    pub refresh_rate_millihertz: Option<u32>,
+}
+
+impl From<MonitorHandle> for MonitorInfo {
+    fn from(monitor: MonitorHandle) -> Self {
+        let size = monitor.size();
+
+        Self {
+            name: monitor.name(),
+            width: size.width,
+            height: size.height,
+            refresh_rate_millihertz: monitor.refresh_rate_millihertz(),
+        }
+    }
+}
+
+/// How the cursor is grabbed by a [Window].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CursorGrab {
+    /// The cursor moves freely.
+    #[default]
+    None,
+    /// The cursor can't leave the window. Not supported on macOS.
+    Confined,
+    /// The cursor is locked in place, only the mouse motion is reported. Not supported on Windows and X11.
+    Locked,
+}
+
+impl From<CursorGrab> for CursorGrabMode {
+    fn from(grab: CursorGrab) -> Self {
+        match grab {
+            CursorGrab::None => CursorGrabMode::None,
+            CursorGrab::Confined => CursorGrabMode::Confined,
+            CursorGrab::Locked => CursorGrabMode::Locked,
+        }
+    }
+}
+
+/// An event that is spawned when the cursor grab of a window changes, see [Window::set_cursor_grab].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CursorGrabChangedEvent {
+    /// The new grab mode.
+    pub mode: CursorGrab,
+    /// The window entity.
+    pub window: Entity,
+}
+
 /// A component that represents a window.
 #[derive(Debug)]
 pub struct Window {
     /// The window handle. The reason this is an `Arc` is because it *could* be shared with the render plugin.
     pub handle: Arc<WinitWindow>,
+    /// Whether the window has input focus, kept up to date by the [WindowRunner].
+    focused: bool,
+    /// The scale factor, kept up to date by the [WindowRunner].
+    scale_factor: f64,
+    /// The current cursor grab.
+    cursor_grab: CursorGrab,
+    /// The cursor grab that was last reported with a [CursorGrabChangedEvent].
+    reported_cursor_grab: CursorGrab,
 }
 
 impl Window {
     /// Creates a new window.
     pub fn new(event_loop: &EventLoop<()>, config: WindowConfig) -> Result<Self> {
+        let size: Size = match config.size_mode {
+            SizeMode::Physical => PhysicalSize::new(config.width, config.height).into(),
+            SizeMode::Logical => LogicalSize::new(config.width, config.height).into(),
+        };
+
         let handle = WindowBuilder::new()
             .with_title(config.title)
-            .with_inner_size(PhysicalSize::new(config.width, config.height))
+            .with_inner_size(size)
+            .with_visible(config.visible)
+            .with_transparent(config.transparent)
             .build(event_loop)?;
 
-        Ok(Self {
-            handle: Arc::new(handle),
-        })
+        Ok(Self::from_handle(Arc::new(handle)))
+    }
+
+    /// Wraps a window that was created elsewhere, e.g. by a host application, see [EmbeddedWindowPlugin].
+    pub fn from_handle(handle: Arc<WinitWindow>) -> Self {
+        Self {
+            focused: handle.has_focus(),
+            scale_factor: handle.scale_factor(),
+            handle,
+            cursor_grab: CursorGrab::None,
+            reported_cursor_grab: CursorGrab::None,
+        }
+    }
+
+    /// Check if the window has input focus.
+    pub fn has_focus(&self) -> bool {
+        self.focused
+    }
+
+    /// Bring the window to the front and give it input focus.
+    ///
+    /// *Note*: This may be a no-op, as some platforms refuse to let an application steal focus
+    /// (e.g. Windows, when the user is busy in another application), and on Wayland and the web it isn't supported at all.
+    /// Use [Window::request_user_attention] as a fallback.
+    pub fn focus(&self) {
+        self.handle.focus_window();
+    }
+
+    /// Get the scale factor of the window, which maps logical pixels to physical pixels.
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Show or hide the window.
+    ///
+    /// While hidden, the app keeps running as usual, and events like resizes are still handled.
+    /// A good moment to show a window that was created hidden is after the first frame has been rendered.
+    pub fn set_visible(&self, visible: bool) {
+        self.handle.set_visible(visible);
+    }
+
+    /// Make the window borderless fullscreen, on the monitor it is currently on.
+    ///
+    /// Returns an error if the monitor can't be determined, e.g. when running headless.
+    pub fn set_borderless_fullscreen(&self) -> Result<()> {
+        let monitor = self
+            .handle
+            .current_monitor()
+            .ok_or(WindowError::NoMonitor)?;
+
+        self.handle
+            .set_fullscreen(Some(Fullscreen::Borderless(Some(monitor))));
+
+        Ok(())
+    }
+
+    /// Make the window windowed again.
+    pub fn set_windowed(&self) {
+        self.handle.set_fullscreen(None);
+    }
+
+    /// Get the monitor the window is currently on.
+    ///
+    /// Returns an error if the monitor can't be determined, e.g. when running headless.
+    pub fn current_monitor(&self) -> Result<MonitorInfo> {
+        self.handle
+            .current_monitor()
+            .map(MonitorInfo::from)
+            .ok_or(WindowError::NoMonitor)
+    }
+
+    /// Get the refresh rate of the monitor the window is currently on. In millihertz, so a 59.94Hz monitor returns `59940`.
+    ///
+    /// Returns `None` if the monitor or its refresh rate can't be determined, e.g. when running headless.
+    ///
+    /// *Note*: This is only a hint for frame pacing. Whether presenting actually waits for the display is decided by the present mode of the surface.
+    pub fn current_refresh_rate_millihertz(&self) -> Option<u32> {
+        self.handle
+            .current_monitor()
+            .and_then(|monitor| monitor.refresh_rate_millihertz())
+    }
+
+    /// Get a sensible target frame rate for the monitor the window is currently on, e.g. `144` on a 143.9Hz monitor.
+    /// Use it instead of hardcoding 60, e.g. for a frame limiter or the rate of a fixed timestep.
+    ///
+    /// Returns `None` in the same cases as [Window::current_refresh_rate_millihertz].
+    pub fn suggested_target_fps(&self) -> Option<u32> {
+        self.current_refresh_rate_millihertz()
+            .map(fps_from_millihertz)
+    }
+
+    /// Get all monitors that are available. This is empty when running headless.
+    pub fn available_monitors(&self) -> Vec<MonitorInfo> {
+        self.handle
+            .available_monitors()
+            .map(MonitorInfo::from)
+            .collect()
+    }
+
+    /// Grab the cursor, e.g. for a first person camera. A [CursorGrabChangedEvent] is spawned in the next update.
+    ///
+    /// Returns an error if the platform doesn't support the mode, in which case the grab is left unchanged.
+    /// [CursorGrab::Confined] and [CursorGrab::Locked] are each unsupported on some platforms, so try the other one as a fallback.
+    ///
+    /// *Note*: The grab is released when the window loses focus, as most platforms drop it anyway.
+    /// So grab the cursor again once the window regains focus (e.g. on a click).
+    pub fn set_cursor_grab(&mut self, mode: CursorGrab) -> Result<()> {
+        self.handle.set_cursor_grab(mode.into())?;
+        self.cursor_grab = mode;
+
+        Ok(())
+    }
+
+    /// Get the current cursor grab.
+    pub fn cursor_grab_mode(&self) -> CursorGrab {
+        self.cursor_grab
+    }
+
+    /// Allow or disallow IME, for typing e.g. Chinese, Japanese or Korean text. Disallowed by default.
+    ///
+    /// A text field would allow it while focused, and disallow it again afterwards, so the IME doesn't
+    /// pop up while playing. While allowed, [ImeEvent]s are spawned, see there for how to handle them.
+    pub fn set_ime_allowed(&self, allowed: bool) {
+        self.handle.set_ime_allowed(allowed);
+    }
+
+    /// Tell the IME where the text is being typed, so it can show its candidate box next to it instead of on top of it.
+    /// Usually the area of the caret, relative to the top left of the window. In physical pixels.
+    pub fn set_ime_cursor_area(&self, position: Vec2, size: Vec2) {
+        self.handle.set_ime_cursor_area(
+            PhysicalPosition::new(position.x, position.y),
+            PhysicalSize::new(size.x, size.y),
+        );
+    }
+
+    /// Request the user's attention, e.g. by flashing the taskbar icon.
+    /// This does nothing if the window already has focus.
+    pub fn request_user_attention(&self) {
+        self.handle
+            .request_user_attention(Some(UserAttentionType::Informational));
     }
 }
 
+/// Rounds a refresh rate in millihertz to whole frames per second.
+fn fps_from_millihertz(millihertz: u32) -> u32 {
+    (millihertz + 500) / 1000
+}
+
 /// This tag marks the primary window.
 #[derive(Debug, Default)]
 pub struct PrimaryWindow;
@@ -211,18 +1009,18 @@ pub struct PrimaryWindow;
 pub struct WindowPlugin;
 
 impl Plugin for WindowPlugin {
-    fn build(&self, app: &mut App) {
-        let event_loop = Arc::new(Mutex::new(Some(
-            EventLoop::new().expect("Failed to create event loop"),
-        )));
+    fn build(&self, app: &mut App) -> PluginResult {
+        // winit only allows a single event loop, so this also fails when the plugin is added twice
+        let event_loop = EventLoop::new()
+            .map_err(|error| PluginError::Custom(WindowError::EventLoop(error).to_string()))?;
+        let event_loop = Arc::new(Mutex::new(Some(event_loop)));
 
         app.set_runner(WindowRunner(event_loop.clone()));
 
         // spawn the primary window
         app.world.spawn((PrimaryWindow, WindowConfig::default()));
 
-        // spawn the keys
-        app.world.spawn((Keys::default(), Mouse::default())); // TODO: seperate input plugin
+        add_window_systems(app);
 
         app.add_direct_startup_system(move |world| {
             let configs = world
@@ -232,22 +1030,213 @@ impl Plugin for WindowPlugin {
                 .map(|(e, c)| (e, c.clone()))
                 .collect::<Vec<_>>();
 
+            let event_loop = event_loop.lock().unwrap();
+            let Some(event_loop) = event_loop.as_ref() else {
+                return;
+            };
+
             for (entity, config) in configs {
-                world
-                    .insert_one(
-                        entity,
-                        Window::new(event_loop.lock().unwrap().as_ref().unwrap(), config)
-                            .expect("Failed to create window"),
-                    )
-                    .unwrap();
+                // the runner reports the missing window, and exits
+                let window = match Window::new(event_loop, config) {
+                    Ok(window) => window,
+                    Err(error) => {
+                        error!("{error}");
+                        continue;
+                    }
+                };
+
+                // so render code knows the scale factor from the start
+                world.spawn((
+                    Event::default(),
+                    WindowScaleFactorEvent {
+                        scale_factor: window.scale_factor(),
+                    },
+                ));
+
+                // the config was just queried, so the entity exists
+                let _ = world.insert_one(entity, window);
             }
         });
 
-        app.add_update_system(handle_input);
+        Ok(())
+    }
+}
+
+/// The window plugin for embedding the [App] in a host application, see [EmbeddedRunner].
+///
+/// Instead of creating a window, it uses the host's `window` as the [PrimaryWindow].
+#[derive(Debug)]
+pub struct EmbeddedWindowPlugin {
+    window: Arc<WinitWindow>,
+}
+
+impl EmbeddedWindowPlugin {
+    /// Creates a new [EmbeddedWindowPlugin] for the host's `window`.
+    pub fn new(window: Arc<WinitWindow>) -> Self {
+        Self { window }
+    }
+}
+
+impl Plugin for EmbeddedWindowPlugin {
+    fn build(&self, app: &mut App) -> PluginResult {
+        let window = Window::from_handle(self.window.clone());
+
+        app.world.spawn((
+            Event::default(),
+            WindowScaleFactorEvent {
+                scale_factor: window.scale_factor(),
+            },
+        ));
+        app.world.spawn((PrimaryWindow, window));
+
+        add_window_systems(app);
+
+        Ok(())
+    }
+}
+
+/// Spawns the input and redraw singletons, and adds the systems shared by the [WindowPlugin] and [EmbeddedWindowPlugin].
+fn add_window_systems(app: &mut App) {
+    // spawn the keys
+    app.world
+        .spawn((Keys::default(), Mouse::default(), InputRecorder::default())); // TODO: seperate input plugin
+
+    app.world
+        .spawn((UpdateMode::default(), RequestRedraw::default()));
+
+    // the windows are real OS windows, so they survive a world swap along with the input
+    app.register_resource::<PrimaryWindow>()
+        .register_resource::<Window>()
+        .register_resource::<Keys>()
+        .register_resource::<UpdateMode>();
+
+    app.add_direct_pre_update_system(record_input);
+    app.add_update_system(handle_input);
+    app.add_update_system(report_cursor_grab);
+    // plugins are built after the user added their systems, so this runs after every other post-update system
+    app.add_post_update_system(clear_mouse_scroll);
+}
+
+/// Resets all [Keys] and [Mouse] components, so no input stays stuck when the window loses focus or is minimized.
+/// The cleared keys and buttons are *not* reported as just released.
+pub fn reset_input(world: &mut World) {
+    for (_, keys) in world.query_mut::<&mut Keys>() {
+        keys.reset();
+    }
+
+    for (_, mouse) in world.query_mut::<&mut Mouse>() {
+        mouse.reset();
+    }
+}
+
+/// Spawns a [MouseMoveEvent], or updates the one that is still pending, so a burst of moves within a frame results in a single event.
+pub(crate) fn coalesce_mouse_move(world: &mut World, position: Vec2) -> Entity {
+    for (entity, (e, mouse_move)) in world.query_mut::<(&Event, &mut MouseMoveEvent)>() {
+        if e.status == EventStatus::NotHandled {
+            mouse_move.delta += position - mouse_move.position;
+            mouse_move.position = position;
+            return entity;
+        }
+    }
+
+    let last_position = world
+        .query_mut::<&Mouse>()
+        .into_iter()
+        .next()
+        .map_or(position, |(_, mouse)| mouse.position());
+
+    world.spawn((
+        Event::default(),
+        MouseMoveEvent {
+            position,
+            delta: position - last_position,
+        },
+    ))
+}
+
+/// Spawns a [MouseMotionEvent], or adds to the one that is still pending, so all raw motion of a frame results in a single event.
+pub(crate) fn coalesce_mouse_motion(world: &mut World, delta: Vec2) -> Entity {
+    for (entity, (e, motion)) in world.query_mut::<(&Event, &mut MouseMotionEvent)>() {
+        if e.status == EventStatus::NotHandled {
+            motion.delta += delta;
+            return entity;
+        }
+    }
+
+    world.spawn((Event::default(), MouseMotionEvent { delta }))
+}
+
+/// Spawns a [MouseInsideEvent], or updates the one that is still pending, so only the final state of a frame is reported.
+pub(crate) fn coalesce_mouse_inside(world: &mut World, window: Entity, inside: bool) -> Entity {
+    for (entity, (e, event)) in world.query_mut::<(&Event, &mut MouseInsideEvent)>() {
+        if e.status == EventStatus::NotHandled {
+            event.inside = inside;
+            event.window = window;
+            return entity;
+        }
+    }
+
+    world.spawn((Event::default(), MouseInsideEvent { inside, window }))
+}
+
+/// Spawns a [WindowResizeEvent] for `window`, or updates the one that is still pending, so only the final size of a frame is reported.
+/// The scale factor is taken from the [Window], so it has to be up to date before the resize is coalesced.
+pub(crate) fn coalesce_resize(
+    world: &mut World,
+    window: Entity,
+    width: u32,
+    height: u32,
+) -> Entity {
+    let scale_factor = world
+        .get::<&Window>(window)
+        .map_or(1.0, |window| window.scale_factor);
+
+    for (entity, (e, resize)) in world.query_mut::<(&Event, &mut WindowResizeEvent)>() {
+        if e.status == EventStatus::NotHandled && resize.window == window {
+            resize.width = width;
+            resize.height = height;
+            resize.scale_factor = scale_factor;
+            return entity;
+        }
+    }
+
+    world.spawn((
+        Event::default(),
+        WindowResizeEvent {
+            width,
+            height,
+            scale_factor,
+            window,
+        },
+    ))
+}
+
+/// System that spawns a [CursorGrabChangedEvent] for every window whose cursor grab changed since the last update.
+pub fn report_cursor_grab(world: SubWorld<&mut Window>, mut commands: Commands) {
+    for (entity, window) in world.query::<&mut Window>().iter() {
+        if window.cursor_grab != window.reported_cursor_grab {
+            window.reported_cursor_grab = window.cursor_grab;
+            commands.spawn((
+                Event::default(),
+                CursorGrabChangedEvent {
+                    mode: window.cursor_grab,
+                    window: entity,
+                },
+            ));
+        }
+    }
+}
+
+/// System that clears the scroll of the [Mouse] at the end of the frame, so [Mouse::scroll] is only set
+/// during the frame of the wheel movement.
+pub fn clear_mouse_scroll(world: SubWorld<&mut Mouse>) {
+    for (_, mouse) in world.query::<&mut Mouse>().iter() {
+        mouse.clear_scroll();
     }
 }
 
 /// System that registers input events with the [Mouse] and [Keys] components.
+/// Does nothing unless there is exactly one of each, like the [WindowPlugin] spawns.
 pub fn handle_input(
     world: SubWorld<(
         &mut Keys,
@@ -259,80 +1248,84 @@ pub fn handle_input(
         &MousePressEvent,
         &MouseReleaseEvent,
         &MouseScrollEvent,
+        &MouseMotionEvent,
+        &MouseInsideEvent,
     )>,
 ) {
-    let mut keys = world.query::<&mut Keys>();
-    let mut mouse = world.query::<&mut Mouse>();
     let mut key_press_events = world.query::<(&mut Event, &KeyPressEvent)>();
     let mut key_release_events = world.query::<(&mut Event, &KeyReleaseEvent)>();
     let mut mouse_move_events = world.query::<(&mut Event, &MouseMoveEvent)>();
     let mut mouse_press_events = world.query::<(&mut Event, &MousePressEvent)>();
     let mut mouse_release_events = world.query::<(&mut Event, &MouseReleaseEvent)>();
     let mut mouse_scroll_events = world.query::<(&mut Event, &MouseScrollEvent)>();
+    let mut mouse_motion_events = world.query::<(&mut Event, &MouseMotionEvent)>();
+    let mut mouse_inside_events = world.query::<(&mut Event, &MouseInsideEvent)>();
 
-    for (_, keys) in keys.iter() {
-        keys.update();
-    }
+    // the window plugin spawns exactly one of each
+    let (Ok(mut keys), Ok(mut mouse)) = (world.single_mut::<Keys>(), world.single_mut::<Mouse>())
+    else {
+        return;
+    };
 
-    for (_, mouse) in mouse.iter() {
-        mouse.update();
-    }
+    keys.update();
+    mouse.update();
 
     for (_, (e, key_press)) in key_press_events.iter() {
-        if e.status != EventStatus::NotHandled {
-            continue;
-        }
-        for (_, keys) in keys.iter() {
+        if e.status == EventStatus::NotHandled {
             keys.press(key_press.key);
             e.status = EventStatus::Handled;
         }
     }
 
     for (_, (e, key_release)) in key_release_events.iter() {
-        if e.status != EventStatus::NotHandled {
-            continue;
-        }
-        for (_, keys) in keys.iter() {
+        if e.status == EventStatus::NotHandled {
             keys.release(key_release.key);
             e.status = EventStatus::Handled;
         }
     }
 
-    for (_, (e, mouse_move)) in mouse_move_events.iter() {
-        if e.status != EventStatus::NotHandled {
-            continue;
+    for (_, (e, mouse_inside)) in mouse_inside_events.iter() {
+        if e.status == EventStatus::NotHandled {
+            mouse.set_inside(mouse_inside.inside);
+            e.status = EventStatus::Handled;
         }
-        for (_, mouse) in mouse.iter() {
+    }
+
+    let mut moved = false;
+    for (_, (e, mouse_move)) in mouse_move_events.iter() {
+        if e.status == EventStatus::NotHandled {
             mouse.move_to(mouse_move.position);
             e.status = EventStatus::Handled;
+            moved = true;
         }
     }
 
-    for (_, (e, mouse_press)) in mouse_press_events.iter() {
-        if e.status != EventStatus::NotHandled {
-            continue;
+    // a reported position always wins over the raw motion, which is only a best-effort guess
+    for (_, (e, mouse_motion)) in mouse_motion_events.iter() {
+        if e.status == EventStatus::NotHandled {
+            if !moved && mouse.is_dragging_outside() {
+                mouse.move_by(mouse_motion.delta);
+            }
+            e.status = EventStatus::Handled;
         }
-        for (_, mouse) in mouse.iter() {
+    }
+
+    for (_, (e, mouse_press)) in mouse_press_events.iter() {
+        if e.status == EventStatus::NotHandled {
             mouse.press(mouse_press.button);
             e.status = EventStatus::Handled;
         }
     }
 
     for (_, (e, mouse_release)) in mouse_release_events.iter() {
-        if e.status != EventStatus::NotHandled {
-            continue;
-        }
-        for (_, mouse) in mouse.iter() {
+        if e.status == EventStatus::NotHandled {
             mouse.release(mouse_release.button);
             e.status = EventStatus::Handled;
         }
     }
 
     for (_, (e, mouse_scroll)) in mouse_scroll_events.iter() {
-        if e.status != EventStatus::NotHandled {
-            continue;
-        }
-        for (_, mouse) in mouse.iter() {
+        if e.status == EventStatus::NotHandled {
             mouse.set_scroll(mouse_scroll.delta);
             e.status = EventStatus::Handled;
         }
@@ -342,6 +1335,398 @@ pub fn handle_input(
 /// Common types, traits, and functions.
 pub mod prelude {
     pub use super::{
-        input::prelude::*, Window, WindowConfig, WindowPlugin, WindowResizeEvent, WindowRunner,
+        clear_mouse_scroll, input::prelude::*, record::prelude::*, report_cursor_grab, reset_input,
+        CursorGrab, CursorGrabChangedEvent, EmbeddedRunner, EmbeddedWindowPlugin, FileDroppedEvent,
+        FileHoverCancelledEvent, FileHoveredEvent, ImeEvent, MonitorInfo, RequestRedraw, SizeMode,
+        StepKeys, UpdateMode, Window, WindowConfig, WindowError, WindowPlugin, WindowResizeEvent,
+        WindowRunner, WindowScaleFactorEvent,
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extra_mouse_buttons() {
+        let mut world = World::new();
+        let mut schedule = ScheduleBuilder::new().add_system(handle_input).build();
+
+        let input = world.spawn((Keys::default(), Mouse::default()));
+
+        world.spawn((
+            Event::default(),
+            MousePressEvent {
+                button: MouseButton::Back,
+            },
+        ));
+        world.spawn((
+            Event::default(),
+            MousePressEvent {
+                button: MouseButton::Other(7),
+            },
+        ));
+        schedule.execute((&mut world,)).unwrap();
+
+        {
+            let mouse = world.get::<&Mouse>(input).unwrap();
+            assert!(mouse.back_pressed());
+            assert!(mouse.just_pressed(MouseButton::Back));
+            assert!(!mouse.forward_pressed());
+            assert!(mouse.other_pressed(7));
+            assert!(!mouse.other_pressed(8));
+        }
+
+        world.spawn((
+            Event::default(),
+            MouseReleaseEvent {
+                button: MouseButton::Back,
+            },
+        ));
+        schedule.execute((&mut world,)).unwrap();
+
+        let mouse = world.get::<&Mouse>(input).unwrap();
+        assert!(!mouse.back_pressed());
+        assert!(mouse.just_released(MouseButton::Back));
+        assert!(!mouse.just_pressed(MouseButton::Back));
+        assert!(mouse.other_pressed(7));
+    }
+
+    #[test]
+    fn test_mouse_move_coalescing() {
+        let mut world = World::new();
+        let mut schedule = ScheduleBuilder::new().add_system(handle_input).build();
+
+        let input = world.spawn((Keys::default(), Mouse::default()));
+
+        // a burst of moves within a single frame
+        let first = coalesce_mouse_move(&mut world, Vec2::new(1.0, 1.0));
+        let second = coalesce_mouse_move(&mut world, Vec2::new(3.0, 2.0));
+        let third = coalesce_mouse_move(&mut world, Vec2::new(5.0, 4.0));
+        assert_eq!(first, second);
+        assert_eq!(first, third);
+
+        {
+            let mut moves = world.query::<&MouseMoveEvent>();
+            let moves = moves.iter().map(|(_, e)| e).collect::<Vec<_>>();
+            assert_eq!(moves.len(), 1);
+            assert_eq!(moves[0].position, Vec2::new(5.0, 4.0));
+            assert_eq!(moves[0].delta, Vec2::new(5.0, 4.0));
+        }
+
+        schedule.execute((&mut world,)).unwrap();
+        assert_eq!(
+            world.get::<&Mouse>(input).unwrap().position(),
+            Vec2::new(5.0, 4.0)
+        );
+
+        // the handled event is no longer pending, so the next frame gets a new one
+        let next = coalesce_mouse_move(&mut world, Vec2::new(6.0, 4.0));
+        assert_ne!(next, first);
+        assert_eq!(
+            world.get::<&MouseMoveEvent>(next).unwrap().delta,
+            Vec2::new(1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_drag_outside_window() {
+        let mut world = World::new();
+        let mut schedule = ScheduleBuilder::new().add_system(handle_input).build();
+
+        let input = world.spawn((Keys::default(), Mouse::default()));
+        let window = world.spawn(());
+
+        coalesce_mouse_inside(&mut world, window, true);
+        coalesce_mouse_move(&mut world, Vec2::new(10.0, 10.0));
+        world.spawn((
+            Event::default(),
+            MousePressEvent {
+                button: MouseButton::Left,
+            },
+        ));
+        schedule.execute((&mut world,)).unwrap();
+        assert!(world.get::<&Mouse>(input).unwrap().is_inside());
+
+        // the drag leaves the window, where only raw motion is reported
+        coalesce_mouse_inside(&mut world, window, false);
+        coalesce_mouse_motion(&mut world, Vec2::new(-15.0, 0.0));
+        coalesce_mouse_motion(&mut world, Vec2::new(-15.0, 2.0));
+        schedule.execute((&mut world,)).unwrap();
+        {
+            let mouse = world.get::<&Mouse>(input).unwrap();
+            assert!(!mouse.is_inside());
+            assert_eq!(mouse.position(), Vec2::new(-20.0, 12.0));
+            assert_eq!(mouse.delta(), Vec2::new(-30.0, 2.0));
+            assert_eq!(
+                mouse.clamped_position(Vec2::new(100.0, 100.0)),
+                Vec2::new(0.0, 12.0)
+            );
+        }
+
+        // back inside, the reported position wins over the raw motion
+        coalesce_mouse_inside(&mut world, window, true);
+        coalesce_mouse_move(&mut world, Vec2::new(5.0, 12.0));
+        coalesce_mouse_motion(&mut world, Vec2::new(3.0, 3.0));
+        schedule.execute((&mut world,)).unwrap();
+        {
+            let mouse = world.get::<&Mouse>(input).unwrap();
+            assert!(mouse.is_inside());
+            assert_eq!(mouse.position(), Vec2::new(5.0, 12.0));
+        }
+
+        // without a held button, raw motion outside the window is ignored
+        world.spawn((
+            Event::default(),
+            MouseReleaseEvent {
+                button: MouseButton::Left,
+            },
+        ));
+        coalesce_mouse_inside(&mut world, window, false);
+        schedule.execute((&mut world,)).unwrap();
+        coalesce_mouse_motion(&mut world, Vec2::new(-50.0, 0.0));
+        schedule.execute((&mut world,)).unwrap();
+        assert_eq!(
+            world.get::<&Mouse>(input).unwrap().position(),
+            Vec2::new(5.0, 12.0)
+        );
+    }
+
+    #[test]
+    fn test_scroll_reset() {
+        let mut world = World::new();
+        let mut update = ScheduleBuilder::new().add_system(handle_input).build();
+        let mut post_update = ScheduleBuilder::new()
+            .add_system(clear_mouse_scroll)
+            .build();
+
+        let input = world.spawn((Keys::default(), Mouse::default()));
+
+        world.spawn((
+            Event::default(),
+            MouseScrollEvent {
+                delta: Vec2::new(0.0, 2.0),
+            },
+        ));
+        update.execute((&mut world,)).unwrap();
+        assert_eq!(
+            world.get::<&Mouse>(input).unwrap().scroll(),
+            Vec2::new(0.0, 2.0)
+        );
+        post_update.execute((&mut world,)).unwrap();
+        assert_eq!(world.get::<&Mouse>(input).unwrap().scroll(), Vec2::ZERO);
+
+        // a frame without wheel movement
+        update.execute((&mut world,)).unwrap();
+        assert_eq!(world.get::<&Mouse>(input).unwrap().scroll(), Vec2::ZERO);
+        post_update.execute((&mut world,)).unwrap();
+        assert_eq!(world.get::<&Mouse>(input).unwrap().scroll(), Vec2::ZERO);
+    }
+
+    #[test]
+    fn test_focus_loss_resets_input() {
+        let mut world = World::new();
+        let mut schedule = ScheduleBuilder::new().add_system(handle_input).build();
+
+        let input = world.spawn((Keys::default(), Mouse::default()));
+
+        world.spawn((Event::default(), KeyPressEvent { key: KeyCode::KeyW }));
+        schedule.execute((&mut world,)).unwrap();
+        assert!(world.get::<&Keys>(input).unwrap().pressed(KeyCode::KeyW));
+
+        // what the runner does when the window loses focus
+        reset_input(&mut world);
+
+        let keys = world.get::<&Keys>(input).unwrap();
+        assert!(!keys.pressed(KeyCode::KeyW));
+        assert!(!keys.just_pressed(KeyCode::KeyW));
+        assert!(!keys.just_released(KeyCode::KeyW));
+    }
+
+    #[test]
+    fn test_window_config_presets() {
+        let sizes = [
+            WindowConfig::p720(),
+            WindowConfig::p1080(),
+            WindowConfig::p1440(),
+            WindowConfig::steam_deck(),
+        ]
+        .map(|config| (config.width, config.height));
+        assert_eq!(
+            sizes,
+            [(1280, 720), (1920, 1080), (2560, 1440), (1280, 800)]
+        );
+
+        // everything that isn't set keeps its default
+        let config = WindowConfig::new()
+            .with_title("Editor")
+            .with_size(1000, 1)
+            .with_aspect_ratio(4.0 / 3.0);
+        assert_eq!(config.title, "Editor");
+        assert_eq!((config.width, config.height), (1000, 750));
+        assert_eq!(config.size_mode, WindowConfig::default().size_mode);
+        assert!(config.visible);
+        assert!(!config.transparent);
+
+        let ultrawide = WindowConfig::p1440()
+            .with_size(3440, 0)
+            .with_aspect_ratio(21.0 / 9.0);
+        assert_eq!(ultrawide.height, 1474);
+    }
+
+    #[test]
+    fn test_ime_events() {
+        let mut world = World::new();
+        let window = world.spawn(());
+
+        let preedit = ImeEvent::new(Ime::Preedit("にほ".to_string(), Some((3, 3))), window);
+        assert_eq!(
+            preedit,
+            ImeEvent::Preedit {
+                text: "にほ".to_string(),
+                cursor: Some((3, 3)),
+                window
+            }
+        );
+
+        let commit = ImeEvent::new(Ime::Commit("日本".to_string()), window);
+        assert!(matches!(&commit, ImeEvent::Commit { text, .. } if text == "日本"));
+        assert_eq!(commit.window(), window);
+        assert_eq!(ImeEvent::new(Ime::Disabled, window).window(), window);
+    }
+
+    #[test]
+    fn test_step_keys() {
+        let mut world = World::new();
+        let mode = world.spawn((StepMode::Running,));
+
+        // without the keys nothing happens
+        handle_step_keys(&mut world, KeyCode::Pause);
+        assert_eq!(*world.get::<&StepMode>(mode).unwrap(), StepMode::Running);
+
+        world.spawn((StepKeys::default(),));
+        handle_step_keys(&mut world, KeyCode::Pause);
+        assert_eq!(*world.get::<&StepMode>(mode).unwrap(), StepMode::Paused);
+        handle_step_keys(&mut world, KeyCode::F10);
+        assert_eq!(*world.get::<&StepMode>(mode).unwrap(), StepMode::StepOnce);
+        handle_step_keys(&mut world, KeyCode::Pause);
+        assert_eq!(*world.get::<&StepMode>(mode).unwrap(), StepMode::Running);
+    }
+
+    #[test]
+    fn test_resize_coalescing() {
+        let mut world = World::new();
+        let window = world.spawn((PrimaryWindow,));
+        let other = world.spawn((PrimaryWindow,));
+
+        // dragging the window around in a single frame
+        let first = coalesce_resize(&mut world, window, 800, 600);
+        let second = coalesce_resize(&mut world, window, 1024, 768);
+        assert_eq!(first, second);
+        assert_ne!(coalesce_resize(&mut world, other, 640, 480), first);
+
+        {
+            let resize = world.get::<&WindowResizeEvent>(first).unwrap();
+            assert_eq!((resize.width, resize.height), (1024, 768));
+            assert_eq!(resize.scale_factor, 1.0);
+        }
+
+        // once handled, the next resize gets its own event
+        world.get::<&mut Event>(first).unwrap().status = EventStatus::Handled;
+        assert_ne!(coalesce_resize(&mut world, window, 1280, 720), first);
+    }
+
+    #[test]
+    fn test_window_creation_failure() {
+        let mut world = World::new();
+        assert!(ensure_windows_created(&mut world).is_ok());
+
+        // what the startup system leaves behind when creating the window fails
+        world.spawn((
+            PrimaryWindow,
+            WindowConfig {
+                title: "Game".to_string(),
+                ..Default::default()
+            },
+        ));
+
+        let error = ensure_windows_created(&mut world).unwrap_err();
+        assert!(matches!(error, WindowError::NotCreated(ref title) if title == "Game"));
+        assert_eq!(error.to_string(), "The window \"Game\" wasn't created");
+
+        let error = WindowError::EventLoop(EventLoopError::RecreationAttempt);
+        assert!(error.to_string().contains("is a display available?"));
+    }
+
+    #[test]
+    fn test_fps_from_millihertz() {
+        assert_eq!(fps_from_millihertz(59_940), 60);
+        assert_eq!(fps_from_millihertz(60_000), 60);
+        assert_eq!(fps_from_millihertz(143_856), 144);
+        assert_eq!(fps_from_millihertz(0), 0);
+    }
+
+    #[test]
+    fn test_reactive_redraw() {
+        let mut world = World::new();
+        let resources = world.spawn((UpdateMode::default(), RequestRedraw::default()));
+
+        let (redraw, control_flow) = redraw_and_control_flow(&mut world);
+        assert!(redraw);
+        assert_eq!(control_flow, ControlFlow::Poll);
+
+        let wait = Duration::from_secs(1);
+        *world.get::<&mut UpdateMode>(resources).unwrap() = UpdateMode::Reactive { wait };
+
+        // idle without a request
+        let (redraw, control_flow) = redraw_and_control_flow(&mut world);
+        assert!(!redraw);
+        assert!(matches!(control_flow, ControlFlow::WaitUntil(_)));
+
+        // a request redraws once
+        world
+            .get::<&mut RequestRedraw>(resources)
+            .unwrap()
+            .request();
+        assert!(redraw_and_control_flow(&mut world).0);
+        assert!(!redraw_and_control_flow(&mut world).0);
+
+        // as does any window event
+        request_redraw(&mut world);
+        assert!(redraw_and_control_flow(&mut world).0);
+    }
+
+    struct Ticks(u32);
+
+    #[test]
+    fn test_embedded_runner() {
+        let runner = EmbeddedRunner::default();
+        let mut app = App::new();
+        app.add_direct_startup_system(|world| {
+            world.spawn((Ticks(0),));
+        })
+        .add_direct_update_system(|world| {
+            for (_, ticks) in world.query_mut::<&mut Ticks>() {
+                ticks.0 += 1;
+            }
+        });
+
+        // nothing to tick before the app started
+        assert!(!runner.tick(&mut app));
+
+        app.set_runner(runner.clone()).run();
+
+        // the host drives the frames
+        for _ in 0..3 {
+            assert!(runner.tick(&mut app));
+        }
+
+        let ticks = app
+            .world
+            .query_mut::<&Ticks>()
+            .into_iter()
+            .map(|(_, ticks)| ticks.0)
+            .collect::<Vec<_>>();
+        assert_eq!(ticks, vec![3]);
+    }
+}
@@ -0,0 +1,425 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+use winit::{event::MouseButton, keyboard::KeyCode};
+
+use thndr_ecs::prelude::*;
+use thndr_event::prelude::*;
+use thndr_math::prelude::*;
+
+use crate::{
+    coalesce_mouse_inside, coalesce_mouse_motion, coalesce_mouse_move, input::prelude::*,
+    PrimaryWindow, Result,
+};
+
+/// A single input event, as recorded by the [InputRecorder].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RecordedInput {
+    /// A [KeyPressEvent].
+    KeyPress(KeyCode),
+    /// A [KeyReleaseEvent].
+    KeyRelease(KeyCode),
+    /// A [MouseMoveEvent], with the new position of the mouse.
+    MouseMove([f32; 2]),
+    /// A [MousePressEvent].
+    MousePress(MouseButton),
+    /// A [MouseReleaseEvent].
+    MouseRelease(MouseButton),
+    /// A [MouseScrollEvent], with the scroll delta.
+    MouseScroll([f32; 2]),
+    /// A [MouseMotionEvent], with the raw movement of a drag outside the window.
+    MouseMotion([f32; 2]),
+    /// A [MouseInsideEvent], with whether the cursor is inside the window now.
+    /// It is played back for the [PrimaryWindow], as entity ids differ between runs.
+    MouseInside(bool),
+}
+
+impl RecordedInput {
+    /// Spawns the input event this was recorded from.
+    fn spawn(self, world: &mut World) -> Entity {
+        let event = Event::default();
+        match self {
+            RecordedInput::KeyPress(key) => world.spawn((event, KeyPressEvent { key })),
+            RecordedInput::KeyRelease(key) => world.spawn((event, KeyReleaseEvent { key })),
+            RecordedInput::MouseMove(position) => coalesce_mouse_move(world, Vec2::from(position)),
+            RecordedInput::MousePress(button) => world.spawn((event, MousePressEvent { button })),
+            RecordedInput::MouseRelease(button) => {
+                world.spawn((event, MouseReleaseEvent { button }))
+            }
+            RecordedInput::MouseScroll(delta) => world.spawn((
+                event,
+                MouseScrollEvent {
+                    delta: Vec2::from(delta),
+                },
+            )),
+            RecordedInput::MouseMotion(delta) => coalesce_mouse_motion(world, Vec2::from(delta)),
+            RecordedInput::MouseInside(inside) => {
+                let window = world
+                    .query_mut::<()>()
+                    .with::<&PrimaryWindow>()
+                    .into_iter()
+                    .next()
+                    .map_or(Entity::DANGLING, |(entity, _)| entity);
+                coalesce_mouse_inside(world, window, inside)
+            }
+        }
+    }
+}
+
+/// A recorded stream of input, that can be saved to and loaded from disk.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputRecording {
+    /// The recorded input, together with the frame (relative to the start of the recording) it happened on.
+    /// Sorted by frame, as the [InputRecorder] records it.
+    pub inputs: Vec<(u64, RecordedInput)>,
+}
+
+impl InputRecording {
+    /// Saves the recording to a JSON file at `path`.
+    pub fn save_to(&self, path: impl AsRef<Path>) -> Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer(BufWriter::new(file), self)?;
+
+        Ok(())
+    }
+
+    /// Loads a recording from a JSON file at `path`.
+    pub fn load_from(path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(path)?;
+
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    /// Returns the input recorded on `frame`.
+    ///
+    /// The inputs are sorted by frame, so this is a binary search, and doesn't scan the whole recording.
+    pub fn inputs_at(&self, frame: u64) -> impl Iterator<Item = RecordedInput> + '_ {
+        let start = self.inputs.partition_point(|(f, _)| *f < frame);
+
+        self.inputs[start..]
+            .iter()
+            .take_while(move |(f, _)| *f == frame)
+            .map(|(_, input)| *input)
+    }
+}
+
+/// What an [InputRecorder] is currently doing.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecorderMode {
+    /// Input passes through untouched.
+    #[default]
+    Idle,
+    /// All input is appended to the recording.
+    Recording,
+    /// Real input is discarded, and the recording is played back instead.
+    Playback,
+}
+
+/// A component for recording input, and deterministically playing it back.
+///
+/// Input is recorded and replayed by [record_input], at the same point of the frame the runner
+/// spawns its input events, so `just_pressed`/`just_released` timings are reproduced exactly.
+#[derive(Debug, Default)]
+pub struct InputRecorder {
+    mode: RecorderMode,
+    frame: u64,
+    recording: InputRecording,
+}
+
+impl InputRecorder {
+    /// Get the current mode of the recorder.
+    pub fn mode(&self) -> RecorderMode {
+        self.mode
+    }
+
+    /// Get the current frame, relative to the start of the recording or playback.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Get the recording.
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+
+    /// Starts a new recording, discarding the previous one.
+    pub fn start_recording(&mut self) {
+        self.mode = RecorderMode::Recording;
+        self.frame = 0;
+        self.recording = InputRecording::default();
+    }
+
+    /// Starts playing back `recording`, from its first frame.
+    ///
+    /// The inputs are sorted by frame first, in case the recording was edited by hand. Inputs of the same frame keep their order.
+    pub fn start_playback(&mut self, mut recording: InputRecording) {
+        recording.inputs.sort_by_key(|(frame, _)| *frame);
+
+        self.mode = RecorderMode::Playback;
+        self.frame = 0;
+        self.recording = recording;
+    }
+
+    /// Stops recording or playing back, and hands back the recording.
+    pub fn stop(&mut self) -> InputRecording {
+        self.mode = RecorderMode::Idle;
+
+        std::mem::take(&mut self.recording)
+    }
+
+    /// Check if playback has gone past the last recorded input.
+    pub fn is_finished(&self) -> bool {
+        self.mode == RecorderMode::Playback
+            && self
+                .recording
+                .inputs
+                .last()
+                .is_none_or(|(frame, _)| *frame < self.frame)
+    }
+}
+
+/// Collects all input events that haven't been handled yet.
+fn pending_inputs(world: &World) -> Vec<(Entity, RecordedInput)> {
+    let mut inputs = Vec::new();
+
+    for (entity, (e, key_press)) in world.query::<(&Event, &KeyPressEvent)>().iter() {
+        if e.status == EventStatus::NotHandled {
+            inputs.push((entity, RecordedInput::KeyPress(key_press.key)));
+        }
+    }
+
+    for (entity, (e, key_release)) in world.query::<(&Event, &KeyReleaseEvent)>().iter() {
+        if e.status == EventStatus::NotHandled {
+            inputs.push((entity, RecordedInput::KeyRelease(key_release.key)));
+        }
+    }
+
+    for (entity, (e, mouse_move)) in world.query::<(&Event, &MouseMoveEvent)>().iter() {
+        if e.status == EventStatus::NotHandled {
+            inputs.push((entity, RecordedInput::MouseMove(mouse_move.position.into())));
+        }
+    }
+
+    for (entity, (e, mouse_press)) in world.query::<(&Event, &MousePressEvent)>().iter() {
+        if e.status == EventStatus::NotHandled {
+            inputs.push((entity, RecordedInput::MousePress(mouse_press.button)));
+        }
+    }
+
+    for (entity, (e, mouse_release)) in world.query::<(&Event, &MouseReleaseEvent)>().iter() {
+        if e.status == EventStatus::NotHandled {
+            inputs.push((entity, RecordedInput::MouseRelease(mouse_release.button)));
+        }
+    }
+
+    for (entity, (e, mouse_scroll)) in world.query::<(&Event, &MouseScrollEvent)>().iter() {
+        if e.status == EventStatus::NotHandled {
+            inputs.push((
+                entity,
+                RecordedInput::MouseScroll(mouse_scroll.delta.into()),
+            ));
+        }
+    }
+
+    for (entity, (e, mouse_motion)) in world.query::<(&Event, &MouseMotionEvent)>().iter() {
+        if e.status == EventStatus::NotHandled {
+            inputs.push((
+                entity,
+                RecordedInput::MouseMotion(mouse_motion.delta.into()),
+            ));
+        }
+    }
+
+    for (entity, (e, mouse_inside)) in world.query::<(&Event, &MouseInsideEvent)>().iter() {
+        if e.status == EventStatus::NotHandled {
+            inputs.push((entity, RecordedInput::MouseInside(mouse_inside.inside)));
+        }
+    }
+
+    inputs
+}
+
+/// Direct system that records or plays back input, depending on the mode of the [InputRecorder].
+///
+/// This needs to run before [crate::handle_input], the [crate::WindowPlugin] adds it as a direct pre-update system.
+/// For headless playback (e.g. in CI), add it together with [crate::handle_input] yourself.
+pub fn record_input(world: &mut World) {
+    let Some((recorder, mode, frame)) = world
+        .query_mut::<&InputRecorder>()
+        .into_iter()
+        .next()
+        .map(|(entity, recorder)| (entity, recorder.mode, recorder.frame))
+    else {
+        return;
+    };
+
+    match mode {
+        RecorderMode::Idle => return,
+        RecorderMode::Recording => {
+            let inputs = pending_inputs(world);
+
+            let mut recorder = world.get::<&mut InputRecorder>(recorder).unwrap();
+            recorder
+                .recording
+                .inputs
+                .extend(inputs.into_iter().map(|(_, input)| (frame, input)));
+        }
+        RecorderMode::Playback => {
+            // the real input is replaced by the recorded input
+            for (entity, _) in pending_inputs(world) {
+                let _ = world.despawn(entity);
+            }
+
+            let inputs = world
+                .get::<&InputRecorder>(recorder)
+                .unwrap()
+                .recording
+                .inputs_at(frame)
+                .collect::<Vec<_>>();
+
+            for input in inputs {
+                input.spawn(world);
+            }
+        }
+    }
+
+    world.get::<&mut InputRecorder>(recorder).unwrap().frame += 1;
+}
+
+/// Common types, traits, and functions.
+pub mod prelude {
+    pub use super::{record_input, InputRecorder, InputRecording, RecordedInput, RecorderMode};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_playback() {
+        let mut world = World::new();
+        let recorder = world.spawn((InputRecorder::default(),));
+        world
+            .get::<&mut InputRecorder>(recorder)
+            .unwrap()
+            .start_recording();
+
+        // frame 0: no input, frame 1: space is pressed
+        record_input(&mut world);
+        world.spawn((
+            Event::default(),
+            KeyPressEvent {
+                key: KeyCode::Space,
+            },
+        ));
+        record_input(&mut world);
+
+        let recording = world.get::<&mut InputRecorder>(recorder).unwrap().stop();
+        assert_eq!(
+            recording.inputs,
+            vec![(1, RecordedInput::KeyPress(KeyCode::Space))]
+        );
+
+        let mut world = World::new();
+        let recorder = world.spawn((InputRecorder::default(),));
+        world
+            .get::<&mut InputRecorder>(recorder)
+            .unwrap()
+            .start_playback(recording);
+
+        // real input is discarded during playback
+        world.spawn((Event::default(), KeyPressEvent { key: KeyCode::KeyA }));
+        record_input(&mut world);
+        assert_eq!(world.query::<&KeyPressEvent>().iter().count(), 0);
+
+        record_input(&mut world);
+        let keys = world
+            .query::<&KeyPressEvent>()
+            .iter()
+            .map(|(_, e)| e.key)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec![KeyCode::Space]);
+        assert!(world.get::<&InputRecorder>(recorder).unwrap().is_finished());
+    }
+
+    #[test]
+    fn test_inputs_at() {
+        let mut recorder = InputRecorder::default();
+        // edited by hand, so out of order
+        recorder.start_playback(InputRecording {
+            inputs: vec![
+                (2, RecordedInput::KeyPress(KeyCode::KeyA)),
+                (0, RecordedInput::KeyPress(KeyCode::Space)),
+                (2, RecordedInput::KeyRelease(KeyCode::KeyA)),
+            ],
+        });
+
+        let recording = recorder.recording();
+        assert_eq!(
+            recording.inputs_at(0).collect::<Vec<_>>(),
+            vec![RecordedInput::KeyPress(KeyCode::Space)]
+        );
+        assert_eq!(recording.inputs_at(1).count(), 0);
+        assert_eq!(
+            recording.inputs_at(2).collect::<Vec<_>>(),
+            vec![
+                RecordedInput::KeyPress(KeyCode::KeyA),
+                RecordedInput::KeyRelease(KeyCode::KeyA)
+            ]
+        );
+        assert!(!recorder.is_finished());
+    }
+
+    #[test]
+    fn test_record_drag_outside() {
+        let mut world = World::new();
+        let window = world.spawn((PrimaryWindow,));
+        let recorder = world.spawn((InputRecorder::default(),));
+        world
+            .get::<&mut InputRecorder>(recorder)
+            .unwrap()
+            .start_recording();
+
+        coalesce_mouse_inside(&mut world, window, false);
+        coalesce_mouse_motion(&mut world, Vec2::new(3.0, 0.0));
+        record_input(&mut world);
+
+        let recording = world.get::<&mut InputRecorder>(recorder).unwrap().stop();
+        assert_eq!(
+            recording.inputs,
+            vec![
+                (0, RecordedInput::MouseMotion([3.0, 0.0])),
+                (0, RecordedInput::MouseInside(false)),
+            ]
+        );
+
+        let mut world = World::new();
+        let window = world.spawn((PrimaryWindow,));
+        let recorder = world.spawn((InputRecorder::default(),));
+        world
+            .get::<&mut InputRecorder>(recorder)
+            .unwrap()
+            .start_playback(recording);
+
+        // the real motion is replaced by the recorded one
+        coalesce_mouse_motion(&mut world, Vec2::new(-50.0, 0.0));
+        record_input(&mut world);
+
+        let motion = world
+            .query::<&MouseMotionEvent>()
+            .iter()
+            .map(|(_, e)| e.delta)
+            .collect::<Vec<_>>();
+        assert_eq!(motion, vec![Vec2::new(3.0, 0.0)]);
+        let inside = world
+            .query::<&MouseInsideEvent>()
+            .iter()
+            .map(|(_, e)| (e.inside, e.window))
+            .collect::<Vec<_>>();
+        assert_eq!(inside, vec![(false, window)]);
+    }
+}
@@ -0,0 +1,32 @@
+//! Shows how to spawn an entity with all transform components at once, using a bundle.
+use thndr::prelude::*;
+
+fn setup(mut commands: Commands) {
+    // a bundle is a struct of components, that can be spawned just like a tuple.
+    // the ones we don't care about are simply left at their default values.
+    commands.spawn(TransformBundle {
+        position: Position::new(0.0, 1.0, 0.0),
+        scale: Scale::new(2.0, 2.0, 2.0),
+        ..Default::default()
+    });
+}
+
+// after one frame, the entity has all components, and its global transform has been computed.
+fn update(world: SubWorld<(&Position, &Rotation, &Scale, &GlobalTransform)>) {
+    let mut q = world.query::<(&Position, &Rotation, &Scale, &GlobalTransform)>();
+
+    for (entity, (position, rotation, scale, global_transform)) in q.iter() {
+        println!(
+            "Entity {:?} has position {:?}, rotation {:?}, scale {:?} and global transform {:?}",
+            entity, position, rotation, scale, global_transform
+        );
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugin(TransformPlugin::default())
+        .add_startup_system(setup)
+        .add_update_system(update)
+        .run();
+}
@@ -43,7 +43,9 @@ fn update(world: SubWorld<(&GlobalTransform, &&'static str)>) {
 
 fn main() {
     App::new()
-        .add_plugin(TransformPlugin)
+        // the root has a non-uniform scale, which would shear rotated children.
+        // use `PropagationMode::ScaleSeparated` to prevent that.
+        .add_plugin(TransformPlugin::default())
         // we use a direct system here, as we need full access to the world (using commands we would not get the entity id, which we need to attach entities to each other)
         .add_direct_startup_system(setup) // a direct system simply gets full access to the world, with no restrictions
         .add_update_system(update)
@@ -0,0 +1,30 @@
+//! Shows how to react to an event once, using an observer instead of a full system.
+
+use thndr::prelude::*;
+
+/// An event that is sent once a level has finished loading.
+#[derive(Debug, Default, Clone)]
+struct LevelLoadedEvent {
+    name: String,
+}
+
+fn load_level(mut commands: Commands) {
+    commands.spawn(EventBundle {
+        payload: LevelLoadedEvent {
+            name: "Forest".to_string(),
+        },
+        ..Default::default()
+    });
+}
+
+fn main() {
+    App::new()
+        .add_plugin(EventCleanupPlugin)
+        .add_startup_system(load_level)
+        // the observer gets full access to the world, so it can spawn the player right away
+        .add_observer(|event: &LevelLoadedEvent, world: &mut World| {
+            println!("Level {:?} loaded, spawning the player!", event.name);
+            world.spawn((Position::new(0.0, 0.0, 0.0),));
+        })
+        .run();
+}
@@ -0,0 +1,32 @@
+//! Shows how to query components that only some entities have, with `Option`.
+use thndr::prelude::*;
+
+/// A color to tint a sprite with. Most sprites don't have one, and are drawn as is.
+struct Tint(Color);
+
+fn setup(mut commands: Commands) {
+    commands.spawn((Position::new(0.0, 0.0, 0.0), "Plain"));
+    commands.spawn((Position::new(1.0, 0.0, 0.0), "Red", Tint(Color::RED)));
+    commands.spawn((Position::new(2.0, 0.0, 0.0), "Also plain"));
+}
+
+// `Option<&Tint>` matches every entity with a position, whether it has a tint or not.
+// the sub world lists it the same way, so the system only gets read access to the tint.
+// this replaces two separate queries, one with `has::<Tint>()` and one with `lacks::<Tint>()`.
+fn draw(world: SubWorld<(&Position, &&'static str, Option<&Tint>)>) {
+    let mut q = world.query::<(&Position, &&str, Option<&Tint>)>();
+
+    for (_, (position, name, tint)) in q.iter() {
+        // fall back to white for the entities without a tint
+        let color = tint.map_or(Color::WHITE, |tint| tint.0);
+
+        println!("Drawing {name} at {:?} with color {:?}", position.0, color);
+    }
+}
+
+fn main() {
+    App::new()
+        .add_startup_system(setup)
+        .add_update_system(draw)
+        .run();
+}
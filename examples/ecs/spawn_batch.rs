@@ -0,0 +1,57 @@
+//! Compares spawning 50k entities one by one with spawning them as a single batch.
+//! Run it with `--release`, the debug numbers aren't meaningful.
+use std::time::Instant;
+
+use thndr::prelude::*;
+
+const TILES: u32 = 50_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Tile {
+    x: u32,
+    y: u32,
+}
+
+fn spawn_one_by_one(mut commands: Commands) {
+    for i in 0..TILES {
+        commands.spawn((
+            Tile {
+                x: i % 256,
+                y: i / 256,
+            },
+            Position::default(),
+        ));
+    }
+}
+
+fn spawn_batched(mut commands: Commands) {
+    commands.spawn_batch((0..TILES).map(|i| {
+        (
+            Tile {
+                x: i % 256,
+                y: i / 256,
+            },
+            Position::default(),
+        )
+    }));
+}
+
+/// Runs the system once on an empty world, including the flush, and returns the time it took.
+fn measure(schedule: &mut Schedule) -> f64 {
+    let mut world = World::new();
+
+    let start = Instant::now();
+    schedule.execute((&mut world,)).unwrap();
+    let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+
+    assert_eq!(world.query::<&Tile>().iter().count(), TILES as usize);
+    elapsed
+}
+
+fn main() {
+    let mut one_by_one = ScheduleBuilder::new().add_system(spawn_one_by_one).build();
+    let mut batched = ScheduleBuilder::new().add_system(spawn_batched).build();
+
+    println!("one by one: {:.2}ms", measure(&mut one_by_one));
+    println!("batched:    {:.2}ms", measure(&mut batched));
+}
@@ -7,7 +7,7 @@ fn update() {
 
 fn main() {
     App::new()
-        .add_plugin(TracingPlugin)
+        .add_plugin(TracingPlugin::default())
         .add_update_system(update)
         .run();
 }
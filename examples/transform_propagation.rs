@@ -0,0 +1,86 @@
+//! Compares the transform propagation running serially, adaptively and always in parallel, on deep and wide trees.
+//! Run it with `--release`, the debug numbers aren't meaningful.
+use std::{cell::Cell, rc::Rc, time::Instant};
+
+use thndr::prelude::*;
+
+const FRAMES: u32 = 100;
+
+/// A few long chains of entities, each the child of the previous one.
+fn spawn_deep(world: &mut World) {
+    for _ in 0..10 {
+        let mut parent = world.spawn(TransformBundle::default());
+        for _ in 0..200 {
+            let child = world.spawn(TransformBundle {
+                position: Position::new(0.0, 1.0, 0.0),
+                ..Default::default()
+            });
+            world.attach::<Tree>(child, parent).unwrap();
+            parent = child;
+        }
+    }
+}
+
+/// Many roots, each with a handful of children.
+fn spawn_wide(world: &mut World) {
+    for _ in 0..200 {
+        let parent = world.spawn(TransformBundle::default());
+        for _ in 0..50 {
+            let child = world.spawn(TransformBundle {
+                position: Position::new(1.0, 0.0, 0.0),
+                ..Default::default()
+            });
+            world.attach::<Tree>(child, parent).unwrap();
+        }
+    }
+}
+
+/// Runs the startup and a warm-up frame, then measures the average duration of a frame.
+struct BenchRunner(Rc<Cell<f64>>);
+
+impl ScheduleRunner for BenchRunner {
+    fn run_app(&mut self, app: &mut App) {
+        let mut schedules = Schedules::new(app);
+        schedules.run_startup(app);
+        schedules.run_update(app);
+
+        let start = Instant::now();
+        for _ in 0..FRAMES {
+            schedules.run_update(app);
+        }
+
+        self.0
+            .set(start.elapsed().as_secs_f64() * 1000.0 / FRAMES as f64);
+    }
+}
+
+/// Returns the average frame time in milliseconds, for the tree spawned by `spawn`.
+fn measure(spawn: fn(&mut World), parallel_threshold: usize) -> f64 {
+    let result = Rc::new(Cell::new(0.0));
+
+    App::new()
+        .add_plugin(TransformPlugin {
+            parallel_threshold,
+            ..Default::default()
+        })
+        .add_direct_startup_system(spawn)
+        .set_runner(BenchRunner(result.clone()))
+        .run();
+
+    result.get()
+}
+
+fn main() {
+    let thresholds = [
+        ("serial:         ", usize::MAX),
+        ("adaptive:       ", DEFAULT_PARALLEL_THRESHOLD),
+        ("always parallel:", 0),
+    ];
+
+    for (tree, spawn) in [("deep", spawn_deep as fn(&mut World)), ("wide", spawn_wide)] {
+        println!("{tree} tree:");
+        for (name, threshold) in thresholds {
+            println!("  {name} {:.3}ms", measure(spawn, threshold));
+        }
+    }
+}
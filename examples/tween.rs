@@ -0,0 +1,32 @@
+//! A basic example of how to animate a value with a tween.
+use thndr::prelude::*;
+
+fn setup(mut commands: Commands) {
+    // a menu sliding in from the left, then bouncing back and forth forever
+    let start = Position::new(-10.0, 0.0, 0.0);
+    let end = Position::new(0.0, 0.0, 0.0);
+
+    commands.spawn((
+        start,
+        Tween::new(start, end, 0.5)
+            .with_easing(Easing::EaseOut)
+            .then(
+                Tween::new(end, Position::new(1.0, 0.0, 0.0), 1.0).with_mode(TweenMode::PingPong),
+            ),
+    ));
+}
+
+fn update(world: SubWorld<&Position>) {
+    for (_, position) in world.query::<&Position>().iter() {
+        println!("Position: {}", position.x);
+    }
+}
+
+fn main() {
+    App::new()
+        .add_plugin(TimePlugin)
+        .add_plugin(TweenPlugin)
+        .add_startup_system(setup)
+        .add_update_system(update)
+        .run();
+}